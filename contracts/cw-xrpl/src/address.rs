@@ -1,8 +1,23 @@
-use crate::{error::ContractError, state::PROHIBITED_XRPL_ADDRESSES};
+use crate::{
+    error::ContractError,
+    state::{XRPLNetwork, PROHIBITED_XRPL_ADDRESSES},
+};
 use bs58::Alphabet;
 use cosmwasm_std::Storage;
 use sha2::{Digest, Sha256};
 
+const X_ADDRESS_MAINNET_PREFIX: [u8; 2] = [0x05, 0x44];
+const X_ADDRESS_TESTNET_PREFIX: [u8; 2] = [0x04, 0x93];
+
+impl XRPLNetwork {
+    const fn x_address_prefix(&self) -> [u8; 2] {
+        match self {
+            Self::Mainnet => X_ADDRESS_MAINNET_PREFIX,
+            Self::Testnet => X_ADDRESS_TESTNET_PREFIX,
+        }
+    }
+}
+
 pub fn validate_xrpl_address_format(address: &str) -> Result<(), ContractError> {
     // We need to use the base58 dictionary for ripple which is rpshnaf39wBUDNEGHJKLM4PQRST7VWXYZ2bcdeCg65jkm8oFqi1tuvAxyz
     // To understand this alphabet, see https://xrpl.org/base58-encodings.html#ripple-base58-alphabet
@@ -45,6 +60,141 @@ pub fn checksum(data: &[u8]) -> Vec<u8> {
     Sha256::digest(Sha256::digest(data)).to_vec()
 }
 
+// X-addresses encode a classic address together with an optional destination tag (https://xrpl.org/addresses.html#x-addresses)
+// They always start with X (mainnet) or T (testnet), while classic addresses always start with r
+pub fn is_x_address(address: &str) -> bool {
+    address.starts_with('X') || address.starts_with('T')
+}
+
+// Decodes an X-address of the given network and returns the classic address and the destination tag that it encodes
+pub fn decode_x_address(
+    address: &str,
+    network: &XRPLNetwork,
+) -> Result<(String, Option<u32>), ContractError> {
+    let invalid_address = || ContractError::InvalidXRPLAddress {
+        address: address.to_string(),
+    };
+
+    let data = bs58::decode(address)
+        .with_alphabet(Alphabet::RIPPLE)
+        .into_vec()
+        .map_err(|_| invalid_address())?;
+
+    // A decoded X-address is 35 bytes long: 2 bytes for the network prefix, 20 bytes for the account ID,
+    // 1 byte for the tag flag, 8 bytes for the tag (little endian) and 4 bytes for the checksum.
+    if data.len() != 35
+        || (data[..2] != X_ADDRESS_MAINNET_PREFIX && data[..2] != X_ADDRESS_TESTNET_PREFIX)
+    {
+        return Err(invalid_address());
+    }
+
+    // Same checksum as classic addresses, over the first 31 bytes
+    if checksum(&data[..31])[..4] != data[31..] {
+        return Err(invalid_address());
+    }
+
+    // A valid X-address of the other network would send the funds to an account the user doesn't expect
+    if data[..2] != network.x_address_prefix() {
+        return Err(ContractError::XAddressWrongNetwork {
+            address: address.to_string(),
+        });
+    }
+
+    let mut tag_bytes = [0u8; 8];
+    tag_bytes.copy_from_slice(&data[23..31]);
+    let tag = u64::from_le_bytes(tag_bytes);
+
+    // XRPL destination tags are 32 bits, the remaining bytes are reserved and must be 0
+    let destination_tag = match data[22] {
+        0 if tag == 0 => None,
+        1 => Some(u32::try_from(tag).map_err(|_| invalid_address())?),
+        _ => return Err(invalid_address()),
+    };
+
+    Ok((encode_classic_address(&data[2..22]), destination_tag))
+}
+
+// Encodes a classic address and an optional destination tag as an X-address of the given network
+pub fn encode_x_address(
+    classic_address: &str,
+    destination_tag: Option<u32>,
+    network: &XRPLNetwork,
+) -> Result<String, ContractError> {
+    validate_xrpl_address_format(classic_address)?;
+    let account_id = bs58::decode(classic_address)
+        .with_alphabet(Alphabet::RIPPLE)
+        .into_vec()
+        .map_err(|_| ContractError::InvalidXRPLAddress {
+            address: classic_address.to_string(),
+        })?;
+
+    let mut data = network.x_address_prefix().to_vec();
+    data.extend_from_slice(&account_id[1..21]);
+    match destination_tag {
+        Some(tag) => {
+            data.push(1);
+            data.extend_from_slice(&u64::from(tag).to_le_bytes());
+        }
+        None => {
+            data.push(0);
+            data.extend_from_slice(&[0u8; 8]);
+        }
+    }
+    let checksum = checksum(&data);
+    data.extend_from_slice(&checksum[..4]);
+
+    Ok(bs58::encode(data)
+        .with_alphabet(Alphabet::RIPPLE)
+        .into_string())
+}
+
+// Encodes a 20 bytes account ID as a classic address: version byte (0) + account ID + checksum
+fn encode_classic_address(account_id: &[u8]) -> String {
+    let mut data = vec![0u8];
+    data.extend_from_slice(account_id);
+    let checksum = checksum(&data);
+    data.extend_from_slice(&checksum[..4]);
+
+    bs58::encode(data)
+        .with_alphabet(Alphabet::RIPPLE)
+        .into_string()
+}
+
+// Returns the classic address of an XRPL address, which can be a classic address or an X-address of the given network.
+// The destination tag of X-addresses is dropped, so this must only be used where tags are meaningless (relayers, prohibited addresses...)
+pub fn normalize_xrpl_address(
+    address: &str,
+    network: &XRPLNetwork,
+) -> Result<String, ContractError> {
+    if is_x_address(address) {
+        let (classic_address, _) = decode_x_address(address, network)?;
+        return Ok(classic_address);
+    }
+
+    validate_xrpl_address_format(address)?;
+    Ok(address.to_string())
+}
+
+// Recipients can be classic addresses with an optional destination tag or X-addresses that already include it.
+// Returns the classic address and the destination tag that must be used in the XRPL payment
+pub fn resolve_xrpl_recipient(
+    recipient: String,
+    destination_tag: Option<u32>,
+    network: &XRPLNetwork,
+) -> Result<(String, Option<u32>), ContractError> {
+    if !is_x_address(&recipient) {
+        return Ok((recipient, destination_tag));
+    }
+
+    let (classic_address, x_address_tag) = decode_x_address(&recipient, network)?;
+    match (x_address_tag, destination_tag) {
+        (Some(x_address_tag), Some(destination_tag)) if x_address_tag != destination_tag => {
+            Err(ContractError::DestinationTagMismatch {})
+        }
+        _ => Ok((classic_address, x_address_tag.or(destination_tag))),
+    }
+}
+
 pub fn validate_xrpl_address_is_not_prohibited(
     storage: &dyn Storage,
     address: String,
@@ -55,9 +205,14 @@ pub fn validate_xrpl_address_is_not_prohibited(
     Ok(())
 }
 
-// Checks that address is a valid XRPL address and that is not in the list of prohibited addresses
-pub fn validate_xrpl_address(storage: &dyn Storage, address: String) -> Result<(), ContractError> {
-    validate_xrpl_address_format(&address)?;
-    validate_xrpl_address_is_not_prohibited(storage, address)?;
-    Ok(())
+// Checks that address is a valid XRPL address and that is not in the list of prohibited addresses.
+// Returns the classic address, which is the one that must be stored and compared
+pub fn validate_xrpl_address(
+    storage: &dyn Storage,
+    address: String,
+    network: &XRPLNetwork,
+) -> Result<String, ContractError> {
+    let address = normalize_xrpl_address(&address, network)?;
+    validate_xrpl_address_is_not_prohibited(storage, address.clone())?;
+    Ok(address)
 }
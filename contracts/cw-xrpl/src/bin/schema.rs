@@ -1,5 +1,5 @@
 use cosmwasm_schema::write_api;
-use cw_xrpl::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use cw_xrpl::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, SudoMsg};
 
 //run cargo schema to generate
 fn main() {
@@ -8,5 +8,6 @@ fn main() {
         execute: ExecuteMsg,
         query: QueryMsg,
         migrate: MigrateMsg,
+        sudo: SudoMsg,
     }
 }
@@ -1,46 +1,67 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 
 use crate::{
-    address::{validate_xrpl_address, validate_xrpl_address_format},
+    address::{
+        normalize_xrpl_address, resolve_xrpl_recipient, validate_xrpl_address,
+        validate_xrpl_address_format,
+    },
     error::{ContractError, ContractResult},
     evidence::{
-        handle_evidence, hash_bytes, Evidence, OperationResult::TicketsAllocation,
-        TransactionResult,
+        handle_evidence, hash_bytes, index_evidences_first_seen, remove_evidences, Evidence,
+        OperationResult::TicketsAllocation, TransactionResult,
     },
     fees::{amount_after_bridge_fees, handle_fee_collection, substract_relayer_fees},
+    ibc_forward::{
+        handle_ibc_forward_reply, handle_ibc_lifecycle_complete, is_ibc_forward_reply,
+        park_untracked_ibc_forward,
+    },
+    memo::{create_memo_action_submsg, parse_memo, MemoAction},
     msg::{
-        AvailableTicketsResponse, BridgeStateResponse, CosmosTokensResponse, ExecuteMsg,
-        FeesCollectedResponse, InstantiateMsg, PendingOperationsResponse, PendingRefund,
-        PendingRefundsResponse, ProcessedTxsResponse, ProhibitedXRPLAddressesResponse, QueryMsg,
-        TransactionEvidence, TransactionEvidencesResponse, XRPLTokensResponse,
+        AllRelayerStatsResponse, AutoClaimRefundsResponse, AvailableTicketsResponse,
+        BridgeStateResponse, ConflictingEvidencesResponse, CosmosTokensResponse, Cw20HookMsg,
+        ExecuteMsg, FeesCollectedResponse, InstantiateMsg, PendingOperationsResponse,
+        PendingRefund, PendingRefundsResponse, PendingRelayerSet, PendingUniversalSwap,
+        PendingUniversalSwapsResponse, ProcessedTxsResponse, ProhibitedXRPLAddressesResponse,
+        QueryMsg, RelayerSetChangeResponse, RelayerSigningPayload, RelayerStatsResponse,
+        SignedTransactionResponse, SigningPayloadResponse, SudoMsg, TransactionEvidence,
+        TransactionEvidencesResponse, XRPLTokensResponse, XRPLTransfer,
     },
     operation::{
-        check_operation_exists, create_pending_operation, handle_operation, remove_pending_refund,
-        Operation, OperationType,
+        check_operation_exists, create_pending_operation, handle_operation, is_operation_expired,
+        is_signed_by_quorum, operation_expiration, remove_pending_refund, Operation, OperationType,
     },
-    relayer::{is_relayer, validate_relayers, Relayer},
+    refund::{handle_auto_refund_reply, is_auto_claim_enabled, is_auto_refund_reply},
+    relayer::{is_relayer, max_signers_for_threshold, validate_relayers, Relayer},
     signatures::add_signature,
     state::{
-        BridgeState, Config, ContractActions, CosmosToken, TokenState, UserType, XRPLToken,
-        AVAILABLE_TICKETS, CONFIG, COSMOS_TOKENS, FEES_COLLECTED, PENDING_OPERATIONS,
-        PENDING_REFUNDS, PENDING_ROTATE_KEYS, PENDING_TICKET_UPDATE, PROCESSED_TXS,
-        PROHIBITED_XRPL_ADDRESSES, TEMP_UNIVERSAL_SWAP, TX_EVIDENCES, USED_TICKETS_COUNTER,
+        BridgeState, Config, ContractActions, CosmosToken, CosmosTokenType, FeeDistribution,
+        RelayerSetChange, TokenState, UserType, XRPLToken, AUTO_CLAIM_REFUNDS, AVAILABLE_TICKETS,
+        CONFIG, COSMOS_TOKENS, FEES_COLLECTED, PENDING_OPERATIONS, PENDING_REFUNDS,
+        PENDING_RELAYER_SET_CHANGE, PENDING_ROTATE_KEYS, PENDING_TICKET_UPDATE,
+        PENDING_UNIVERSAL_SWAPS, PROCESSED_TXS, PROHIBITED_XRPL_ADDRESSES, RELAYER_STATS,
+        TX_EVIDENCES, TX_EVIDENCES_FIRST_SEEN, TX_HASH_EVIDENCES, USED_TICKETS_COUNTER,
         XRPL_TOKENS,
     },
     tickets::{allocate_ticket, register_used_ticket},
     token::{
-        build_xrpl_token_key, full_denom, is_token_xrp, set_token_bridging_fee,
-        set_token_max_holding_amount, set_token_sending_precision, set_token_state,
+        build_send_msgs, build_xrpl_token_key, full_denom, is_token_xrp,
+        query_cosmos_token_balance, set_token_bridging_fee, set_token_max_holding_amount,
+        set_token_sending_precision, set_token_state,
+    },
+    universal_swap::{handle_universal_swap_reply, is_universal_swap_reply},
+    xrpl_tx::{
+        multisigning_data, serialize_operation, serialize_signed_operation, to_hex,
+        TransactionSigner,
     },
 };
 
 use cosmwasm_std::{
-    coins, entry_point, to_json_binary, wasm_execute, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps,
-    DepsMut, Empty, Env, HexBinary, MessageInfo, Order, Reply, Response, StdResult, Storage,
-    SubMsg, SubMsgResult, Uint128,
+    coin, coins, entry_point, from_json, to_json_binary, wasm_execute, Addr, Binary, Coin,
+    CosmosMsg, Deps, DepsMut, Empty, Env, Event, HexBinary, MessageInfo, Order, Reply, Response,
+    StdError, StdResult, Storage, SubMsg, Uint128,
 };
 use cw2::set_contract_version;
-use cw20::Cw20Coin;
+use cw20::{Cw20Coin, Cw20QueryMsg, Cw20ReceiveMsg, TokenInfoResponse};
 use cw_ownable::{get_ownership, initialize_owner, is_owner, Action};
 use cw_storage_plus::Bound;
 use cw_utils::one_coin;
@@ -48,7 +69,6 @@ use rate_limiter::{
     msg::{ExecuteMsg as RateLimitMsg, QuotaMsg},
     packet::Packet,
 };
-use skip::entry_point::ExecuteMsg as EntryPointExecuteMsg;
 
 // version info for migration info
 pub const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
@@ -57,6 +77,12 @@ pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 // pagination info for queries
 const MAX_PAGE_LIMIT: u32 = 250;
 
+// Time that users must wait after an operation expires to be able to get a refund for it
+pub const OPERATION_EXPIRATION_GRACE_PERIOD: u64 = 60 * 10;
+
+// Evidences that didn't reach the threshold after this amount of seconds can be pruned by anyone
+pub const MIN_STALE_EVIDENCE_AGE: u64 = 60 * 60 * 24 * 7;
+
 // Range of precisions that can be used for tokens
 const MIN_SENDING_PRECISION: i32 = -15;
 const MAX_SENDING_PRECISION: i32 = 15;
@@ -103,7 +129,6 @@ pub const INITIAL_PROHIBITED_XRPL_ADDRESSES: [&str; 5] = [
 ];
 
 pub const CHANNEL: &str = "channel-0"; // chanel default for rate limit
-pub const UNIVERSAL_SWAP_ERROR_ID: u64 = 1;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -125,7 +150,13 @@ pub fn instantiate(
     }
     PROHIBITED_XRPL_ADDRESSES.save(deps.storage, msg.bridge_xrpl_address.clone(), &Empty {})?;
 
-    validate_relayers(deps.as_ref(), &msg.relayers, msg.evidence_threshold)?;
+    let xrpl_network = msg.xrpl_network.unwrap_or_default();
+    let relayers = validate_relayers(
+        deps.as_ref(),
+        &msg.relayers,
+        msg.evidence_threshold,
+        &xrpl_network,
+    )?;
 
     // The multisig address on XRPL must be valid
     validate_xrpl_address_format(&msg.bridge_xrpl_address)?;
@@ -147,7 +178,7 @@ pub fn instantiate(
     AVAILABLE_TICKETS.save(deps.storage, &VecDeque::new())?;
 
     let config = Config {
-        relayers: msg.relayers,
+        relayers,
         evidence_threshold: msg.evidence_threshold,
         used_ticket_sequence_threshold: msg.used_ticket_sequence_threshold,
         trust_set_limit_amount: msg.trust_set_limit_amount,
@@ -157,6 +188,12 @@ pub fn instantiate(
         token_factory_addr: msg.token_factory_addr,
         rate_limit_addr: msg.rate_limit_addr,
         osor_entry_point: msg.osor_entry_point,
+        xrpl_network,
+        halt_on_evidence_conflict: false,
+        fee_distribution: FeeDistribution::Equal,
+        operation_timeout: None,
+        auto_claim_refunds: false,
+        enforce_signature_verification: false,
     };
 
     CONFIG.save(deps.storage, &config)?;
@@ -224,6 +261,7 @@ pub fn execute(
             sending_precision,
             max_holding_amount,
             bridging_fee,
+            token_type,
         } => register_cosmos_token(
             deps,
             env,
@@ -233,6 +271,7 @@ pub fn execute(
             sending_precision,
             max_holding_amount,
             bridging_fee,
+            token_type.unwrap_or_default(),
         ),
         ExecuteMsg::RegisterXRPLToken {
             issuer,
@@ -276,6 +315,7 @@ pub fn execute(
             signature,
         } => save_signature(
             deps,
+            env,
             info.sender,
             operation_id,
             operation_version,
@@ -284,7 +324,19 @@ pub fn execute(
         ExecuteMsg::SendToXRPL {
             recipient,
             deliver_amount,
-        } => send_to_xrpl(deps, env, info, recipient, deliver_amount),
+            destination_tag,
+            refund_to,
+        } => send_to_xrpl(
+            deps,
+            env,
+            info,
+            recipient,
+            deliver_amount,
+            destination_tag,
+            refund_to,
+        ),
+        ExecuteMsg::SendToXRPLBatch { transfers } => send_to_xrpl_batch(deps, env, info, transfers),
+        ExecuteMsg::Receive(cw20_msg) => receive_cw20(deps, env, info, cw20_msg),
         ExecuteMsg::UpdateXRPLToken {
             issuer,
             currency,
@@ -324,13 +376,43 @@ pub fn execute(
         ExecuteMsg::ClaimRefund { pending_refund_id } => {
             claim_pending_refund(deps, info.sender, pending_refund_id)
         }
+        ExecuteMsg::ClaimRefundFor { address, id } => {
+            claim_refund_for(deps, info.sender, address, id)
+        }
         ExecuteMsg::ClaimRelayerFees { amounts } => claim_relayer_fees(deps, info.sender, amounts),
         ExecuteMsg::HaltBridge {} => halt_bridge(deps, info.sender),
+        ExecuteMsg::FreezeFunds {} => freeze_funds(deps, info.sender),
         ExecuteMsg::ResumeBridge {} => resume_bridge(deps, info.sender),
         ExecuteMsg::RotateKeys {
             new_relayers,
             new_evidence_threshold,
         } => rotate_keys(deps, env, info.sender, new_relayers, new_evidence_threshold),
+        ExecuteMsg::AddRelayer {
+            relayer,
+            new_evidence_threshold,
+        } => add_relayer(deps, env, info.sender, relayer, new_evidence_threshold),
+        ExecuteMsg::RemoveRelayer {
+            cosmos_address,
+            new_evidence_threshold,
+        } => remove_relayer(
+            deps,
+            env,
+            info.sender,
+            cosmos_address,
+            new_evidence_threshold,
+        ),
+        ExecuteMsg::ReplaceRelayerKey {
+            cosmos_address,
+            xrpl_address,
+            xrpl_pub_key,
+        } => replace_relayer_key(
+            deps,
+            env,
+            info.sender,
+            cosmos_address,
+            xrpl_address,
+            xrpl_pub_key,
+        ),
         ExecuteMsg::UpdateProhibitedXRPLAddresses {
             prohibited_xrpl_addresses,
         } => update_prohibited_xrpl_addresses(deps, info.sender, prohibited_xrpl_addresses),
@@ -390,32 +472,80 @@ pub fn execute(
             xrpl_denom,
             quota_id,
         } => execute_reset_rate_limit_quota(deps, info, xrpl_denom, quota_id),
+        ExecuteMsg::UpdateHaltOnEvidenceConflict {
+            halt_on_evidence_conflict,
+        } => update_halt_on_evidence_conflict(deps, info.sender, halt_on_evidence_conflict),
+        ExecuteMsg::UpdateSignatureVerification {
+            enforce_signature_verification,
+        } => update_signature_verification(deps, info.sender, enforce_signature_verification),
+        ExecuteMsg::UpdateFeeDistribution { fee_distribution } => {
+            update_fee_distribution(deps, info.sender, fee_distribution)
+        }
+        ExecuteMsg::UpdateOperationTimeout { operation_timeout } => {
+            update_operation_timeout(deps, info.sender, operation_timeout)
+        }
+        ExecuteMsg::ExpireOperation { operation_id } => {
+            expire_operation(env, deps, info.sender, operation_id)
+        }
+        ExecuteMsg::CancelMyTransfer { operation_id } => {
+            cancel_my_transfer(env, deps, info.sender, operation_id)
+        }
+        ExecuteMsg::UpdateAutoClaimRefunds { auto_claim_refunds } => {
+            update_auto_claim_refunds(deps, info.sender, auto_claim_refunds)
+        }
+        ExecuteMsg::SetAutoClaimRefunds { enabled } => {
+            set_auto_claim_refunds(deps, info.sender, enabled)
+        }
+        ExecuteMsg::ClaimAllRefunds { limit } => claim_all_refunds(deps, info.sender, limit),
+        ExecuteMsg::PruneStaleEvidences {
+            older_than_seconds,
+            limit,
+        } => prune_stale_evidences(deps, env, info.sender, older_than_seconds, limit),
+        ExecuteMsg::IndexLegacyEvidences { limit } => {
+            index_legacy_evidences(deps, env, info.sender, limit)
+        }
+        ExecuteMsg::SweepPendingUniversalSwaps { nonces } => {
+            sweep_pending_universal_swaps(deps, info.sender, nonces)
+        }
+        ExecuteMsg::RecoverUntrackedIBCForward { tx_hash } => {
+            recover_untracked_ibc_forward(deps, env, info.sender, tx_hash)
+        }
     }
 }
 
-#[entry_point]
-pub fn reply(deps: DepsMut, _env: Env, reply: Reply) -> Result<Response, ContractError> {
-    if let SubMsgResult::Err(err) = reply.result {
-        return match reply.id {
-            UNIVERSAL_SWAP_ERROR_ID => {
-                let universal_swap_data = TEMP_UNIVERSAL_SWAP.load(deps.storage)?;
-
-                let refund_msg = BankMsg::Send {
-                    to_address: universal_swap_data.recovery_address,
-                    amount: vec![universal_swap_data.return_amount],
-                };
-                TEMP_UNIVERSAL_SWAP.remove(deps.storage);
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> ContractResult<Response> {
+    match msg {
+        SudoMsg::IBCLifecycleComplete(lifecycle) => {
+            handle_ibc_lifecycle_complete(deps.storage, env.block.time.seconds(), lifecycle)
+        }
+    }
+}
 
-                Ok(Response::new()
-                    .add_attribute("action", "refund_universal_swap")
-                    .add_attribute("error_trying_to_call_entrypoint_for_universal_swap", err)
-                    .add_message(refund_msg))
-            }
-            _ => Err(ContractError::UnknownReplyId { id: reply.id }),
-        };
+#[entry_point]
+pub fn reply(deps: DepsMut, env: Env, reply: Reply) -> Result<Response, ContractError> {
+    // Universal swaps, IBC forwards and auto refunds use a nonce as reply id
+    if is_ibc_forward_reply(deps.storage, reply.id) {
+        return handle_ibc_forward_reply(
+            deps.storage,
+            env.block.time.seconds(),
+            reply.id,
+            reply.result,
+        );
+    }
+    if is_auto_refund_reply(deps.storage, reply.id) {
+        return handle_auto_refund_reply(deps.storage, reply.id, reply.result);
     }
-    // default response
-    Ok(Response::new())
+    if is_universal_swap_reply(deps.storage, reply.id) {
+        return handle_universal_swap_reply(
+            deps.storage,
+            env.block.time.seconds(),
+            reply.id,
+            reply.result,
+        );
+    }
+
+    Err(ContractError::UnknownReplyId { id: reply.id })
 }
 
 fn update_ownership(
@@ -518,6 +648,7 @@ fn register_cosmos_token(
     sending_precision: i32,
     max_holding_amount: Uint128,
     bridging_fee: Uint128,
+    token_type: CosmosTokenType,
 ) -> ContractResult<Response> {
     check_authorization(deps.storage, &sender, &ContractActions::RegisterCosmosToken)?;
     assert_bridge_active(deps.as_ref())?;
@@ -529,7 +660,16 @@ fn register_cosmos_token(
         return Err(ContractError::CosmosTokenAlreadyRegistered { denom });
     }
 
-    validate_cosmos_denom(&denom)?;
+    match token_type {
+        CosmosTokenType::Native => validate_cosmos_denom(&denom)?,
+        // For CW20 tokens the denom must be the address of a CW20 contract
+        CosmosTokenType::CW20 => {
+            deps.api.addr_validate(&denom)?;
+            let _: TokenInfoResponse = deps
+                .querier
+                .query_wasm_smart(denom.clone(), &Cw20QueryMsg::TokenInfo {})?;
+        }
+    }
 
     // We generate a currency creating a Sha256 hash of the denom, the decimals and the current time so that if it fails we can try again
     let to_hash = format!("{}{}{}", denom, decimals, env.block.time.seconds()).into_bytes();
@@ -561,6 +701,7 @@ fn register_cosmos_token(
         // All registered Cosmos originated tokens will start as enabled because they don't need a TrustSet operation to be bridged because issuer for such tokens is bridge address
         state: TokenState::Enabled,
         bridging_fee,
+        token_type,
     };
     COSMOS_TOKENS.save(deps.storage, denom.clone(), &token)?;
 
@@ -589,7 +730,11 @@ fn register_xrpl_token(
         &ContractActions::RegisterXRPLToken,
     )?;
 
-    validate_xrpl_address(deps.storage, issuer.clone())?;
+    let issuer = validate_xrpl_address(
+        deps.storage,
+        issuer,
+        &CONFIG.load(deps.storage)?.xrpl_network,
+    )?;
     validate_xrpl_currency(&currency)?;
 
     validate_sending_precision(sending_precision, XRPL_TOKENS_DECIMALS)?;
@@ -685,12 +830,19 @@ fn save_evidence(
 
     evidence.validate_basic()?;
 
-    let threshold_reached = handle_evidence(deps.storage, sender.clone(), &evidence)?;
-
     let mut response = Response::new()
         .add_attribute("action", ContractActions::SaveEvidence.as_str())
         .add_attribute("sender", sender.as_str());
 
+    let threshold_relayers = handle_evidence(
+        deps.storage,
+        env.block.time,
+        sender.clone(),
+        &evidence,
+        &mut response,
+    )?;
+    let threshold_reached = threshold_relayers.is_some();
+
     match evidence {
         Evidence::XRPLToCosmosTransfer {
             tx_hash,
@@ -699,12 +851,22 @@ fn save_evidence(
             amount,
             recipient,
             memo,
+            source_tag,
+            raw_memos,
         } => {
-            if config.bridge_state == BridgeState::Halted {
+            if config.bridge_state != BridgeState::Active {
                 return Err(ContractError::BridgeHalted {});
             }
             deps.api.addr_validate(recipient.as_ref())?;
-            let memo = memo.unwrap_or_default();
+
+            // Memos that don't follow the memo schema are plain transfers
+            let memo_action = match memo {
+                Some(memo) => parse_memo(&memo)?.map(|bridge_memo| bridge_memo.action),
+                None => None,
+            };
+            if let Some(MemoAction::ContractCall { contract, .. }) = &memo_action {
+                deps.api.addr_validate(contract)?;
+            }
 
             // If the recipient of the operation is the bridge contract address, we error
             if recipient.eq(&env.contract.address) {
@@ -761,6 +923,7 @@ fn save_evidence(
                         token.bridging_fee,
                         token.cosmos_denom.clone(),
                         remainder,
+                        threshold_relayers.as_deref(),
                     )?;
 
                     if !fee_collected.is_zero() {
@@ -777,9 +940,17 @@ fn save_evidence(
                     }
 
                     if !amount_to_send.is_zero() {
-                        let is_universal_swap =
-                            !memo.is_empty() && config.osor_entry_point.is_some();
-                        let recipient_to_mint = if is_universal_swap {
+                        let memo_action_sub_msg = create_memo_action_submsg(
+                            deps.storage,
+                            config.osor_entry_point.clone(),
+                            memo_action,
+                            &env,
+                            tx_hash.clone(),
+                            recipient.clone(),
+                            coin(amount_to_send.u128(), token.cosmos_denom.clone()),
+                        )?;
+                        // If there is an action to execute, the bridge receives the tokens and sends them with the action
+                        let recipient_to_mint = if memo_action_sub_msg.is_some() {
                             env.contract.address
                         } else {
                             recipient.clone()
@@ -794,17 +965,7 @@ fn save_evidence(
                             },
                             vec![],
                         )?;
-                        // if universal swap , call entry point contract
-                        if is_universal_swap {
-                            sub_msgs.push(SubMsg::reply_on_error(
-                                wasm_execute(
-                                    config.osor_entry_point.unwrap(),
-                                    &EntryPointExecuteMsg::UniversalSwap { memo },
-                                    coins(amount_to_send.u128(), token.cosmos_denom),
-                                )?,
-                                UNIVERSAL_SWAP_ERROR_ID,
-                            ));
-                        }
+                        sub_msgs.extend(memo_action_sub_msg);
 
                         msgs.push(mint_msg_for_recipient.into());
 
@@ -870,26 +1031,30 @@ fn save_evidence(
                         token.bridging_fee,
                         token.denom.clone(),
                         remainder,
+                        threshold_relayers.as_deref(),
                     )?;
 
-                    let is_universal_swap = !memo.is_empty() && config.osor_entry_point.is_some();
-
-                    // TODO: should we support CW20 as well?
-                    if is_universal_swap {
-                        sub_msgs.push(SubMsg::reply_on_error(
-                            wasm_execute(
-                                config.osor_entry_point.unwrap(),
-                                &EntryPointExecuteMsg::UniversalSwap { memo },
-                                coins(amount_to_send.u128(), token.denom),
-                            )?,
-                            UNIVERSAL_SWAP_ERROR_ID,
-                        ));
-                    } else {
-                        let send_msg = BankMsg::Send {
-                            to_address: recipient.to_string(),
-                            amount: coins(amount_to_send.u128(), token.denom),
-                        };
-                        msgs.push(send_msg.into());
+                    // Memo actions send native funds, so CW20 tokens are always sent to the recipient
+                    let memo_action_sub_msg = match token.token_type {
+                        CosmosTokenType::Native => create_memo_action_submsg(
+                            deps.storage,
+                            config.osor_entry_point.clone(),
+                            memo_action,
+                            &env,
+                            tx_hash.clone(),
+                            recipient.clone(),
+                            coin(amount_to_send.u128(), token.denom.clone()),
+                        )?,
+                        CosmosTokenType::CW20 => None,
+                    };
+
+                    match memo_action_sub_msg {
+                        Some(sub_msg) => sub_msgs.push(sub_msg),
+                        None => msgs.extend(build_send_msgs(
+                            deps.storage,
+                            &recipient,
+                            coins(amount_to_send.u128(), token.denom),
+                        )?),
                     }
 
                     // handle rate limit
@@ -925,6 +1090,23 @@ fn save_evidence(
                 .add_attribute("amount", amount.to_string())
                 .add_attribute("recipient", recipient.to_string())
                 .add_attribute("threshold_reached", threshold_reached.to_string());
+
+            // Source tag and memos of the XRPL payment are added once the transfer is executed so that it can be reconciled
+            if threshold_reached {
+                if let Some(source_tag) = source_tag {
+                    response = response.add_attribute("source_tag", source_tag.to_string());
+                }
+                if let Some(raw_memos) = raw_memos {
+                    response = response.add_attribute(
+                        "raw_memos",
+                        raw_memos
+                            .iter()
+                            .map(HexBinary::to_hex)
+                            .collect::<Vec<String>>()
+                            .join(","),
+                    );
+                }
+            }
         }
         Evidence::XRPLTransactionResult {
             tx_hash,
@@ -1119,6 +1301,7 @@ fn recover_xrpl_token_registration(
 
 fn save_signature(
     deps: DepsMut,
+    env: Env,
     sender: Addr,
     operation_id: u64,
     operation_version: u64,
@@ -1130,19 +1313,31 @@ fn save_signature(
         &ContractActions::SaveSignature,
     )?;
 
-    add_signature(
+    let signature_verified = add_signature(
         deps,
+        env.block.time,
         operation_id,
         operation_version,
         sender.clone(),
         signature.to_string(),
     )?;
 
-    Ok(Response::new()
+    let mut response = Response::new()
         .add_attribute("action", ContractActions::SaveSignature.as_str())
-        .add_attribute("sender", sender)
+        .add_attribute("sender", sender.as_str())
         .add_attribute("operation_id", operation_id.to_string())
-        .add_attribute("signature", signature))
+        .add_attribute("signature", signature)
+        .add_attribute("signature_verified", signature_verified.to_string());
+
+    if !signature_verified {
+        response = response.add_event(
+            Event::new("unverified_signature")
+                .add_attribute("relayer", sender)
+                .add_attribute("operation_id", operation_id.to_string()),
+        );
+    }
+
+    Ok(response)
 }
 
 fn send_to_xrpl(
@@ -1151,13 +1346,251 @@ fn send_to_xrpl(
     info: MessageInfo,
     recipient: String,
     deliver_amount: Option<Uint128>,
+    destination_tag: Option<u32>,
+    refund_to: Option<String>,
 ) -> ContractResult<Response> {
-    assert_bridge_active(deps.as_ref())?;
     // Check that we are only sending 1 type of coin
     let funds = one_coin(&info)?;
+    let refund_to = refund_to
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+
+    handle_send_to_xrpl(
+        deps,
+        env,
+        info.sender,
+        funds,
+        CosmosTokenType::Native,
+        recipient,
+        deliver_amount,
+        destination_tag,
+        refund_to,
+    )
+}
+
+fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> ContractResult<Response> {
+    let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+    // The CW20 contract is the one calling us so its address is the denom of the token
+    let funds = coin(cw20_msg.amount.u128(), info.sender.to_string());
+
+    match from_json(&cw20_msg.msg)? {
+        Cw20HookMsg::SendToXRPL {
+            recipient,
+            destination_tag,
+            refund_to,
+        } => {
+            let refund_to = refund_to
+                .map(|address| deps.api.addr_validate(&address))
+                .transpose()?;
+
+            handle_send_to_xrpl(
+                deps,
+                env,
+                sender,
+                funds,
+                CosmosTokenType::CW20,
+                recipient,
+                None,
+                destination_tag,
+                refund_to,
+            )
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_send_to_xrpl(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    funds: Coin,
+    token_type: CosmosTokenType,
+    recipient: String,
+    deliver_amount: Option<Uint128>,
+    destination_tag: Option<u32>,
+    refund_to: Option<Addr>,
+) -> ContractResult<Response> {
+    let transfer = prepare_transfer_to_xrpl(
+        deps.branch(),
+        &env,
+        sender.clone(),
+        funds.clone(),
+        token_type,
+        recipient.clone(),
+        deliver_amount,
+        destination_tag,
+        refund_to,
+    )?;
+
+    // Get a ticket and store the pending operation
+    let ticket = allocate_ticket(deps.storage)?;
+    create_pending_operation(
+        deps.storage,
+        env.block.time.seconds(),
+        Some(ticket),
+        None,
+        transfer.operation_type,
+    )?;
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let config = CONFIG.load(deps.storage)?;
+    if let Some(rate_limit_addr) = config.rate_limit_addr {
+        msgs.push(
+            wasm_execute(
+                rate_limit_addr,
+                &RateLimitMsg::SendPacket {
+                    packet: Packet {
+                        channel: CHANNEL.to_string(),
+                        denom: transfer.xrpl_denom,
+                        amount: transfer.increase_limit_amount,
+                    },
+                },
+                vec![],
+            )?
+            .into(),
+        )
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", ContractActions::SendToXRPL.as_str())
+        .add_attribute("sender", sender)
+        .add_attribute("recipient", recipient)
+        .add_attribute("coin", funds.to_string())
+        .add_messages(msgs))
+}
+
+fn send_to_xrpl_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfers: Vec<XRPLTransfer>,
+) -> ContractResult<Response> {
+    assert_bridge_active(deps.as_ref())?;
+
+    if transfers.is_empty() {
+        return Err(ContractError::EmptyTransferBatch {});
+    }
+
+    // The funds attached must be exactly the sum of the coins of all the transfers
+    let mut batch_funds: BTreeMap<String, Uint128> = BTreeMap::new();
+    for transfer in &transfers {
+        let amount = batch_funds.entry(transfer.coin.denom.clone()).or_default();
+        *amount = amount.checked_add(transfer.coin.amount)?;
+    }
+    let sent_funds: BTreeMap<String, Uint128> = info
+        .funds
+        .iter()
+        .map(|c| (c.denom.clone(), c.amount))
+        .collect();
+    if batch_funds.ne(&sent_funds) {
+        return Err(ContractError::InvalidBatchFunds {});
+    }
+
+    // Each transfer needs its own ticket, and the last available ticket is always reserved for ticket allocations
+    let available_tickets = AVAILABLE_TICKETS.load(deps.storage)?.len();
+    if available_tickets <= transfers.len() {
+        return Err(ContractError::NotEnoughTickets {
+            required: transfers.len(),
+            available: available_tickets.saturating_sub(1),
+        });
+    }
+
+    // Rate limits are applied once per token with the sum of all the transfers of that token
+    let mut rate_limit_amounts: BTreeMap<String, Uint128> = BTreeMap::new();
+    for transfer in &transfers {
+        let refund_to = transfer
+            .refund_to
+            .as_ref()
+            .map(|address| deps.api.addr_validate(address))
+            .transpose()?;
+        let prepared_transfer = prepare_transfer_to_xrpl(
+            deps.branch(),
+            &env,
+            info.sender.clone(),
+            transfer.coin.clone(),
+            CosmosTokenType::Native,
+            transfer.recipient.clone(),
+            transfer.deliver_amount,
+            transfer.destination_tag,
+            refund_to,
+        )?;
+
+        let ticket = allocate_ticket(deps.storage)?;
+        create_pending_operation(
+            deps.storage,
+            env.block.time.seconds(),
+            Some(ticket),
+            None,
+            prepared_transfer.operation_type,
+        )?;
+
+        let amount = rate_limit_amounts
+            .entry(prepared_transfer.xrpl_denom)
+            .or_default();
+        *amount = amount.checked_add(prepared_transfer.increase_limit_amount)?;
+    }
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let config = CONFIG.load(deps.storage)?;
+    if let Some(rate_limit_addr) = config.rate_limit_addr {
+        for (denom, amount) in rate_limit_amounts {
+            msgs.push(
+                wasm_execute(
+                    rate_limit_addr.clone(),
+                    &RateLimitMsg::SendPacket {
+                        packet: Packet {
+                            channel: CHANNEL.to_string(),
+                            denom,
+                            amount,
+                        },
+                    },
+                    vec![],
+                )?
+                .into(),
+            )
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", ContractActions::SendToXRPLBatch.as_str())
+        .add_attribute("sender", info.sender)
+        .add_attribute("transfers", transfers.len().to_string())
+        .add_messages(msgs))
+}
+
+// Transfer to XRPL that was validated and had its fees collected, ready to be stored as a pending operation
+struct PreparedTransferToXRPL {
+    operation_type: OperationType,
+    xrpl_denom: String,
+    increase_limit_amount: Uint128,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn prepare_transfer_to_xrpl(
+    deps: DepsMut,
+    env: &Env,
+    sender: Addr,
+    funds: Coin,
+    token_type: CosmosTokenType,
+    recipient: String,
+    deliver_amount: Option<Uint128>,
+    destination_tag: Option<u32>,
+    refund_to: Option<Addr>,
+) -> ContractResult<PreparedTransferToXRPL> {
+    assert_bridge_active(deps.as_ref())?;
+
+    // X-addresses are converted to the classic address and destination tag that are used in the XRPL payment
+    let xrpl_network = CONFIG.load(deps.storage)?.xrpl_network;
+    let (recipient, destination_tag) =
+        resolve_xrpl_recipient(recipient, destination_tag, &xrpl_network)?;
 
     // Check that the recipient is a valid XRPL address and it's not prohibited
-    validate_xrpl_address(deps.storage, recipient.clone())?;
+    let recipient = validate_xrpl_address(deps.storage, recipient, &xrpl_network)?;
 
     // We check that deliver_amount is not greater than the funds sent
     if deliver_amount.is_some() && deliver_amount.unwrap().gt(&funds.amount) {
@@ -1171,13 +1604,16 @@ fn send_to_xrpl(
     let issuer;
     let currency;
     let increase_limit_amount;
-    // We check if the token we are sending is an XRPL originated token or not
-    if let Some(xrpl_token) = XRPL_TOKENS
-        .idx
-        .cosmos_denom
-        .item(deps.storage, funds.denom.clone())
-        .map(|res| res.map(|pk_token| pk_token.1))?
-    {
+    // We check if the token we are sending is an XRPL originated token or not (they are always native tokens)
+    let xrpl_token = match token_type {
+        CosmosTokenType::Native => XRPL_TOKENS
+            .idx
+            .cosmos_denom
+            .item(deps.storage, funds.denom.clone())
+            .map(|res| res.map(|pk_token| pk_token.1))?,
+        CosmosTokenType::CW20 => None,
+    };
+    if let Some(xrpl_token) = xrpl_token {
         // If it's an XRPL originated token we need to check that it's enabled and if it is apply the sending precision
         if xrpl_token.state.ne(&TokenState::Enabled) {
             return Err(ContractError::TokenNotEnabled {});
@@ -1235,12 +1671,17 @@ fn send_to_xrpl(
             xrpl_token.bridging_fee,
             xrpl_token.cosmos_denom,
             remainder,
+            None,
         )?;
     } else {
         // If it's not an XRPL originated token we need to check that it's registered as a Cosmos originated token and that it's enabled
         let cosmos_token = COSMOS_TOKENS
             .load(deps.storage, funds.denom.clone())
             .map_err(|_| ContractError::TokenNotRegistered {})?;
+        // The token must have been sent the same way it was registered (native funds or CW20 send)
+        if cosmos_token.token_type.ne(&token_type) {
+            return Err(ContractError::TokenNotRegistered {});
+        }
         if cosmos_token.state.ne(&TokenState::Enabled) {
             return Err(ContractError::TokenNotEnabled {});
         }
@@ -1275,14 +1716,12 @@ fn send_to_xrpl(
             cosmos_token.bridging_fee,
             cosmos_token.denom.clone(),
             remainder,
+            None,
         )?;
 
         // For Cosmos originated tokens we need to check that we are not going over the amount
         // that the bridge will hold in escrow
-        if deps
-            .querier
-            .query_balance(env.contract.address, cosmos_token.denom)?
-            .amount
+        if query_cosmos_token_balance(&deps.querier, &env.contract.address, &cosmos_token)?
             .gt(&cosmos_token.max_holding_amount)
         {
             return Err(ContractError::MaximumBridgedAmountReached {});
@@ -1298,49 +1737,20 @@ fn send_to_xrpl(
         validate_xrpl_amount(max_amount.unwrap())?;
     }
 
-    let xrpl_denom = build_xrpl_token_key(&issuer, &currency);
-    // Get a ticket and store the pending operation
-    let ticket = allocate_ticket(deps.storage)?;
-    create_pending_operation(
-        deps.storage,
-        env.block.time.seconds(),
-        Some(ticket),
-        None,
-        OperationType::CosmosToXRPLTransfer {
+    Ok(PreparedTransferToXRPL {
+        xrpl_denom: build_xrpl_token_key(&issuer, &currency),
+        operation_type: OperationType::CosmosToXRPLTransfer {
             issuer,
             currency,
             amount: amount_to_send,
             max_amount,
-            sender: info.sender.clone(),
-            recipient: recipient.clone(),
+            sender,
+            recipient,
+            destination_tag,
+            refund_to,
         },
-    )?;
-
-    let mut msgs: Vec<CosmosMsg> = vec![];
-    let config = CONFIG.load(deps.storage)?;
-    if let Some(rate_limit_addr) = config.rate_limit_addr {
-        msgs.push(
-            wasm_execute(
-                rate_limit_addr,
-                &RateLimitMsg::SendPacket {
-                    packet: Packet {
-                        channel: CHANNEL.to_string(),
-                        denom: xrpl_denom,
-                        amount: increase_limit_amount,
-                    },
-                },
-                vec![],
-            )?
-            .into(),
-        )
-    }
-
-    Ok(Response::new()
-        .add_attribute("action", ContractActions::SendToXRPL.as_str())
-        .add_attribute("sender", info.sender)
-        .add_attribute("recipient", recipient)
-        .add_attribute("coin", funds.to_string())
-        .add_messages(msgs))
+        increase_limit_amount,
+    })
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -1430,10 +1840,8 @@ fn update_cosmos_token(
     set_token_bridging_fee(&mut token.bridging_fee, bridging_fee)?;
 
     // Get the current bridged amount for this token to verify that we are not setting a max_holding_amount that is less than the current amount
-    let current_bridged_amount = deps
-        .querier
-        .query_balance(env.contract.address, token.denom.clone())?
-        .amount;
+    let current_bridged_amount =
+        query_cosmos_token_balance(&deps.querier, &env.contract.address, &token)?;
     set_token_max_holding_amount(
         current_bridged_amount,
         &mut token.max_holding_amount,
@@ -1483,6 +1891,7 @@ fn update_xrpl_base_fee(
                 signatures: vec![],
                 operation_type: operation.1.operation_type.clone(),
                 xrpl_base_fee,
+                created_at: operation.1.created_at,
             },
         )?;
     }
@@ -1521,53 +1930,201 @@ fn update_used_ticket_sequence_threshold(
         ))
 }
 
-fn claim_relayer_fees(deps: DepsMut, sender: Addr, amounts: Vec<Coin>) -> ContractResult<Response> {
-    assert_bridge_active(deps.as_ref())?;
-
-    // If fees were never collected for this address we don't allow the claim
-    if FEES_COLLECTED
-        .may_load(deps.storage, sender.clone())?
-        .is_none()
-    {
-        return Err(ContractError::UnauthorizedSender {});
-    };
-
-    substract_relayer_fees(deps.storage, &sender, &amounts)?;
-
-    let send_msg = BankMsg::Send {
-        to_address: sender.to_string(),
-        amount: amounts,
-    };
-
-    Ok(Response::new()
-        .add_attribute("action", ContractActions::ClaimFees.as_str())
-        .add_attribute("sender", sender)
-        .add_message(send_msg))
-}
-
-fn claim_pending_refund(
+fn update_halt_on_evidence_conflict(
     deps: DepsMut,
     sender: Addr,
-    pending_refund_id: String,
+    halt_on_evidence_conflict: bool,
 ) -> ContractResult<Response> {
-    assert_bridge_active(deps.as_ref())?;
-    let coin = remove_pending_refund(deps.storage, &sender, pending_refund_id)?;
+    check_authorization(
+        deps.as_ref().storage,
+        &sender,
+        &ContractActions::UpdateHaltOnEvidenceConflict,
+    )?;
 
-    let send_msg = BankMsg::Send {
-        to_address: sender.to_string(),
-        amount: vec![coin],
+    let mut config = CONFIG.load(deps.storage)?;
+    config.halt_on_evidence_conflict = halt_on_evidence_conflict;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute(
+            "action",
+            ContractActions::UpdateHaltOnEvidenceConflict.as_str(),
+        )
+        .add_attribute("sender", sender)
+        .add_attribute(
+            "halt_on_evidence_conflict",
+            halt_on_evidence_conflict.to_string(),
+        ))
+}
+
+fn update_signature_verification(
+    deps: DepsMut,
+    sender: Addr,
+    enforce_signature_verification: bool,
+) -> ContractResult<Response> {
+    check_authorization(
+        deps.as_ref().storage,
+        &sender,
+        &ContractActions::UpdateSignatureVerification,
+    )?;
+
+    let mut config = CONFIG.load(deps.storage)?;
+    config.enforce_signature_verification = enforce_signature_verification;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute(
+            "action",
+            ContractActions::UpdateSignatureVerification.as_str(),
+        )
+        .add_attribute("sender", sender)
+        .add_attribute(
+            "enforce_signature_verification",
+            enforce_signature_verification.to_string(),
+        ))
+}
+
+fn update_fee_distribution(
+    deps: DepsMut,
+    sender: Addr,
+    fee_distribution: FeeDistribution,
+) -> ContractResult<Response> {
+    check_authorization(
+        deps.as_ref().storage,
+        &sender,
+        &ContractActions::UpdateFeeDistribution,
+    )?;
+
+    let mut config = CONFIG.load(deps.storage)?;
+    config.fee_distribution = fee_distribution.clone();
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", ContractActions::UpdateFeeDistribution.as_str())
+        .add_attribute("sender", sender)
+        .add_attribute("fee_distribution", fee_distribution.as_str()))
+}
+
+fn claim_relayer_fees(deps: DepsMut, sender: Addr, amounts: Vec<Coin>) -> ContractResult<Response> {
+    assert_funds_not_frozen(deps.as_ref())?;
+
+    // If fees were never collected for this address we don't allow the claim
+    if FEES_COLLECTED
+        .may_load(deps.storage, sender.clone())?
+        .is_none()
+    {
+        return Err(ContractError::UnauthorizedSender {});
     };
 
+    substract_relayer_fees(deps.storage, &sender, &amounts)?;
+
+    let send_msgs = build_send_msgs(deps.storage, &sender, amounts)?;
+
+    Ok(Response::new()
+        .add_attribute("action", ContractActions::ClaimFees.as_str())
+        .add_attribute("sender", sender)
+        .add_messages(send_msgs))
+}
+
+fn claim_pending_refund(
+    deps: DepsMut,
+    sender: Addr,
+    pending_refund_id: String,
+) -> ContractResult<Response> {
+    assert_funds_not_frozen(deps.as_ref())?;
+    let pending_refund = remove_pending_refund(deps.storage, &sender, pending_refund_id)?;
+
+    let send_msgs = build_send_msgs(
+        deps.storage,
+        pending_refund.destination(),
+        vec![pending_refund.coin.clone()],
+    )?;
+
     Ok(Response::new()
         .add_attribute("action", ContractActions::ClaimRefunds.as_str())
         .add_attribute("sender", sender)
-        .add_message(send_msg))
+        .add_messages(send_msgs))
+}
+
+fn claim_refund_for(
+    deps: DepsMut,
+    sender: Addr,
+    address: Addr,
+    pending_refund_id: String,
+) -> ContractResult<Response> {
+    assert_funds_not_frozen(deps.as_ref())?;
+    let pending_refund = remove_pending_refund(deps.storage, &address, pending_refund_id.clone())?;
+    let destination = pending_refund.destination().to_owned();
+
+    // Funds always go to the refund destination, so it doesn't matter who claims them
+    let send_msgs = build_send_msgs(deps.storage, &destination, vec![pending_refund.coin])?;
+
+    Ok(Response::new()
+        .add_attribute("action", ContractActions::ClaimRefundFor.as_str())
+        .add_attribute("sender", sender)
+        .add_attribute("address", address)
+        .add_attribute("pending_refund_id", pending_refund_id)
+        .add_attribute("destination", destination)
+        .add_messages(send_msgs))
+}
+
+fn claim_all_refunds(deps: DepsMut, sender: Addr, limit: Option<u32>) -> ContractResult<Response> {
+    assert_funds_not_frozen(deps.as_ref())?;
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+
+    let pending_refund_ids: Vec<String> = PENDING_REFUNDS
+        .idx
+        .address
+        .prefix(sender.clone())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(limit as usize)
+        .filter_map(Result::ok)
+        .map(|(_, pending_refund_id)| pending_refund_id)
+        .collect();
+
+    // Refunds are grouped by destination and the ones of the same denom are merged because a bank send can't have the
+    // same denom more than once
+    let mut refunds: BTreeMap<Addr, Vec<Coin>> = BTreeMap::new();
+    for pending_refund_id in &pending_refund_ids {
+        let pending_refund =
+            remove_pending_refund(deps.storage, &sender, pending_refund_id.clone())?;
+        let coins = refunds
+            .entry(pending_refund.destination().to_owned())
+            .or_default();
+        match coins
+            .iter_mut()
+            .find(|c| c.denom == pending_refund.coin.denom)
+        {
+            Some(coin) => coin.amount = coin.amount.checked_add(pending_refund.coin.amount)?,
+            None => coins.push(pending_refund.coin),
+        }
+    }
+
+    let mut send_msgs = vec![];
+    for (destination, mut coins) in refunds {
+        coins.sort_by(|a, b| a.denom.cmp(&b.denom));
+        send_msgs.extend(build_send_msgs(deps.storage, &destination, coins)?);
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", ContractActions::ClaimAllRefunds.as_str())
+        .add_attribute("sender", sender)
+        .add_attribute("claimed", pending_refund_ids.len().to_string())
+        .add_messages(send_msgs))
 }
 
 fn halt_bridge(deps: DepsMut, sender: Addr) -> ContractResult<Response> {
     check_authorization(deps.as_ref().storage, &sender, &ContractActions::HaltBridge)?;
-    // No point halting a bridge that is already halted
-    assert_bridge_active(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+    match config.bridge_state {
+        // No point halting a bridge that is already halted
+        BridgeState::Halted => return Err(ContractError::BridgeHalted {}),
+        // Halting a bridge with frozen funds allows claiming them again, so only the owner can do it
+        BridgeState::FundsFrozen if !is_owner(deps.storage, &sender)? => {
+            return Err(ContractError::UnauthorizedSender {})
+        }
+        _ => (),
+    }
     update_bridge_state(deps.storage, BridgeState::Halted)?;
 
     Ok(Response::new()
@@ -1575,6 +2132,20 @@ fn halt_bridge(deps: DepsMut, sender: Addr) -> ContractResult<Response> {
         .add_attribute("sender", sender))
 }
 
+fn freeze_funds(deps: DepsMut, sender: Addr) -> ContractResult<Response> {
+    check_authorization(
+        deps.as_ref().storage,
+        &sender,
+        &ContractActions::FreezeFunds,
+    )?;
+    assert_funds_not_frozen(deps.as_ref())?;
+    update_bridge_state(deps.storage, BridgeState::FundsFrozen)?;
+
+    Ok(Response::new()
+        .add_attribute("action", ContractActions::FreezeFunds.as_str())
+        .add_attribute("sender", sender))
+}
+
 fn resume_bridge(deps: DepsMut, sender: Addr) -> ContractResult<Response> {
     check_authorization(
         deps.as_ref().storage,
@@ -1582,8 +2153,12 @@ fn resume_bridge(deps: DepsMut, sender: Addr) -> ContractResult<Response> {
         &ContractActions::ResumeBridge,
     )?;
 
-    // Can't resume the bridge if there is a pending rotate keys ongoing
-    if PENDING_ROTATE_KEYS.load(deps.storage)? {
+    // Can't resume the bridge if there is a pending rotate keys ongoing that halted it
+    if PENDING_ROTATE_KEYS.load(deps.storage)?
+        && PENDING_RELAYER_SET_CHANGE
+            .may_load(deps.storage)?
+            .map_or(true, |change| change.halts_bridge)
+    {
         return Err(ContractError::RotateKeysOngoing {});
     }
 
@@ -1603,6 +2178,123 @@ fn rotate_keys(
 ) -> ContractResult<Response> {
     check_authorization(deps.as_ref().storage, &sender, &ContractActions::RotateKeys)?;
 
+    // We halt the bridge, unless its funds are frozen because that halts it already
+    if CONFIG.load(deps.storage)?.bridge_state != BridgeState::FundsFrozen {
+        update_bridge_state(deps.storage, BridgeState::Halted)?;
+    }
+
+    start_relayer_set_change(deps, env, new_relayers, new_evidence_threshold, true)?;
+
+    Ok(Response::new()
+        .add_attribute("action", ContractActions::RotateKeys.as_str())
+        .add_attribute("sender", sender))
+}
+
+fn add_relayer(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    relayer: Relayer,
+    new_evidence_threshold: Option<u32>,
+) -> ContractResult<Response> {
+    check_authorization(deps.as_ref().storage, &sender, &ContractActions::AddRelayer)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let mut new_relayers = config.relayers;
+    new_relayers.push(relayer.clone());
+
+    let operation_id = start_relayer_set_change(
+        deps,
+        env,
+        new_relayers,
+        new_evidence_threshold.unwrap_or(config.evidence_threshold),
+        false,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", ContractActions::AddRelayer.as_str())
+        .add_attribute("sender", sender)
+        .add_attribute("relayer", relayer.cosmos_address)
+        .add_attribute("operation_id", operation_id.to_string()))
+}
+
+fn remove_relayer(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    cosmos_address: Addr,
+    new_evidence_threshold: Option<u32>,
+) -> ContractResult<Response> {
+    check_authorization(
+        deps.as_ref().storage,
+        &sender,
+        &ContractActions::RemoveRelayer,
+    )?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let mut new_relayers = config.relayers;
+    let relayers_count = new_relayers.len();
+    new_relayers.retain(|relayer| relayer.cosmos_address != cosmos_address);
+    if new_relayers.len() == relayers_count {
+        return Err(ContractError::RelayerNotFound {});
+    }
+
+    let operation_id = start_relayer_set_change(
+        deps,
+        env,
+        new_relayers,
+        new_evidence_threshold.unwrap_or(config.evidence_threshold),
+        false,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", ContractActions::RemoveRelayer.as_str())
+        .add_attribute("sender", sender)
+        .add_attribute("relayer", cosmos_address)
+        .add_attribute("operation_id", operation_id.to_string()))
+}
+
+fn replace_relayer_key(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    cosmos_address: Addr,
+    xrpl_address: String,
+    xrpl_pub_key: String,
+) -> ContractResult<Response> {
+    check_authorization(
+        deps.as_ref().storage,
+        &sender,
+        &ContractActions::ReplaceRelayerKey,
+    )?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let mut new_relayers = config.relayers;
+    let relayer = new_relayers
+        .iter_mut()
+        .find(|relayer| relayer.cosmos_address == cosmos_address)
+        .ok_or(ContractError::RelayerNotFound {})?;
+    relayer.xrpl_address = xrpl_address;
+    relayer.xrpl_pub_key = xrpl_pub_key;
+
+    let operation_id =
+        start_relayer_set_change(deps, env, new_relayers, config.evidence_threshold, false)?;
+
+    Ok(Response::new()
+        .add_attribute("action", ContractActions::ReplaceRelayerKey.as_str())
+        .add_attribute("sender", sender)
+        .add_attribute("relayer", cosmos_address)
+        .add_attribute("operation_id", operation_id.to_string()))
+}
+
+// Creates the SignerListSet operation for a new relayer set and returns its operation id
+fn start_relayer_set_change(
+    deps: DepsMut,
+    env: Env,
+    new_relayers: Vec<Relayer>,
+    new_evidence_threshold: u32,
+    halts_bridge: bool,
+) -> ContractResult<u64> {
     // If there is already a pending rotate keys ongoing, we don't allow another one until that one is confirmed
     if PENDING_ROTATE_KEYS.load(deps.storage)? {
         return Err(ContractError::RotateKeysOngoing {});
@@ -1610,11 +2302,13 @@ fn rotate_keys(
     // We set the pending rotate keys flag to true so that we don't allow another rotate keys operation until this one is confirmed
     PENDING_ROTATE_KEYS.save(deps.storage, &true)?;
 
-    // We halt the bridge
-    update_bridge_state(deps.storage, BridgeState::Halted)?;
-
     // Validate the new relayer set so that we are sure that the new set is valid (e.g. no duplicated relayers, etc.)
-    validate_relayers(deps.as_ref(), &new_relayers, new_evidence_threshold)?;
+    let new_relayers = validate_relayers(
+        deps.as_ref(),
+        &new_relayers,
+        new_evidence_threshold,
+        &CONFIG.load(deps.storage)?.xrpl_network,
+    )?;
 
     let ticket = allocate_ticket(deps.storage)?;
 
@@ -1629,9 +2323,15 @@ fn rotate_keys(
         },
     )?;
 
-    Ok(Response::new()
-        .add_attribute("action", ContractActions::RotateKeys.as_str())
-        .add_attribute("sender", sender))
+    PENDING_RELAYER_SET_CHANGE.save(
+        deps.storage,
+        &RelayerSetChange {
+            operation_id: ticket,
+            halts_bridge,
+        },
+    )?;
+
+    Ok(ticket)
 }
 
 fn update_prohibited_xrpl_addresses(
@@ -1655,7 +2355,9 @@ fn update_prohibited_xrpl_addresses(
     // Add all prohibited addresses provided
     for prohibited_xrpl_address in prohibited_xrpl_addresses {
         // Validate the address that we are adding, to not add useless things
-        validate_xrpl_address_format(&prohibited_xrpl_address)?;
+        // X-addresses are stored as classic addresses, which is what recipients are compared with
+        let prohibited_xrpl_address =
+            normalize_xrpl_address(&prohibited_xrpl_address, &config.xrpl_network)?;
         PROHIBITED_XRPL_ADDRESSES.save(deps.storage, prohibited_xrpl_address, &Empty {})?;
     }
 
@@ -1680,19 +2382,113 @@ fn cancel_pending_operation(
     )?;
 
     let operation = check_operation_exists(deps.storage, operation_id)?;
+    let mut response = Response::new();
+    let msgs = invalidate_operation(deps.storage, &env, &operation, operation_id, &mut response)?;
+
+    Ok(response
+        .add_attribute("action", ContractActions::CancelPendingOperation.as_str())
+        .add_attribute("sender", sender)
+        .add_messages(msgs))
+}
+
+fn expire_operation(
+    env: Env,
+    deps: DepsMut,
+    sender: Addr,
+    operation_id: u64,
+) -> ContractResult<Response> {
+    let operation = check_operation_exists(deps.storage, operation_id)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    let OperationType::CosmosToXRPLTransfer {
+        sender: transfer_sender,
+        ..
+    } = &operation.operation_type
+    else {
+        return Err(ContractError::OperationCannotExpire {});
+    };
+    if transfer_sender.ne(&sender) {
+        return Err(ContractError::UnauthorizedSender {});
+    }
+
+    // Relayers stop signing and submitting the operation at its expiration. We wait some extra time so that
+    // transactions that were submitted right before are confirmed instead of refunded
+    let expiration =
+        operation_expiration(&operation, &config).ok_or(ContractError::OperationCannotExpire {})?;
+    if env.block.time.seconds() < expiration.saturating_add(OPERATION_EXPIRATION_GRACE_PERIOD) {
+        return Err(ContractError::OperationNotExpired {});
+    }
+
+    // With enough signatures the transaction can still be submitted to XRPL, so refunding it could pay the transfer twice.
+    // Relayers keep completing it after its expiration instead
+    if is_signed_by_quorum(&operation, &config) {
+        return Err(ContractError::OperationSignedByQuorum {});
+    }
+
+    let mut response = Response::new();
+    let msgs = invalidate_operation(deps.storage, &env, &operation, operation_id, &mut response)?;
+
+    Ok(response
+        .add_attribute("action", ContractActions::ExpireOperation.as_str())
+        .add_attribute("sender", sender)
+        .add_attribute("operation_id", operation_id.to_string())
+        .add_messages(msgs))
+}
+
+fn cancel_my_transfer(
+    env: Env,
+    deps: DepsMut,
+    sender: Addr,
+    operation_id: u64,
+) -> ContractResult<Response> {
+    let operation = check_operation_exists(deps.storage, operation_id)?;
+
+    let OperationType::CosmosToXRPLTransfer {
+        sender: transfer_sender,
+        ..
+    } = &operation.operation_type
+    else {
+        return Err(ContractError::UnauthorizedSender {});
+    };
+    if transfer_sender.ne(&sender) {
+        return Err(ContractError::UnauthorizedSender {});
+    }
+
+    // Once a relayer signed, the transaction might be submitted to XRPL at any moment so it can't be cancelled anymore
+    if !operation.signatures.is_empty() {
+        return Err(ContractError::OperationAlreadySigned {});
+    }
+
+    let mut response = Response::new();
+    let msgs = invalidate_operation(deps.storage, &env, &operation, operation_id, &mut response)?;
+
+    Ok(response
+        .add_attribute("action", ContractActions::CancelMyTransfer.as_str())
+        .add_attribute("sender", sender)
+        .add_attribute("operation_id", operation_id.to_string())
+        .add_messages(msgs))
+}
+
+// Handles the operation as if relayers returned an invalid result, so its ticket is returned and funds are refunded
+fn invalidate_operation(
+    storage: &mut dyn Storage,
+    env: &Env,
+    operation: &Operation,
+    operation_id: u64,
+    response: &mut Response,
+) -> ContractResult<Vec<CosmosMsg>> {
     // We'll provide a TransactionResult::Invalid evidence to the handlers so that they perform the right action
     let transaction_result = &TransactionResult::Invalid;
     let operation_result = match operation.operation_type {
         OperationType::AllocateTickets { .. } => Some(TicketsAllocation { tickets: None }),
         _ => None,
     };
-    let mut response = Response::new();
 
-    let config = CONFIG.load(deps.storage)?;
+    let config = CONFIG.load(storage)?;
     // We handle the operation with an invalid result
-    let msgs = handle_operation(
-        deps.storage,
-        &operation,
+    handle_operation(
+        storage,
+        operation,
         &operation_result,
         transaction_result,
         &None,
@@ -1700,13 +2496,66 @@ fn cancel_pending_operation(
         operation.ticket_sequence,
         &config.token_factory_addr,
         &env.contract.address,
-        &mut response,
+        response,
+    )
+}
+
+fn update_auto_claim_refunds(
+    deps: DepsMut,
+    sender: Addr,
+    auto_claim_refunds: bool,
+) -> ContractResult<Response> {
+    check_authorization(
+        deps.as_ref().storage,
+        &sender,
+        &ContractActions::UpdateAutoClaimRefunds,
     )?;
 
-    Ok(response
-        .add_attribute("action", ContractActions::CancelPendingOperation.as_str())
+    let mut config = CONFIG.load(deps.storage)?;
+    config.auto_claim_refunds = auto_claim_refunds;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", ContractActions::UpdateAutoClaimRefunds.as_str())
         .add_attribute("sender", sender)
-        .add_messages(msgs))
+        .add_attribute("auto_claim_refunds", auto_claim_refunds.to_string()))
+}
+
+fn set_auto_claim_refunds(deps: DepsMut, sender: Addr, enabled: bool) -> ContractResult<Response> {
+    if enabled {
+        AUTO_CLAIM_REFUNDS.save(deps.storage, sender.clone(), &Empty {})?;
+    } else {
+        AUTO_CLAIM_REFUNDS.remove(deps.storage, sender.clone());
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", ContractActions::SetAutoClaimRefunds.as_str())
+        .add_attribute("sender", sender)
+        .add_attribute("enabled", enabled.to_string()))
+}
+
+fn update_operation_timeout(
+    deps: DepsMut,
+    sender: Addr,
+    operation_timeout: Option<u64>,
+) -> ContractResult<Response> {
+    check_authorization(
+        deps.as_ref().storage,
+        &sender,
+        &ContractActions::UpdateOperationTimeout,
+    )?;
+
+    let mut config = CONFIG.load(deps.storage)?;
+    config.operation_timeout = operation_timeout;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", ContractActions::UpdateOperationTimeout.as_str())
+        .add_attribute("sender", sender)
+        .add_attribute(
+            "operation_timeout",
+            operation_timeout.map_or("none".to_string(), |timeout| timeout.to_string()),
+        ))
 }
 
 fn execute_add_rate_limit(
@@ -1784,9 +2633,121 @@ fn execute_reset_rate_limit_quota(
             vec![],
         )?))
 }
+fn sweep_pending_universal_swaps(
+    deps: DepsMut,
+    sender: Addr,
+    nonces: Vec<u64>,
+) -> ContractResult<Response> {
+    check_authorization(
+        deps.as_ref().storage,
+        &sender,
+        &ContractActions::SweepPendingUniversalSwaps,
+    )?;
+
+    // Nonces without an entry are ignored
+    for nonce in &nonces {
+        PENDING_UNIVERSAL_SWAPS.remove(deps.storage, *nonce);
+    }
+
+    Ok(Response::new()
+        .add_attribute(
+            "action",
+            ContractActions::SweepPendingUniversalSwaps.as_str(),
+        )
+        .add_attribute("sender", sender)
+        .add_attribute("swept", nonces.len().to_string()))
+}
+
+fn recover_untracked_ibc_forward(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    tx_hash: String,
+) -> ContractResult<Response> {
+    check_authorization(
+        deps.as_ref().storage,
+        &sender,
+        &ContractActions::RecoverUntrackedIBCForward,
+    )?;
+
+    let response = park_untracked_ibc_forward(deps.storage, env.block.time.seconds(), tx_hash)?;
+
+    Ok(response
+        .add_attribute(
+            "action",
+            ContractActions::RecoverUntrackedIBCForward.as_str(),
+        )
+        .add_attribute("sender", sender))
+}
+
+fn prune_stale_evidences(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    older_than_seconds: u64,
+    limit: Option<u32>,
+) -> ContractResult<Response> {
+    // Anyone can clean up evidences that have been stuck for long enough, only the owner can prune more recent ones
+    if older_than_seconds < MIN_STALE_EVIDENCE_AGE && !is_owner(deps.storage, &sender)? {
+        return Err(ContractError::StaleEvidenceAgeTooLow {
+            min_age: MIN_STALE_EVIDENCE_AGE,
+        });
+    }
+
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+    let cutoff = env.block.time.seconds().saturating_sub(older_than_seconds);
+    // The index is sorted by first seen time so we only go through the evidences that are stale (first seen at or before the cutoff)
+    let stale_evidences: Vec<(u64, String)> = TX_EVIDENCES_FIRST_SEEN
+        .keys(
+            deps.storage,
+            None,
+            Some(Bound::exclusive((cutoff.saturating_add(1), String::new()))),
+            Order::Ascending,
+        )
+        .take(limit as usize)
+        .filter_map(Result::ok)
+        .collect();
+
+    for (first_seen, evidence_hash) in &stale_evidences {
+        match TX_EVIDENCES.may_load(deps.storage, evidence_hash.clone())? {
+            Some(evidences) => remove_evidences(deps.storage, evidence_hash.clone(), &evidences),
+            None => {
+                TX_EVIDENCES_FIRST_SEEN.remove(deps.storage, (*first_seen, evidence_hash.clone()))
+            }
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", ContractActions::PruneStaleEvidences.as_str())
+        .add_attribute("sender", sender)
+        .add_attribute("pruned", stale_evidences.len().to_string()))
+}
+
+fn index_legacy_evidences(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    limit: Option<u32>,
+) -> ContractResult<Response> {
+    check_authorization(
+        deps.as_ref().storage,
+        &sender,
+        &ContractActions::IndexLegacyEvidences,
+    )?;
+
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+    let (indexed, completed) = index_evidences_first_seen(deps.storage, env.block.time, limit)?;
+
+    Ok(Response::new()
+        .add_attribute("action", ContractActions::IndexLegacyEvidences.as_str())
+        .add_attribute("sender", sender)
+        .add_attribute("indexed", indexed.to_string())
+        .add_attribute("completed", completed.to_string()))
+}
+
 // ********** Queries **********
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_json_binary(&query_config(deps)?),
         QueryMsg::XRPLTokens {
@@ -1834,6 +2795,25 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::ProhibitedXRPLAddresses {} => {
             to_json_binary(&query_prohibited_xrpl_addresses(deps))
         }
+        QueryMsg::PendingUniversalSwaps {
+            start_after_key,
+            limit,
+        } => to_json_binary(&query_pending_universal_swaps(deps, start_after_key, limit)),
+        QueryMsg::SigningPayload { operation_id } => {
+            to_json_binary(&query_signing_payload(deps, operation_id)?)
+        }
+        QueryMsg::SignedTransaction { operation_id } => {
+            to_json_binary(&query_signed_transaction(deps, env, operation_id)?)
+        }
+        QueryMsg::RelayerSetChange {} => to_json_binary(&query_relayer_set_change(deps)?),
+        QueryMsg::ConflictingEvidences { tx_hash } => {
+            to_json_binary(&query_conflicting_evidences(deps, tx_hash)?)
+        }
+        QueryMsg::RelayerStats { address } => to_json_binary(&query_relayer_stats(deps, address)?),
+        QueryMsg::AllRelayerStats {} => to_json_binary(&query_all_relayer_stats(deps)),
+        QueryMsg::AutoClaimRefunds { address } => {
+            to_json_binary(&query_auto_claim_refunds(deps, address)?)
+        }
     }
 }
 
@@ -1959,6 +2939,7 @@ fn query_pending_refunds(
         .map(|(key, pr)| {
             last_key = Some(key);
             PendingRefund {
+                destination: pr.destination().to_owned(),
                 id: pr.id,
                 xrpl_tx_hash: pr.xrpl_tx_hash,
                 coin: pr.coin,
@@ -2010,6 +2991,53 @@ fn query_transaction_evidences(
     }
 }
 
+fn query_conflicting_evidences(
+    deps: Deps,
+    tx_hash: String,
+) -> StdResult<ConflictingEvidencesResponse> {
+    let tx_hash = tx_hash.to_uppercase();
+    let evidence_hashes: Vec<String> = TX_HASH_EVIDENCES
+        .prefix(tx_hash.clone())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .filter_map(Result::ok)
+        .collect();
+
+    let mut evidences = vec![];
+    for evidence_hash in evidence_hashes {
+        let e = TX_EVIDENCES.load(deps.storage, evidence_hash.clone())?;
+        evidences.push(TransactionEvidence {
+            hash: evidence_hash,
+            relayer_addresses: e.relayer_cosmos_addresses,
+        });
+    }
+
+    Ok(ConflictingEvidencesResponse { tx_hash, evidences })
+}
+
+fn query_relayer_stats(deps: Deps, address: Addr) -> StdResult<RelayerStatsResponse> {
+    let stats = RELAYER_STATS
+        .may_load(deps.storage, address.clone())?
+        .unwrap_or_default();
+
+    Ok(RelayerStatsResponse { address, stats })
+}
+
+fn query_all_relayer_stats(deps: Deps) -> AllRelayerStatsResponse {
+    let relayer_stats = RELAYER_STATS
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter_map(Result::ok)
+        .map(|(address, stats)| RelayerStatsResponse { address, stats })
+        .collect();
+
+    AllRelayerStatsResponse { relayer_stats }
+}
+
+fn query_auto_claim_refunds(deps: Deps, address: Addr) -> StdResult<AutoClaimRefundsResponse> {
+    let enabled = is_auto_claim_enabled(deps.storage, &address)?;
+
+    Ok(AutoClaimRefundsResponse { address, enabled })
+}
+
 fn query_processed_tx(deps: Deps, hash: String) -> bool {
     PROCESSED_TXS.has(deps.storage, hash)
 }
@@ -2050,6 +3078,188 @@ fn query_prohibited_xrpl_addresses(deps: Deps) -> ProhibitedXRPLAddressesRespons
     }
 }
 
+fn query_signing_payload(deps: Deps, operation_id: u64) -> StdResult<SigningPayloadResponse> {
+    let operation = PENDING_OPERATIONS.load(deps.storage, operation_id)?;
+    let config = CONFIG.load(deps.storage)?;
+    let number_of_signers = max_signers_for_threshold(&config.relayers, config.evidence_threshold);
+
+    let transaction =
+        serialize_operation(&operation, &config.bridge_xrpl_address, number_of_signers)
+            .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    let signing_payloads = config
+        .relayers
+        .iter()
+        .map(|relayer| {
+            multisigning_data(
+                &operation,
+                &config.bridge_xrpl_address,
+                number_of_signers,
+                &relayer.xrpl_address,
+            )
+            .map(|payload| RelayerSigningPayload {
+                relayer_cosmos_address: relayer.cosmos_address.clone(),
+                xrpl_address: relayer.xrpl_address.clone(),
+                payload: to_hex(&payload),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    Ok(SigningPayloadResponse {
+        operation_id,
+        operation_version: operation.version,
+        transaction: to_hex(&transaction),
+        signing_payloads,
+    })
+}
+
+fn query_signed_transaction(
+    deps: Deps,
+    env: Env,
+    operation_id: u64,
+) -> StdResult<SignedTransactionResponse> {
+    let operation = PENDING_OPERATIONS.load(deps.storage, operation_id)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    // Relayers must not submit expired operations because their sender can get a refund
+    let expired = is_operation_expired(&operation, &config, env.block.time.seconds());
+
+    let number_of_signers = max_signers_for_threshold(&config.relayers, config.evidence_threshold);
+
+    // We add signatures until their weight reaches the threshold. The fee covers the worst case so including
+    // more signatures than needed isn't necessary
+    let mut signers = vec![];
+    let mut accumulated_weight = 0;
+    for signature in operation.signatures.iter() {
+        if accumulated_weight >= config.evidence_threshold {
+            break;
+        }
+        if let Some(relayer) = config
+            .relayers
+            .iter()
+            .find(|relayer| relayer.cosmos_address == signature.relayer_cosmos_address)
+        {
+            accumulated_weight += u32::from(relayer.signer_weight());
+            signers.push(TransactionSigner {
+                account: relayer.xrpl_address.clone(),
+                signing_pub_key: relayer.xrpl_pub_key.clone(),
+                txn_signature: signature.signature.clone(),
+            });
+        }
+    }
+
+    let tx_blob = if expired || accumulated_weight < config.evidence_threshold {
+        None
+    } else {
+        let tx_blob = serialize_signed_operation(
+            &operation,
+            &config.bridge_xrpl_address,
+            number_of_signers,
+            &signers,
+        )
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+        Some(to_hex(&tx_blob))
+    };
+
+    Ok(SignedTransactionResponse {
+        operation_id,
+        operation_version: operation.version,
+        tx_blob,
+    })
+}
+
+fn query_relayer_set_change(deps: Deps) -> StdResult<RelayerSetChangeResponse> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let pending = match PENDING_RELAYER_SET_CHANGE.may_load(deps.storage)? {
+        Some(change) => match PENDING_OPERATIONS
+            .load(deps.storage, change.operation_id)?
+            .operation_type
+        {
+            OperationType::RotateKeys {
+                new_relayers,
+                new_evidence_threshold,
+            } => {
+                let find = |relayers: &[Relayer], cosmos_address: &Addr| {
+                    relayers
+                        .iter()
+                        .find(|relayer| &relayer.cosmos_address == cosmos_address)
+                        .cloned()
+                };
+
+                let added = new_relayers
+                    .iter()
+                    .filter(|relayer| find(&config.relayers, &relayer.cosmos_address).is_none())
+                    .cloned()
+                    .collect();
+                let removed = config
+                    .relayers
+                    .iter()
+                    .filter(|relayer| find(&new_relayers, &relayer.cosmos_address).is_none())
+                    .cloned()
+                    .collect();
+                let updated = new_relayers
+                    .iter()
+                    .filter(|relayer| {
+                        find(&config.relayers, &relayer.cosmos_address)
+                            .is_some_and(|current| current != **relayer)
+                    })
+                    .cloned()
+                    .collect();
+
+                Some(PendingRelayerSet {
+                    operation_id: change.operation_id,
+                    halts_bridge: change.halts_bridge,
+                    new_relayers,
+                    new_evidence_threshold,
+                    added,
+                    removed,
+                    updated,
+                })
+            }
+            _ => None,
+        },
+        None => None,
+    };
+
+    Ok(RelayerSetChangeResponse {
+        relayers: config.relayers,
+        evidence_threshold: config.evidence_threshold,
+        pending,
+    })
+}
+
+fn query_pending_universal_swaps(
+    deps: Deps,
+    start_after_key: Option<u64>,
+    limit: Option<u32>,
+) -> PendingUniversalSwapsResponse {
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+    let start = start_after_key.map(Bound::exclusive);
+    let mut last_key = None;
+    let pending_universal_swaps: Vec<PendingUniversalSwap> = PENDING_UNIVERSAL_SWAPS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit as usize)
+        .filter_map(Result::ok)
+        .map(|(nonce, swap)| {
+            last_key = Some(nonce);
+            PendingUniversalSwap {
+                nonce,
+                recipient: swap.recipient,
+                coin: swap.coin,
+                tx_hash: swap.tx_hash,
+                fallback: swap.fallback,
+            }
+        })
+        .collect();
+
+    PendingUniversalSwapsResponse {
+        last_key,
+        pending_universal_swaps,
+    }
+}
+
 // ********** Helpers **********
 
 pub fn validate_xrpl_currency(currency: &str) -> Result<(), ContractError> {
@@ -2267,7 +3477,16 @@ pub fn assert_bridge_active(deps: Deps) -> Result<(), ContractError> {
     Ok(())
 }
 
-fn update_bridge_state(
+// Helper function to check that funds can leave the bridge, which is allowed while it's halted
+pub fn assert_funds_not_frozen(deps: Deps) -> Result<(), ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.bridge_state.eq(&BridgeState::FundsFrozen) {
+        return Err(ContractError::FundsFrozen {});
+    }
+    Ok(())
+}
+
+pub fn update_bridge_state(
     storage: &mut dyn Storage,
     bridge_state: BridgeState,
 ) -> Result<(), ContractError> {
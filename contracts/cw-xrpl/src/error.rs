@@ -3,7 +3,10 @@ use cw_ownable::OwnershipError;
 use cw_utils::PaymentError;
 use thiserror::Error;
 
-use crate::contract::{MAX_COSMOS_TOKEN_DECIMALS, MAX_RELAYERS, MAX_TICKETS};
+use crate::{
+    contract::{MAX_COSMOS_TOKEN_DECIMALS, MAX_RELAYERS, MAX_TICKETS},
+    memo::MAX_MEMO_LENGTH,
+};
 
 #[derive(Error, Debug)]
 pub enum ContractError {
@@ -22,7 +25,9 @@ pub enum ContractError {
     #[error("Payment error: {0}")]
     Payment(#[from] PaymentError),
 
-    #[error("InvalidThreshold: Threshold can not be 0 or higher than amount of relayers")]
+    #[error(
+        "InvalidThreshold: Threshold can not be 0 or higher than the sum of the weights of the relayers"
+    )]
     InvalidThreshold {},
 
     #[error("InvalidXRPLAddress: XRPL address {} is not valid", address)]
@@ -200,6 +205,91 @@ pub enum ContractError {
 
     #[error("Got a submessage reply with unknown id: {id}")]
     UnknownReplyId { id: u64 },
+
+    #[error("UniversalSwapNonceOverflow: No more universal swap nonces available")]
+    UniversalSwapNonceOverflow {},
+
+    #[error("MemoTooLong: The memo can't be longer than {} bytes", MAX_MEMO_LENGTH)]
+    MemoTooLong {},
+
+    #[error("UnsupportedMemoVersion: Memo version {} is not supported", version)]
+    UnsupportedMemoVersion { version: u16 },
+
+    #[error("InvalidMemo: The action of the memo has missing or invalid fields")]
+    InvalidMemo {},
+
+    #[error(
+        "UnknownIBCForward: There is no IBC forward for channel {} and sequence {}",
+        channel,
+        sequence
+    )]
+    UnknownIBCForward { channel: String, sequence: u64 },
+
+    #[error("UntrackedIBCForwardNotFound: There is no untracked IBC forward for this transaction")]
+    UntrackedIBCForwardNotFound {},
+
+    #[error("EmptyTransferBatch: A batch must contain at least one transfer")]
+    EmptyTransferBatch {},
+
+    #[error(
+        "InvalidBatchFunds: The funds sent must be exactly the sum of the coins of all transfers"
+    )]
+    InvalidBatchFunds {},
+
+    #[error(
+        "NotEnoughTickets: {} tickets are needed but only {} can be used",
+        required,
+        available
+    )]
+    NotEnoughTickets { required: usize, available: usize },
+
+    #[error("DestinationTagMismatch: The destination tag doesn't match the one encoded in the X-address")]
+    DestinationTagMismatch {},
+
+    #[error(
+        "XAddressWrongNetwork: X-address {} doesn't belong to the XRPL network of the bridge",
+        address
+    )]
+    XAddressWrongNetwork { address: String },
+
+    #[error("InvalidSignature: The signature is not valid for this operation and relayer")]
+    InvalidSignature {},
+
+    #[error("InvalidXRPLTransaction: The XRPL transaction of this operation can't be serialized")]
+    InvalidXRPLTransaction {},
+
+    #[error("InvalidRelayerWeight: The weight of a relayer can't be 0")]
+    InvalidRelayerWeight {},
+
+    #[error("RelayerNotFound: There is no relayer with this address in the current set")]
+    RelayerNotFound {},
+
+    #[error("RelayerKeyChangePending: The key of this relayer is being removed or replaced, it can only sign the key rotation")]
+    RelayerKeyChangePending {},
+
+    #[error(
+        "StaleEvidenceAgeTooLow: Only the owner can prune evidences that are newer than {} seconds",
+        min_age
+    )]
+    StaleEvidenceAgeTooLow { min_age: u64 },
+
+    #[error("OperationExpired: This operation expired and can't be signed anymore")]
+    OperationExpired {},
+
+    #[error("OperationCannotExpire: Only transfers to XRPL can expire and only if an operation timeout is set")]
+    OperationCannotExpire {},
+
+    #[error("OperationNotExpired: This operation can't be expired yet")]
+    OperationNotExpired {},
+
+    #[error("OperationSignedByQuorum: This operation has enough signatures to be executed in XRPL and can't expire")]
+    OperationSignedByQuorum {},
+
+    #[error("OperationAlreadySigned: This operation was already signed by relayers and can't be cancelled")]
+    OperationAlreadySigned {},
+
+    #[error("FundsFrozen: The funds of the bridge are frozen and can't be claimed")]
+    FundsFrozen {},
 }
 
 pub type ContractResult<T> = std::result::Result<T, ContractError>;
@@ -1,10 +1,19 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{to_json_vec, Addr, Empty, HexBinary, Storage, Uint128};
+use cosmwasm_std::{
+    to_json_vec, Addr, Empty, Event, HexBinary, Order, Response, Storage, Timestamp, Uint128,
+};
+use cw_storage_plus::Bound;
 use sha2::{Digest, Sha256};
 
 use crate::{
+    contract::update_bridge_state,
     error::{ContractError, ContractResult},
-    state::{CONFIG, PROCESSED_TXS, TX_EVIDENCES},
+    memo::{parse_memo, MAX_MEMO_LENGTH},
+    relayer::{record_relayer_activity, weight_of, RelayerActivity},
+    state::{
+        BridgeState, CONFIG, LEGACY_EVIDENCES_CURSOR, PROCESSED_TXS, TX_EVIDENCES,
+        TX_EVIDENCES_FIRST_SEEN, TX_HASH_EVIDENCES,
+    },
 };
 
 #[cw_serde]
@@ -18,6 +27,12 @@ pub enum Evidence {
         amount: Uint128,
         recipient: Addr,
         memo: Option<String>,
+        // These fields are not serialized when empty so that the hash of evidences without them stays the same
+        #[serde(skip_serializing_if = "Option::is_none")]
+        source_tag: Option<u32>,
+        // MemoData of the memos of the XRPL transaction as they were received
+        #[serde(skip_serializing_if = "Option::is_none")]
+        raw_memos: Option<Vec<HexBinary>>,
     },
     // This type will be used for ANY transaction that comes from XRPL and that is notifying a confirmation or rejection
     #[serde(rename = "xrpl_transaction_result")]
@@ -63,6 +78,14 @@ impl Evidence {
         Ok(hash_bytes(&to_hash_bytes))
     }
 
+    // Invalid transaction results are the only evidences without a transaction hash
+    pub fn get_optional_tx_hash(&self) -> Option<String> {
+        match self {
+            Self::XRPLTransactionResult { tx_hash: None, .. } => None,
+            _ => Some(self.get_tx_hash()),
+        }
+    }
+
     pub fn get_tx_hash(&self) -> String {
         match self {
             Self::XRPLToCosmosTransfer { tx_hash, .. } => tx_hash.clone(),
@@ -83,10 +106,25 @@ impl Evidence {
     // Function for basic validation of evidences in case relayers send something that is not valid
     pub fn validate_basic(&self) -> ContractResult<()> {
         match self {
-            Self::XRPLToCosmosTransfer { amount, .. } => {
+            Self::XRPLToCosmosTransfer {
+                amount,
+                memo,
+                raw_memos,
+                ..
+            } => {
                 if amount.is_zero() {
                     return Err(ContractError::InvalidAmount {});
                 }
+                // Structured memos with invalid values are rejected, anything else is a plain transfer
+                if let Some(memo) = memo {
+                    parse_memo(memo)?;
+                }
+                // XRPL doesn't allow more than 1KB of memos in a transaction
+                if let Some(raw_memos) = raw_memos {
+                    if raw_memos.iter().map(|m| m.len()).sum::<usize>() > MAX_MEMO_LENGTH {
+                        return Err(ContractError::MemoTooLong {});
+                    }
+                }
                 Ok(())
             }
             Self::XRPLTransactionResult {
@@ -141,23 +179,33 @@ impl Evidence {
 #[cw_serde]
 pub struct Evidences {
     pub relayer_cosmos_addresses: Vec<Addr>,
+    // Block time when the first relayer provided this evidence. Evidences stored before this field existed have time 0
+    #[serde(default)]
+    pub first_seen: Timestamp,
+    #[serde(default)]
+    pub tx_hash: Option<String>,
 }
 
 pub fn hash_bytes(bytes: &[u8]) -> String {
     HexBinary::from(Sha256::digest(bytes).as_slice()).to_hex()
 }
 
+// Returns the relayers that provided the evidence if it reached the threshold
 pub fn handle_evidence(
     storage: &mut dyn Storage,
+    block_time: Timestamp,
     sender: Addr,
     evidence: &Evidence,
-) -> Result<bool, ContractError> {
+    response: &mut Response,
+) -> Result<Option<Vec<Addr>>, ContractError> {
     let operation_valid = evidence.is_operation_valid();
 
     if operation_valid && PROCESSED_TXS.has(storage, evidence.get_tx_hash()) {
         return Err(ContractError::OperationAlreadyExecuted {});
     }
 
+    record_relayer_activity(storage, &sender, RelayerActivity::Evidence, block_time)?;
+
     let mut evidences: Evidences;
     // Relayers can only provide the evidence once
     match TX_EVIDENCES.may_load(storage, evidence.get_hash()?)? {
@@ -171,24 +219,159 @@ pub fn handle_evidence(
         None => {
             evidences = Evidences {
                 relayer_cosmos_addresses: vec![sender],
+                first_seen: block_time,
+                tx_hash: evidence.get_optional_tx_hash(),
             };
+            if let Some(tx_hash) = &evidences.tx_hash {
+                handle_evidence_conflict(storage, tx_hash, evidence.get_hash()?, response)?;
+            }
         }
     }
 
+    // While there are different evidences for the same transaction none of them is executed, whatever the bridge state,
+    // they are only stored until the owner investigates the conflict
+    let conflicting = match &evidences.tx_hash {
+        Some(tx_hash) => has_conflicting_evidences(storage, tx_hash, &evidence.get_hash()?),
+        None => false,
+    };
     let config = CONFIG.load(storage)?;
-    if evidences.relayer_cosmos_addresses.len() >= config.evidence_threshold as usize {
+    if !conflicting
+        && weight_of(&config.relayers, &evidences.relayer_cosmos_addresses)
+            >= config.evidence_threshold
+    {
+        for relayer in &evidences.relayer_cosmos_addresses {
+            record_relayer_activity(
+                storage,
+                relayer,
+                RelayerActivity::ThresholdCompletion,
+                block_time,
+            )?;
+        }
         // We only registered the transaction as processed if its execution didn't fail (it wasn't Invalid)
         if operation_valid {
             PROCESSED_TXS.save(storage, evidence.get_tx_hash(), &Empty {})?;
         }
         // If there is just one relayer there is nothing to delete
         if evidences.relayer_cosmos_addresses.len() != 1 {
-            TX_EVIDENCES.remove(storage, evidence.get_hash()?);
+            remove_evidences(storage, evidence.get_hash()?, &evidences);
         }
-        return Ok(true);
+        return Ok(Some(evidences.relayer_cosmos_addresses));
     }
 
     TX_EVIDENCES.save(storage, evidence.get_hash()?, &evidences)?;
+    TX_EVIDENCES_FIRST_SEEN.save(
+        storage,
+        (evidences.first_seen.seconds(), evidence.get_hash()?),
+        &Empty {},
+    )?;
+    if let Some(tx_hash) = &evidences.tx_hash {
+        TX_HASH_EVIDENCES.save(storage, (tx_hash.clone(), evidence.get_hash()?), &Empty {})?;
+    }
+
+    Ok(None)
+}
+
+// Relayers that provide a different evidence for a transaction that already has one are not working with the same data.
+// This is reported with an event and, if configured, the bridge is halted so that the owner can investigate
+fn handle_evidence_conflict(
+    storage: &mut dyn Storage,
+    tx_hash: &str,
+    evidence_hash: String,
+    response: &mut Response,
+) -> Result<(), ContractError> {
+    let conflicting_evidence_hashes: Vec<String> = TX_HASH_EVIDENCES
+        .prefix(tx_hash.to_owned())
+        .keys(storage, None, None, Order::Ascending)
+        .filter_map(Result::ok)
+        .collect();
+
+    if conflicting_evidence_hashes.is_empty() {
+        return Ok(());
+    }
+
+    let config = CONFIG.load(storage)?;
+    let halt_bridge =
+        config.halt_on_evidence_conflict && config.bridge_state.eq(&BridgeState::Active);
+    if halt_bridge {
+        update_bridge_state(storage, BridgeState::Halted)?;
+    }
+
+    *response = response.to_owned().add_event(
+        Event::new("evidence_conflict")
+            .add_attribute("tx_hash", tx_hash)
+            .add_attribute("evidence_hash", evidence_hash)
+            .add_attribute(
+                "conflicting_evidence_hashes",
+                conflicting_evidence_hashes.join(","),
+            )
+            .add_attribute("bridge_halted", halt_bridge.to_string()),
+    );
+
+    Ok(())
+}
+
+// Whether a different evidence than the given one is stored for the same XRPL transaction
+fn has_conflicting_evidences(storage: &dyn Storage, tx_hash: &str, evidence_hash: &str) -> bool {
+    TX_HASH_EVIDENCES
+        .prefix(tx_hash.to_owned())
+        .keys(storage, None, None, Order::Ascending)
+        .filter_map(Result::ok)
+        .any(|stored_evidence_hash| stored_evidence_hash != evidence_hash)
+}
+
+pub fn remove_evidences(storage: &mut dyn Storage, evidence_hash: String, evidences: &Evidences) {
+    TX_EVIDENCES_FIRST_SEEN.remove(
+        storage,
+        (evidences.first_seen.seconds(), evidence_hash.clone()),
+    );
+    if let Some(tx_hash) = &evidences.tx_hash {
+        TX_HASH_EVIDENCES.remove(storage, (tx_hash.clone(), evidence_hash.clone()));
+    }
+    TX_EVIDENCES.remove(storage, evidence_hash);
+}
+
+// Evidences stored before the first seen index existed can't be found when pruning.
+// We check up to limit evidences, continuing from where the previous call stopped, and index the ones without first seen
+// time as first seen at the given time so that they can be pruned once they are old enough.
+// Returns how many evidences were indexed and whether all evidences were checked
+pub fn index_evidences_first_seen(
+    storage: &mut dyn Storage,
+    block_time: Timestamp,
+    limit: u32,
+) -> ContractResult<(usize, bool)> {
+    let start = LEGACY_EVIDENCES_CURSOR
+        .may_load(storage)?
+        .map(Bound::exclusive);
+    let checked_evidences: Vec<(String, Evidences)> = TX_EVIDENCES
+        .range(storage, start, None, Order::Ascending)
+        .take(limit as usize)
+        .filter_map(Result::ok)
+        .collect();
+
+    let completed = checked_evidences.len() < limit as usize;
+    match checked_evidences.last() {
+        Some((evidence_hash, _)) if !completed => {
+            LEGACY_EVIDENCES_CURSOR.save(storage, evidence_hash)?
+        }
+        _ => LEGACY_EVIDENCES_CURSOR.remove(storage),
+    }
+
+    let mut indexed = 0;
+    for (evidence_hash, mut evidences) in checked_evidences {
+        if evidences.first_seen != Timestamp::default() {
+            continue;
+        }
+        evidences.first_seen = block_time;
+        TX_EVIDENCES.save(storage, evidence_hash.clone(), &evidences)?;
+        TX_EVIDENCES_FIRST_SEEN.save(storage, (block_time.seconds(), evidence_hash), &Empty {})?;
+        indexed += 1;
+    }
+
+    Ok((indexed, completed))
+}
 
-    Ok(false)
+pub fn clear_evidences(storage: &mut dyn Storage) {
+    TX_EVIDENCES.clear(storage);
+    TX_EVIDENCES_FIRST_SEEN.clear(storage);
+    TX_HASH_EVIDENCES.clear(storage);
 }
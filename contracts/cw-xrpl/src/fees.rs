@@ -2,7 +2,7 @@ use cosmwasm_std::{coin, Addr, Coin, Storage, Uint128};
 
 use crate::{
     error::ContractError,
-    state::{CONFIG, FEES_COLLECTED, FEE_REMAINDERS},
+    state::{FeeDistribution, CONFIG, FEES_COLLECTED, FEE_REMAINDERS},
 };
 
 pub fn amount_after_bridge_fees(
@@ -21,15 +21,72 @@ pub fn handle_fee_collection(
     bridging_fee: Uint128,
     token_denom: String,
     remainder: Uint128,
+    participants: Option<&[Addr]>,
 ) -> Result<Uint128, ContractError> {
     // We add the bridging fee we charged and the truncated portion after all fees were charged
     let fee_collected = bridging_fee.checked_add(remainder)?;
 
-    collect_fees(storage, coin(fee_collected.u128(), token_denom))?;
+    collect_fees(
+        storage,
+        coin(fee_collected.u128(), token_denom),
+        participants,
+    )?;
     Ok(fee_collected)
 }
 
-fn collect_fees(storage: &mut dyn Storage, fee: Coin) -> Result<(), ContractError> {
+// Relayers that will get a part of the fee and the amount of parts each one gets
+fn fee_shares(
+    storage: &dyn Storage,
+    participants: Option<&[Addr]>,
+) -> Result<Vec<(Addr, u128)>, ContractError> {
+    let config = CONFIG.load(storage)?;
+    let all_relayers_equally = || -> Vec<(Addr, u128)> {
+        config
+            .relayers
+            .iter()
+            .map(|relayer| (relayer.cosmos_address.clone(), 1))
+            .collect()
+    };
+
+    let shares: Vec<(Addr, u128)> = match config.fee_distribution {
+        FeeDistribution::Equal => all_relayers_equally(),
+        FeeDistribution::Weighted => config
+            .relayers
+            .iter()
+            .map(|relayer| {
+                (
+                    relayer.cosmos_address.clone(),
+                    u128::from(relayer.signer_weight()),
+                )
+            })
+            .collect(),
+        FeeDistribution::ByParticipation => match participants {
+            // Participants that are not relayers anymore (e.g. removed by a key rotation) don't get fees
+            Some(participants) => {
+                let shares: Vec<(Addr, u128)> = config
+                    .relayers
+                    .iter()
+                    .filter(|relayer| participants.contains(&relayer.cosmos_address))
+                    .map(|relayer| (relayer.cosmos_address.clone(), 1))
+                    .collect();
+                if shares.is_empty() {
+                    all_relayers_equally()
+                } else {
+                    shares
+                }
+            }
+            None => all_relayers_equally(),
+        },
+    };
+
+    Ok(shares)
+}
+
+fn collect_fees(
+    storage: &mut dyn Storage,
+    fee: Coin,
+    participants: Option<&[Addr]>,
+) -> Result<(), ContractError> {
     // We only collect fees if there is something to collect
     // If for some reason there is a coin that we are not charging fees for, we don't collect it
     if !fee.amount.is_zero() {
@@ -40,36 +97,32 @@ fn collect_fees(storage: &mut dyn Storage, fee: Coin) -> Result<(), ContractErro
             None => fee.amount,
         };
 
-        // We will divide the total fee by the number of relayers to know how much we need to send to each relayer and the remainder will be saved for the next fee collection
-        let relayers = CONFIG.load(storage)?.relayers;
-        let amount_for_each_relayer =
-            total_fee.checked_div(Uint128::new(relayers.len().try_into().unwrap()))?;
+        // We will divide the total fee by the number of shares to know how much we need to send to each relayer and the remainder will be saved for the next fee collection
+        let shares = fee_shares(storage, participants)?;
+        let total_shares = Uint128::new(shares.iter().map(|(_, share)| share).sum());
+        let amount_per_share = total_fee.checked_div(total_shares)?;
 
         // If the amount is 0, there's nothing to send to the relayers
-        if !amount_for_each_relayer.is_zero() {
-            for relayer in &relayers {
+        if !amount_per_share.is_zero() {
+            for (relayer, share) in &shares {
+                let amount_for_relayer = amount_per_share.checked_mul(Uint128::new(*share))?;
                 // We get previous relayer fees collected to update them. If it's the first time the relayer gets fees, we initialize the array
                 let mut fees_collected = FEES_COLLECTED
-                    .may_load(storage, relayer.cosmos_address.clone())?
+                    .may_load(storage, relayer.clone())?
                     .unwrap_or_default();
 
                 // Add fees to the relayer fees collected
                 match fees_collected.iter_mut().find(|c| c.denom == fee.denom) {
-                    Some(coin) => coin.amount += amount_for_each_relayer,
-                    None => {
-                        fees_collected.push(coin(amount_for_each_relayer.u128(), fee.denom.clone()))
-                    }
+                    Some(coin) => coin.amount += amount_for_relayer,
+                    None => fees_collected.push(coin(amount_for_relayer.u128(), fee.denom.clone())),
                 }
 
-                FEES_COLLECTED.save(storage, relayer.cosmos_address.clone(), &fees_collected)?;
+                FEES_COLLECTED.save(storage, relayer.clone(), &fees_collected)?;
             }
         }
 
         // We get the remainder in case there is one and save it for the next fee collection
-        let remainder = total_fee.checked_sub(
-            amount_for_each_relayer
-                .checked_mul(Uint128::new(relayers.len().try_into().unwrap()))?,
-        )?;
+        let remainder = total_fee.checked_sub(amount_per_share.checked_mul(total_shares)?)?;
 
         // We save the remainder
         FEE_REMAINDERS.save(storage, fee.denom, &remainder)?;
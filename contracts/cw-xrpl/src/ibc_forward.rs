@@ -0,0 +1,248 @@
+use cosmwasm_std::{
+    Addr, Binary, CosmosMsg, Event, Response, Storage, SubMsg, SubMsgResult, Timestamp,
+};
+
+use crate::{
+    error::{ContractError, ContractResult},
+    msg::IBCLifecycleComplete,
+    operation::store_pending_refund,
+    state::{IBCForward, IBC_FORWARDS_IN_FLIGHT, PENDING_IBC_FORWARDS, UNTRACKED_IBC_FORWARDS},
+    universal_swap::next_reply_nonce,
+};
+
+// Timeout used for IBC forwards that don't specify one in the memo
+pub const DEFAULT_IBC_FORWARD_TIMEOUT_SECONDS: u64 = 600;
+// Max timeout that can be requested in the memo (1 week)
+pub const MAX_IBC_FORWARD_TIMEOUT_SECONDS: u64 = 604800;
+pub const IBC_TRANSFER_TYPE_URL: &str = "/ibc.applications.transfer.v1.MsgTransfer";
+const IBC_TRANSFER_PORT: &str = "transfer";
+
+// Builds the IBC transfer that sends the funds to the destination chain.
+// IbcMsg::Transfer doesn't support memos, so we send a MsgTransfer with the ibc_callback memo to get the
+// acknowledgement or timeout of the packet through sudo.
+// We reply always: on success to track the packet sequence and on error to park the funds that are still in the contract
+pub fn create_ibc_forward_submsg(
+    storage: &mut dyn Storage,
+    sender: &Addr,
+    timeout: Timestamp,
+    forward: IBCForward,
+) -> ContractResult<SubMsg> {
+    let nonce = next_reply_nonce(storage)?;
+
+    let transfer_msg = CosmosMsg::Stargate {
+        type_url: IBC_TRANSFER_TYPE_URL.to_string(),
+        value: encode_msg_transfer(sender, timeout, &forward),
+    };
+
+    PENDING_IBC_FORWARDS.save(storage, nonce, &forward)?;
+
+    Ok(SubMsg::reply_always(transfer_msg, nonce))
+}
+
+pub fn is_ibc_forward_reply(storage: &dyn Storage, nonce: u64) -> bool {
+    PENDING_IBC_FORWARDS.has(storage, nonce)
+}
+
+pub fn handle_ibc_forward_reply(
+    storage: &mut dyn Storage,
+    timestamp: u64,
+    nonce: u64,
+    result: SubMsgResult,
+) -> ContractResult<Response> {
+    let forward = PENDING_IBC_FORWARDS.load(storage, nonce)?;
+    PENDING_IBC_FORWARDS.remove(storage, nonce);
+
+    match result {
+        SubMsgResult::Ok(response) => {
+            let Some(sequence) = response
+                .data
+                .as_ref()
+                .and_then(|data| parse_transfer_sequence(data.as_slice()))
+            else {
+                // The packet was sent but we can't track it. Failing here would revert the evidence, so we let it through
+                // and keep the forward so that the owner can refund it if the packet is lost
+                UNTRACKED_IBC_FORWARDS.save(storage, forward.tx_hash.clone(), &forward)?;
+                return Ok(Response::new()
+                    .add_attribute("action", "ibc_forward")
+                    .add_event(
+                        Event::new("untracked_ibc_forward")
+                            .add_attribute("tx_hash", forward.tx_hash)
+                            .add_attribute("channel", forward.channel)
+                            .add_attribute("receiver", forward.receiver)
+                            .add_attribute("amount", forward.coin.to_string()),
+                    ));
+            };
+
+            IBC_FORWARDS_IN_FLIGHT.save(storage, (forward.channel.clone(), sequence), &forward)?;
+
+            Ok(Response::new()
+                .add_attribute("action", "ibc_forward")
+                .add_attribute("channel", forward.channel)
+                .add_attribute("sequence", sequence.to_string())
+                .add_attribute("receiver", forward.receiver)
+                .add_attribute("amount", forward.coin.to_string()))
+        }
+        // The transfer could not be sent so the funds are still in the contract
+        SubMsgResult::Err(err) => park_ibc_forward(
+            storage,
+            format!("{timestamp}-forward-{nonce}"),
+            forward,
+            err,
+        ),
+    }
+}
+
+// Called by the chain (through sudo) when the packet of an IBC forward was acknowledged or timed out
+pub fn handle_ibc_lifecycle_complete(
+    storage: &mut dyn Storage,
+    timestamp: u64,
+    lifecycle: IBCLifecycleComplete,
+) -> ContractResult<Response> {
+    match lifecycle {
+        IBCLifecycleComplete::IBCAck {
+            channel,
+            sequence,
+            ack,
+            success,
+        } => {
+            let forward = take_ibc_forward_in_flight(storage, channel.clone(), sequence)?;
+            if success {
+                return Ok(Response::new()
+                    .add_attribute("action", "ibc_forward_ack")
+                    .add_attribute("channel", channel)
+                    .add_attribute("sequence", sequence.to_string()));
+            }
+            park_ibc_forward(
+                storage,
+                format!("{timestamp}-{channel}-{sequence}"),
+                forward,
+                ack,
+            )
+        }
+        IBCLifecycleComplete::IBCTimeout { channel, sequence } => {
+            let forward = take_ibc_forward_in_flight(storage, channel.clone(), sequence)?;
+            park_ibc_forward(
+                storage,
+                format!("{timestamp}-{channel}-{sequence}"),
+                forward,
+                "timeout".to_string(),
+            )
+        }
+    }
+}
+
+// Used by the owner when the packet of a forward that we couldn't track was not delivered
+pub fn park_untracked_ibc_forward(
+    storage: &mut dyn Storage,
+    timestamp: u64,
+    tx_hash: String,
+) -> ContractResult<Response> {
+    let forward = UNTRACKED_IBC_FORWARDS
+        .may_load(storage, tx_hash.clone())?
+        .ok_or(ContractError::UntrackedIBCForwardNotFound {})?;
+    UNTRACKED_IBC_FORWARDS.remove(storage, tx_hash);
+
+    park_ibc_forward(
+        storage,
+        format!("{timestamp}-untracked-{}", forward.tx_hash),
+        forward,
+        "untracked".to_string(),
+    )
+}
+
+fn take_ibc_forward_in_flight(
+    storage: &mut dyn Storage,
+    channel: String,
+    sequence: u64,
+) -> ContractResult<IBCForward> {
+    let key = (channel.clone(), sequence);
+    let forward = IBC_FORWARDS_IN_FLIGHT
+        .may_load(storage, key.clone())?
+        .ok_or(ContractError::UnknownIBCForward { channel, sequence })?;
+    IBC_FORWARDS_IN_FLIGHT.remove(storage, key);
+
+    Ok(forward)
+}
+
+// Funds of failed forwards are stored as a pending refund that the recipient of the XRPL transfer can claim
+fn park_ibc_forward(
+    storage: &mut dyn Storage,
+    pending_refund_id: String,
+    forward: IBCForward,
+    error: String,
+) -> ContractResult<Response> {
+    store_pending_refund(
+        storage,
+        pending_refund_id.clone(),
+        Some(forward.tx_hash.clone()),
+        forward.recipient.clone(),
+        forward.coin.clone(),
+        None,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "refund_ibc_forward")
+        .add_event(
+            Event::new("refund_ibc_forward")
+                .add_attribute("pending_refund_id", pending_refund_id)
+                .add_attribute("tx_hash", forward.tx_hash)
+                .add_attribute("channel", forward.channel)
+                .add_attribute("recipient", forward.recipient)
+                .add_attribute("amount", forward.coin.to_string())
+                .add_attribute("error", error),
+        ))
+}
+
+// Protobuf encoding of MsgTransfer. The timeout height is left empty because we only use the timestamp
+fn encode_msg_transfer(sender: &Addr, timeout: Timestamp, forward: &IBCForward) -> Binary {
+    let mut token = vec![];
+    encode_bytes_field(&mut token, 1, forward.coin.denom.as_bytes());
+    encode_bytes_field(&mut token, 2, forward.coin.amount.to_string().as_bytes());
+
+    let memo = format!(r#"{{"ibc_callback":"{sender}"}}"#);
+
+    let mut msg = vec![];
+    encode_bytes_field(&mut msg, 1, IBC_TRANSFER_PORT.as_bytes());
+    encode_bytes_field(&mut msg, 2, forward.channel.as_bytes());
+    encode_bytes_field(&mut msg, 3, &token);
+    encode_bytes_field(&mut msg, 4, sender.as_bytes());
+    encode_bytes_field(&mut msg, 5, forward.receiver.as_bytes());
+    // Field 7 (timeout_timestamp) is a varint in nanoseconds
+    msg.push(7 << 3);
+    encode_varint(&mut msg, timeout.nanos());
+    encode_bytes_field(&mut msg, 8, memo.as_bytes());
+
+    Binary::from(msg)
+}
+
+// Length delimited field (wire type 2)
+fn encode_bytes_field(buf: &mut Vec<u8>, field_number: u8, data: &[u8]) {
+    buf.push((field_number << 3) | 2);
+    encode_varint(buf, data.len() as u64);
+    buf.extend_from_slice(data);
+}
+
+fn encode_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(((value as u8) & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+// The response of an IBC transfer is a protobuf MsgTransferResponse, which only has the sequence (field 1, varint)
+fn parse_transfer_sequence(data: &[u8]) -> Option<u64> {
+    let (tag, varint) = data.split_first()?;
+    if *tag != 0x08 {
+        return None;
+    }
+
+    let mut sequence: u64 = 0;
+    for (i, byte) in varint.iter().take(10).enumerate() {
+        sequence |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some(sequence);
+        }
+    }
+    None
+}
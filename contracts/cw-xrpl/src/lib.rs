@@ -3,8 +3,11 @@ pub mod contract;
 pub mod error;
 pub mod evidence;
 pub mod fees;
+pub mod ibc_forward;
+pub mod memo;
 pub mod msg;
 pub mod operation;
+pub mod refund;
 pub mod relayer;
 pub mod signatures;
 pub mod state;
@@ -12,4 +15,6 @@ pub mod state;
 mod tests;
 pub mod tickets;
 pub mod token;
+pub mod universal_swap;
+pub mod xrpl_tx;
 pub mod migration;
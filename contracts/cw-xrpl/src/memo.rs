@@ -0,0 +1,173 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{from_json, Addr, Binary, Coin, Env, Storage, SubMsg};
+
+use crate::{
+    error::{ContractError, ContractResult},
+    ibc_forward::{
+        create_ibc_forward_submsg, DEFAULT_IBC_FORWARD_TIMEOUT_SECONDS,
+        MAX_IBC_FORWARD_TIMEOUT_SECONDS,
+    },
+    state::IBCForward,
+    universal_swap::{
+        create_contract_call_submsg, create_universal_swap_submsg, UniversalSwapFallback,
+    },
+};
+
+// Versions of the memo schema that the bridge understands
+pub const MEMO_VERSION: u16 = 1;
+// XRPL limits the memos of a transaction to 1KB so we will never receive more than this
+pub const MAX_MEMO_LENGTH: usize = 1024;
+// Max length for the identifiers (channels, addresses...) sent inside the memo
+pub const MAX_MEMO_FIELD_LENGTH: usize = 128;
+
+// Structured memo that can be attached to an XRPL to Cosmos transfer to tell the bridge what to do with the funds.
+// Example: {"version":1,"action":{"swap":{"memo":"...","fallback":"park_as_pending_refund"}}}
+#[cw_serde]
+pub struct BridgeMemo {
+    pub version: u16,
+    pub action: MemoAction,
+}
+
+#[cw_serde]
+pub enum MemoAction {
+    // Universal swap through the osor entry point. The memo is forwarded as is to the entry point
+    Swap {
+        memo: String,
+        fallback: Option<UniversalSwapFallback>,
+    },
+    // Send the funds to another chain through IBC
+    Forward {
+        channel: String,
+        receiver: String,
+        timeout_seconds: Option<u64>,
+    },
+    // Execute a contract sending the funds with the message
+    ContractCall {
+        contract: String,
+        msg: Binary,
+        fallback: Option<UniversalSwapFallback>,
+    },
+}
+
+impl MemoAction {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Swap { .. } => "swap",
+            Self::Forward { .. } => "forward",
+            Self::ContractCall { .. } => "contract_call",
+        }
+    }
+}
+
+// Parses the memo of a transfer. Memos that don't follow the schema are treated as plain transfers (None is returned),
+// but memos that follow the schema and have invalid values are rejected so that relayers don't reach the threshold for them
+pub fn parse_memo(memo: &str) -> ContractResult<Option<BridgeMemo>> {
+    if memo.len() > MAX_MEMO_LENGTH {
+        return Err(ContractError::MemoTooLong {});
+    }
+
+    let bridge_memo = match from_json::<BridgeMemo>(memo.as_bytes()) {
+        Ok(bridge_memo) => bridge_memo,
+        Err(_) => return Ok(None),
+    };
+
+    if bridge_memo.version != MEMO_VERSION {
+        return Err(ContractError::UnsupportedMemoVersion {
+            version: bridge_memo.version,
+        });
+    }
+
+    match &bridge_memo.action {
+        MemoAction::Swap { memo, .. } => {
+            if memo.is_empty() {
+                return Err(ContractError::InvalidMemo {});
+            }
+        }
+        MemoAction::Forward {
+            channel,
+            receiver,
+            timeout_seconds,
+        } => {
+            let timeout_seconds = timeout_seconds.unwrap_or(DEFAULT_IBC_FORWARD_TIMEOUT_SECONDS);
+            if !is_valid_memo_field(channel)
+                || !is_valid_memo_field(receiver)
+                || timeout_seconds == 0
+                || timeout_seconds > MAX_IBC_FORWARD_TIMEOUT_SECONDS
+            {
+                return Err(ContractError::InvalidMemo {});
+            }
+        }
+        MemoAction::ContractCall { contract, msg, .. } => {
+            if !is_valid_memo_field(contract) || msg.is_empty() {
+                return Err(ContractError::InvalidMemo {});
+            }
+        }
+    }
+
+    Ok(Some(bridge_memo))
+}
+
+fn is_valid_memo_field(field: &str) -> bool {
+    !field.is_empty() && field.len() <= MAX_MEMO_FIELD_LENGTH
+}
+
+// Builds the submessage that executes the action of the memo with the bridged funds.
+// None means that there is nothing to execute and the funds must be sent to the recipient
+pub fn create_memo_action_submsg(
+    storage: &mut dyn Storage,
+    osor_entry_point: Option<Addr>,
+    action: Option<MemoAction>,
+    env: &Env,
+    tx_hash: String,
+    recipient: Addr,
+    coin: Coin,
+) -> ContractResult<Option<SubMsg>> {
+    match action {
+        Some(MemoAction::Swap { memo, fallback }) => match osor_entry_point {
+            Some(entry_point) => Ok(Some(create_universal_swap_submsg(
+                storage,
+                entry_point,
+                memo,
+                fallback.unwrap_or_default(),
+                tx_hash,
+                recipient,
+                coin,
+            )?)),
+            // If there is no entry point configured we can't swap so it's a plain transfer
+            None => Ok(None),
+        },
+        // The contract address was validated when the evidence was received
+        Some(MemoAction::ContractCall {
+            contract,
+            msg,
+            fallback,
+        }) => Ok(Some(create_contract_call_submsg(
+            storage,
+            Addr::unchecked(contract),
+            msg,
+            fallback.unwrap_or_default(),
+            tx_hash,
+            recipient,
+            coin,
+        )?)),
+        Some(MemoAction::Forward {
+            channel,
+            receiver,
+            timeout_seconds,
+        }) => Ok(Some(create_ibc_forward_submsg(
+            storage,
+            &env.contract.address,
+            env.block
+                .time
+                .plus_seconds(timeout_seconds.unwrap_or(DEFAULT_IBC_FORWARD_TIMEOUT_SECONDS)),
+            IBCForward {
+                channel,
+                receiver,
+                recipient,
+                coin,
+                tx_hash,
+            },
+        )?)),
+        None => Ok(None),
+    }
+}
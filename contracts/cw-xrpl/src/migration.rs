@@ -9,8 +9,13 @@ use cw2::set_contract_version;
 
 use crate::msg::MigrateMsg;
 
+// Storage key of the single recovery item that universal swaps used before they were keyed by nonce
+const TEMP_UNIVERSAL_SWAP_KEY: &[u8] = b"temp_universal_swap";
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    // Settings that are not part of the migrate message are kept
+    let previous_config = CONFIG.load(deps.storage)?;
     let config = Config {
         relayers: msg.relayers,
         evidence_threshold: msg.evidence_threshold,
@@ -22,9 +27,16 @@ pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, Co
         token_factory_addr: msg.token_factory_addr,
         rate_limit_addr: msg.rate_limit_addr,
         osor_entry_point: msg.osor_entry_point,
+        xrpl_network: msg.xrpl_network.unwrap_or(previous_config.xrpl_network),
+        halt_on_evidence_conflict: previous_config.halt_on_evidence_conflict,
+        fee_distribution: previous_config.fee_distribution,
+        operation_timeout: previous_config.operation_timeout,
+        auto_claim_refunds: previous_config.auto_claim_refunds,
+        enforce_signature_verification: previous_config.enforce_signature_verification,
     };
 
     CONFIG.save(deps.storage, &config)?;
+    deps.storage.remove(TEMP_UNIVERSAL_SWAP_KEY);
     let ver = cw2::get_contract_version(deps.storage)?;
     if ver.contract != CONTRACT_NAME {
         return Err(StdError::generic_err("Can only upgrade from same contract type").into());
@@ -1,6 +1,6 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::{Addr, Coin, Uint128};
-use cw20::Cw20Coin;
+use cw20::{Cw20Coin, Cw20ReceiveMsg};
 use cw_ownable::{cw_ownable_execute, cw_ownable_query};
 use rate_limiter::msg::QuotaMsg;
 
@@ -9,8 +9,9 @@ use crate::state::{Config, CosmosToken, XRPLToken};
 use crate::{
     evidence::Evidence,
     operation::Operation,
-    relayer::Relayer,
-    state::{BridgeState, TokenState},
+    relayer::{Relayer, RelayerStats},
+    state::{BridgeState, CosmosTokenType, FeeDistribution, TokenState, XRPLNetwork},
+    universal_swap::UniversalSwapFallback,
 };
 
 #[cw_serde]
@@ -36,6 +37,8 @@ pub struct InstantiateMsg {
     pub rate_limit_addr: Option<Addr>,
     // osor entry point contract
     pub osor_entry_point: Option<Addr>,
+    // XRPL network of the bridge, used to validate X-addresses. Mainnet if not provided
+    pub xrpl_network: Option<XRPLNetwork>,
 }
 
 #[cw_serde]
@@ -50,6 +53,8 @@ pub struct MigrateMsg {
     pub token_factory_addr: Addr,
     pub rate_limit_addr: Option<Addr>,
     pub osor_entry_point: Option<Addr>,
+    // The current network is kept if not provided
+    pub xrpl_network: Option<XRPLNetwork>,
 }
 
 #[cw_ownable_execute]
@@ -67,11 +72,14 @@ pub enum ExecuteMsg {
     // Registers a Cosmos token so that it can be bridged to XRPL
     // Only the owner can do this
     RegisterCosmosToken {
+        // For CW20 tokens this is the address of the CW20 contract
         denom: String,
         decimals: u32,
         sending_precision: i32,
         max_holding_amount: Uint128,
         bridging_fee: Uint128,
+        // If not sent, the token is registered as a native token
+        token_type: Option<CosmosTokenType>,
     },
     // Registers an XRPL originated token so that it can be bridge to Cosmos
     // Only the owner can do this
@@ -120,7 +128,21 @@ pub enum ExecuteMsg {
         // 2. If the token is XRPL originated, if this is not sent, amount = max_amount = funds sent - bridging_fee
         // 3. If the token is XRPL originated, if this is sent, amount = deliver_amount, max_amount = funds sent - bridging fee
         deliver_amount: Option<Uint128>,
+        // Destination tag of the XRPL payment. The recipient can also be an X-address that includes it
+        destination_tag: Option<u32>,
+        // Address that gets the funds back if the transfer fails. Useful for contracts that can't claim refunds themselves
+        refund_to: Option<String>,
     },
+    // Send multiple transfers from Cosmos to XRPL in a single message. The funds attached must be exactly the sum of the coins of all transfers
+    // Each transfer gets its own ticket and if there aren't enough tickets available for all of them the whole batch fails
+    // Anyone can do this
+    #[serde(rename = "send_to_xrpl_batch")]
+    SendToXRPLBatch {
+        transfers: Vec<XRPLTransfer>,
+    },
+    // Send a CW20 token from Cosmos to XRPL. The CW20 contract calls this when tokens are sent to the bridge with a Cw20HookMsg
+    // Anyone can do this
+    Receive(Cw20ReceiveMsg),
     // Update the configuration of an XRPL originated token
     // Only the owner can do this
     #[serde(rename = "update_xrpl_token")]
@@ -156,15 +178,24 @@ pub enum ExecuteMsg {
     ClaimRefund {
         pending_refund_id: String,
     },
+    // Claims a pending refund of another address, the funds are sent to the refund destination of the transfer
+    // Anyone can do this
+    ClaimRefundFor {
+        address: Addr,
+        id: String,
+    },
     // Any relayer can claim fees at any point in time. They need to provide what they want to claim
     // Only relayers can do this
     ClaimRelayerFees {
         amounts: Vec<Coin>,
     },
     // Halt the bridge. This will prevent certain new operations to be created
-    // Only the owner or a relayer can do this
+    // Only the owner or a relayer can do this, and only the owner if the funds are frozen, which unfreezes them
     HaltBridge {},
-    // Resume a bridge in halted state and with no pending key rotations
+    // Halt the bridge and also stop all refund and relayer fee claims, for emergencies
+    // Only the owner can do this
+    FreezeFunds {},
+    // Resume a bridge in halted or frozen state and with no pending key rotations
     // Only the owner can do this
     ResumeBridge {},
     // Trigger a rotate keys operation, removing and/or adding relayers, and specifying a new threshold
@@ -173,6 +204,27 @@ pub enum ExecuteMsg {
         new_relayers: Vec<Relayer>,
         new_evidence_threshold: u32,
     },
+    // Trigger a rotate keys operation that adds a relayer to the current set. The bridge is not halted
+    // If no threshold is provided the current one is kept
+    // Only the owner can do this
+    AddRelayer {
+        relayer: Relayer,
+        new_evidence_threshold: Option<u32>,
+    },
+    // Trigger a rotate keys operation that removes a relayer from the current set. The bridge is not halted
+    // If no threshold is provided the current one is kept
+    // Only the owner can do this
+    RemoveRelayer {
+        cosmos_address: Addr,
+        new_evidence_threshold: Option<u32>,
+    },
+    // Trigger a rotate keys operation that changes the XRPL account and key of a relayer. The bridge is not halted
+    // Only the owner can do this
+    ReplaceRelayerKey {
+        cosmos_address: Addr,
+        xrpl_address: String,
+        xrpl_pub_key: String,
+    },
     // Update the prohibited addresses list
     // Only the owner can do this
     #[serde(rename = "update_prohibited_xrpl_addresses")]
@@ -189,6 +241,52 @@ pub enum ExecuteMsg {
     UpdateUsedTicketSequenceThreshold {
         used_ticket_sequence_threshold: u32,
     },
+    // Enables or disables halting the bridge when relayers provide different evidences for the same XRPL transaction
+    // Only the owner can do this
+    UpdateHaltOnEvidenceConflict {
+        halt_on_evidence_conflict: bool,
+    },
+    // Enables or disables rejecting relayer signatures that don't match the XRPL transaction serialized by the contract
+    // Only the owner can do this
+    UpdateSignatureVerification {
+        enforce_signature_verification: bool,
+    },
+    // Changes how the bridging fees collected from now on are split between relayers
+    // Only the owner can do this
+    UpdateFeeDistribution {
+        fee_distribution: FeeDistribution,
+    },
+    // Sets the seconds after which pending transfers to XRPL can be expired, or disables expiration if not provided
+    // Only the owner can do this
+    UpdateOperationTimeout {
+        operation_timeout: Option<u64>,
+    },
+    // Expires a pending transfer to XRPL that relayers didn't complete in time, the funds can be claimed back as a pending refund
+    // Transfers that relayers signed with enough weight to reach the threshold before their expiration can't expire, relayers complete them instead
+    // Only the sender of the transfer can do this
+    ExpireOperation {
+        operation_id: u64,
+    },
+    // Cancels a pending transfer to XRPL that no relayer has signed yet, the funds can be claimed back as a pending refund
+    // Only the sender of the transfer can do this
+    CancelMyTransfer {
+        operation_id: u64,
+    },
+    // Enables or disables sending the refunds of rejected transfers to XRPL to all senders right away
+    // Only the owner can do this
+    UpdateAutoClaimRefunds {
+        auto_claim_refunds: bool,
+    },
+    // Opts in or out of getting the refunds of the sender's rejected transfers to XRPL right away
+    // Anyone can do this
+    SetAutoClaimRefunds {
+        enabled: bool,
+    },
+    // Claims all pending refunds of the sender, up to the limit provided
+    // Anyone can do this
+    ClaimAllRefunds {
+        limit: Option<u32>,
+    },
 
     #[cfg(any(test, feature = "test-tube"))]
     BurnTokens {
@@ -214,6 +312,30 @@ pub enum ExecuteMsg {
         xrpl_denom: String,
         quota_id: String,
     },
+    // Removes orphaned recovery entries of universal swaps. Entries are removed when the reply of their swap arrives,
+    // so this is only needed for entries that are left behind
+    // Only the owner can do this
+    SweepPendingUniversalSwaps {
+        nonces: Vec<u64>,
+    },
+    // Stores an IBC forward whose packet sequence we couldn't get as a pending refund for the recipient of the XRPL transfer.
+    // The owner must check first that the packet was not delivered, because we can't get its acknowledgement
+    // Only the owner can do this
+    RecoverUntrackedIBCForward {
+        tx_hash: String,
+    },
+    // Removes up to limit evidences that were first seen more than older_than_seconds ago and never reached the threshold
+    // Anyone can prune evidences older than MIN_STALE_EVIDENCE_AGE, the owner can prune any of them
+    PruneStaleEvidences {
+        older_than_seconds: u64,
+        limit: Option<u32>,
+    },
+    // Indexes up to limit evidences stored before evidences kept their first seen time, so that they can be pruned.
+    // They are considered first seen now. Each call continues where the previous one stopped
+    // Only the owner can do this
+    IndexLegacyEvidences {
+        limit: Option<u32>,
+    },
 }
 
 #[cw_ownable_query]
@@ -272,6 +394,75 @@ pub enum QueryMsg {
     #[returns(ProhibitedXRPLAddressesResponse)]
     #[serde(rename = "prohibited_xrpl_addresses")]
     ProhibitedXRPLAddresses {},
+    #[returns(PendingUniversalSwapsResponse)]
+    PendingUniversalSwaps {
+        start_after_key: Option<u64>,
+        limit: Option<u32>,
+    },
+    // Returns the exact bytes that relayers must sign for a pending operation
+    #[returns(SigningPayloadResponse)]
+    SigningPayload { operation_id: u64 },
+    // Returns the multisigned XRPL transaction of a pending operation once it has enough signatures
+    #[returns(SignedTransactionResponse)]
+    SignedTransaction { operation_id: u64 },
+    // Returns the current relayer set and, if there is a key rotation pending, the new set and what changes
+    #[returns(RelayerSetChangeResponse)]
+    RelayerSetChange {},
+    // Returns all evidences that are being collected for an XRPL transaction hash
+    // If there is more than one, relayers provided conflicting evidences for the same transaction and none of them is executed
+    #[returns(ConflictingEvidencesResponse)]
+    ConflictingEvidences { tx_hash: String },
+    // Activity of a relayer. Addresses without activity return empty stats
+    #[returns(RelayerStatsResponse)]
+    RelayerStats { address: Addr },
+    // Activity of all relayers that ever submitted an evidence or a signature
+    #[returns(AllRelayerStatsResponse)]
+    AllRelayerStats {},
+    // Whether the refunds of the address are sent right away, either because it opted in or because the bridge does it for everyone
+    #[returns(AutoClaimRefundsResponse)]
+    AutoClaimRefunds { address: Addr },
+}
+
+// Single transfer of a batch sent with SendToXRPLBatch. deliver_amount, destination_tag and refund_to work the same way as in SendToXRPL
+#[cw_serde]
+pub struct XRPLTransfer {
+    pub recipient: String,
+    pub coin: Coin,
+    pub deliver_amount: Option<Uint128>,
+    pub destination_tag: Option<u32>,
+    pub refund_to: Option<String>,
+}
+
+// Messages that can be attached to a CW20 Send to the bridge
+#[cw_serde]
+pub enum Cw20HookMsg {
+    #[serde(rename = "send_to_xrpl")]
+    SendToXRPL {
+        recipient: String,
+        destination_tag: Option<u32>,
+        // Works the same way as in SendToXRPL
+        refund_to: Option<String>,
+    },
+}
+
+// Messages sent by the chain when the packet of an IBC transfer sent by the contract is acknowledged or times out
+#[cw_serde]
+pub enum SudoMsg {
+    #[serde(rename = "ibc_lifecycle_complete")]
+    IBCLifecycleComplete(IBCLifecycleComplete),
+}
+
+#[cw_serde]
+pub enum IBCLifecycleComplete {
+    #[serde(rename = "ibc_ack")]
+    IBCAck {
+        channel: String,
+        sequence: u64,
+        ack: String,
+        success: bool,
+    },
+    #[serde(rename = "ibc_timeout")]
+    IBCTimeout { channel: String, sequence: u64 },
 }
 
 #[cw_serde]
@@ -318,6 +509,8 @@ pub struct PendingRefund {
     pub id: String,
     pub xrpl_tx_hash: Option<String>,
     pub coin: Coin,
+    // Address that gets the funds when the refund is claimed
+    pub destination: Addr,
 }
 
 #[cw_serde]
@@ -337,6 +530,29 @@ pub struct TransactionEvidencesResponse {
     pub transaction_evidences: Vec<TransactionEvidence>,
 }
 
+#[cw_serde]
+pub struct ConflictingEvidencesResponse {
+    pub tx_hash: String,
+    pub evidences: Vec<TransactionEvidence>,
+}
+
+#[cw_serde]
+pub struct RelayerStatsResponse {
+    pub address: Addr,
+    pub stats: RelayerStats,
+}
+
+#[cw_serde]
+pub struct AutoClaimRefundsResponse {
+    pub address: Addr,
+    pub enabled: bool,
+}
+
+#[cw_serde]
+pub struct AllRelayerStatsResponse {
+    pub relayer_stats: Vec<RelayerStatsResponse>,
+}
+
 #[cw_serde]
 pub struct ProcessedTxsResponse {
     pub last_key: Option<String>,
@@ -348,3 +564,64 @@ pub struct ProcessedTxsResponse {
 pub struct ProhibitedXRPLAddressesResponse {
     pub prohibited_xrpl_addresses: Vec<String>,
 }
+
+#[cw_serde]
+pub struct PendingUniversalSwapsResponse {
+    pub last_key: Option<u64>,
+    pub pending_universal_swaps: Vec<PendingUniversalSwap>,
+}
+
+#[cw_serde]
+pub struct SigningPayloadResponse {
+    pub operation_id: u64,
+    pub operation_version: u64,
+    // XRPL transaction (signing fields only) in hex
+    pub transaction: String,
+    // Data that each relayer signs in hex: multisigning prefix + transaction + account ID of the relayer
+    pub signing_payloads: Vec<RelayerSigningPayload>,
+}
+
+#[cw_serde]
+pub struct RelayerSigningPayload {
+    pub relayer_cosmos_address: Addr,
+    pub xrpl_address: String,
+    pub payload: String,
+}
+
+#[cw_serde]
+pub struct SignedTransactionResponse {
+    pub operation_id: u64,
+    pub operation_version: u64,
+    // Submittable XRPL transaction in hex, None until the signatures reach the evidence threshold
+    pub tx_blob: Option<String>,
+}
+
+#[cw_serde]
+pub struct RelayerSetChangeResponse {
+    pub relayers: Vec<Relayer>,
+    pub evidence_threshold: u32,
+    pub pending: Option<PendingRelayerSet>,
+}
+
+#[cw_serde]
+pub struct PendingRelayerSet {
+    pub operation_id: u64,
+    pub halts_bridge: bool,
+    pub new_relayers: Vec<Relayer>,
+    pub new_evidence_threshold: u32,
+    // Relayers that are in the new set but not in the current one
+    pub added: Vec<Relayer>,
+    // Relayers that are in the current set but not in the new one
+    pub removed: Vec<Relayer>,
+    // Relayers that are in both sets but with a different XRPL account, key or weight (as they will be in the new set)
+    pub updated: Vec<Relayer>,
+}
+
+#[cw_serde]
+pub struct PendingUniversalSwap {
+    pub nonce: u64,
+    pub recipient: Addr,
+    pub coin: Coin,
+    pub tx_hash: String,
+    pub fallback: UniversalSwapFallback,
+}
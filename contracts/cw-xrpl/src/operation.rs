@@ -1,11 +1,14 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{coin, wasm_execute, Addr, Coin, CosmosMsg, Response, Storage, Uint128};
+use cosmwasm_std::{
+    coin, wasm_execute, Addr, Coin, CosmosMsg, Response, Storage, Timestamp, Uint128,
+};
 
 use crate::{
     contract::{convert_amount_decimals, CHANNEL, XRPL_TOKENS_DECIMALS},
     error::{ContractError, ContractResult},
     evidence::{OperationResult, TransactionResult},
-    relayer::{handle_rotate_keys_confirmation, Relayer},
+    refund::refund,
+    relayer::{handle_rotate_keys_confirmation, weight_of, Relayer},
     signatures::Signature,
     state::{
         BridgeState, Config, PendingRefund, TokenState, CONFIG, COSMOS_TOKENS, PENDING_OPERATIONS,
@@ -29,6 +32,9 @@ pub struct Operation {
     pub operation_type: OperationType,
     // xrpl_base_fee must be part of operation too to avoid race conditions
     pub xrpl_base_fee: u64,
+    // Block time when the operation was created. Operations created before this field existed don't have it and never expire
+    #[serde(default)]
+    pub created_at: Option<Timestamp>,
 }
 
 #[cw_serde]
@@ -53,6 +59,11 @@ pub enum OperationType {
         max_amount: Option<Uint128>,
         sender: Addr,
         recipient: String,
+        // Required by some XRPL recipients (e.g. exchanges) to identify the beneficiary of the payment
+        destination_tag: Option<u32>,
+        // Address that gets the funds back if the transfer fails, instead of the sender
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        refund_to: Option<Addr>,
     },
 }
 
@@ -103,6 +114,7 @@ pub fn create_pending_operation(
         signatures: vec![],
         operation_type,
         xrpl_base_fee: config.xrpl_base_fee,
+        created_at: Some(Timestamp::from_seconds(timestamp)),
     };
 
     if PENDING_OPERATIONS.has(storage, operation_id) {
@@ -113,6 +125,38 @@ pub fn create_pending_operation(
     Ok(())
 }
 
+// Time (in seconds) from which relayers must not sign or submit the operation anymore
+// Only transfers to XRPL expire, other operations are needed by the bridge and can only be cancelled by the owner
+pub fn operation_expiration(operation: &Operation, config: &Config) -> Option<u64> {
+    match (
+        &operation.operation_type,
+        operation.created_at,
+        config.operation_timeout,
+    ) {
+        (OperationType::CosmosToXRPLTransfer { .. }, Some(created_at), Some(timeout)) => {
+            Some(created_at.seconds().saturating_add(timeout))
+        }
+        _ => None,
+    }
+}
+
+// Whether the relayers that signed the operation reach the threshold, so that it can be submitted to XRPL
+pub fn is_signed_by_quorum(operation: &Operation, config: &Config) -> bool {
+    let signers: Vec<Addr> = operation
+        .signatures
+        .iter()
+        .map(|signature| signature.relayer_cosmos_address.clone())
+        .collect();
+    weight_of(&config.relayers, &signers) >= config.evidence_threshold
+}
+
+// An operation that reached the threshold before its expiration can be submitted to XRPL at any moment, so it doesn't
+// expire and is completed by relayers. Otherwise it's expired from its expiration on and its sender can get a refund
+pub fn is_operation_expired(operation: &Operation, config: &Config, block_time: u64) -> bool {
+    operation_expiration(operation, config).is_some_and(|expiration| block_time >= expiration)
+        && !is_signed_by_quorum(operation, config)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn handle_operation(
     storage: &mut dyn Storage,
@@ -146,9 +190,11 @@ pub fn handle_operation(
         } => {
             handle_rotate_keys_confirmation(
                 storage,
+                operation_id,
                 new_relayers.to_owned(),
                 new_evidence_threshold.to_owned(),
                 transaction_result,
+                response,
             )?;
         }
         OperationType::CosmosToXRPLTransfer { .. } => {
@@ -220,6 +266,7 @@ pub fn handle_cosmos_to_xrpl_transfer_confirmation(
             amount,
             max_amount,
             sender,
+            refund_to,
             ..
         } => {
             // We check that the token that was sent was an XRPL originated token:
@@ -246,13 +293,15 @@ pub fn handle_cosmos_to_xrpl_transfer_confirmation(
 
                         *response = response.to_owned().add_message(burn_msg);
                     } else {
-                        // If transaction was rejected, we must store the amount so that sender can claim it back
-                        store_pending_refund(
+                        // If transaction was rejected, we must refund the amount to the sender
+                        refund(
                             storage,
                             pending_operation.id,
                             tx_hash,
                             sender,
                             coin(amount_sent.u128(), xrpl_token.cosmos_denom),
+                            refund_to,
+                            response,
                         )?;
                         let config = CONFIG.load(storage)?;
                         if let Some(rate_limit_addr) = config.rate_limit_addr {
@@ -289,13 +338,15 @@ pub fn handle_cosmos_to_xrpl_transfer_confirmation(
                                     token.decimals,
                                     max_amount.unwrap(),
                                 )?;
-                                // If transaction was rejected, we must store the amount so that sender can claim it back.
-                                store_pending_refund(
+                                // If transaction was rejected, we must refund the amount to the sender.
+                                refund(
                                     storage,
                                     pending_operation.id,
                                     tx_hash,
                                     sender,
                                     coin(amount_to_send_back.u128(), token.denom),
+                                    refund_to,
+                                    response,
                                 )?;
                                 let config = CONFIG.load(storage)?;
                                 if let Some(rate_limit_addr) = config.rate_limit_addr {
@@ -337,6 +388,7 @@ pub fn store_pending_refund(
     xrpl_tx_hash: Option<String>,
     receiver: Addr,
     coin: Coin,
+    refund_to: Option<Addr>,
 ) -> Result<(), ContractError> {
     // We store the pending refund for this user and this pending_operation_id
     let pending_refund = PendingRefund {
@@ -344,6 +396,7 @@ pub fn store_pending_refund(
         xrpl_tx_hash,
         id: pending_operation_id.clone(),
         coin,
+        refund_to,
     };
 
     PENDING_REFUNDS.save(storage, (receiver, pending_operation_id), &pending_refund)?;
@@ -355,7 +408,7 @@ pub fn remove_pending_refund(
     storage: &mut dyn Storage,
     sender: &Addr,
     pending_refund_id: String,
-) -> Result<Coin, ContractError> {
+) -> Result<PendingRefund, ContractError> {
     // If pending refund is not found we return the error
     let pending_refund = PENDING_REFUNDS
         .load(storage, (sender.clone(), pending_refund_id.clone()))
@@ -363,7 +416,7 @@ pub fn remove_pending_refund(
 
     PENDING_REFUNDS.remove(storage, (sender.clone(), pending_refund_id))?;
 
-    Ok(pending_refund.coin)
+    Ok(pending_refund)
 }
 
 pub fn check_valid_operation_if_halt(
@@ -371,7 +424,7 @@ pub fn check_valid_operation_if_halt(
     config: &Config,
     operation_type: &OperationType,
 ) -> Result<(), ContractError> {
-    if config.bridge_state.eq(&BridgeState::Halted) {
+    if config.bridge_state.ne(&BridgeState::Active) {
         match &operation_type {
             // Only RotateKeys operations (if there is a pending rotate keys ongoing) or ticket allocations are allowed during bridge halt
             OperationType::RotateKeys { .. } => {
@@ -0,0 +1,108 @@
+use cosmwasm_std::{Addr, Coin, Event, Response, StdResult, Storage, SubMsg, SubMsgResult};
+
+use crate::{
+    error::ContractResult,
+    operation::store_pending_refund,
+    state::{BridgeState, PendingRefund, AUTO_CLAIM_REFUNDS, CONFIG, PENDING_AUTO_REFUNDS},
+    token::build_send_msgs,
+    universal_swap::next_reply_nonce,
+};
+
+// Refunds are sent right away if the bridge has auto claim enabled or if the receiver opted in for it
+pub fn is_auto_claim_enabled(storage: &dyn Storage, receiver: &Addr) -> StdResult<bool> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.auto_claim_refunds || AUTO_CLAIM_REFUNDS.has(storage, receiver.clone()))
+}
+
+// Sends the refund to its destination if auto claim is enabled for the receiver, otherwise it's stored so that it can be claimed.
+// Refunds are also stored while the funds of the bridge are frozen
+// We reply always: on success to clean up and on error to store the refund because the funds are still in the contract
+pub fn refund(
+    storage: &mut dyn Storage,
+    pending_refund_id: String,
+    xrpl_tx_hash: Option<String>,
+    receiver: Addr,
+    coin: Coin,
+    refund_to: Option<Addr>,
+    response: &mut Response,
+) -> ContractResult<()> {
+    let funds_frozen = CONFIG.load(storage)?.bridge_state == BridgeState::FundsFrozen;
+    if funds_frozen || !is_auto_claim_enabled(storage, &receiver)? {
+        return store_pending_refund(
+            storage,
+            pending_refund_id,
+            xrpl_tx_hash,
+            receiver,
+            coin,
+            refund_to,
+        );
+    }
+
+    let pending_refund = PendingRefund {
+        address: receiver,
+        id: pending_refund_id,
+        xrpl_tx_hash,
+        coin,
+        refund_to,
+    };
+    let nonce = next_reply_nonce(storage)?;
+    let send_msgs = build_send_msgs(
+        storage,
+        pending_refund.destination(),
+        vec![pending_refund.coin.clone()],
+    )?;
+
+    PENDING_AUTO_REFUNDS.save(storage, nonce, &pending_refund)?;
+
+    // A single coin is always sent with a single message
+    *response = response.to_owned().add_submessages(
+        send_msgs
+            .into_iter()
+            .map(|msg| SubMsg::reply_always(msg, nonce)),
+    );
+
+    Ok(())
+}
+
+pub fn is_auto_refund_reply(storage: &dyn Storage, nonce: u64) -> bool {
+    PENDING_AUTO_REFUNDS.has(storage, nonce)
+}
+
+pub fn handle_auto_refund_reply(
+    storage: &mut dyn Storage,
+    nonce: u64,
+    result: SubMsgResult,
+) -> ContractResult<Response> {
+    let pending_refund = PENDING_AUTO_REFUNDS.load(storage, nonce)?;
+    PENDING_AUTO_REFUNDS.remove(storage, nonce);
+
+    match result {
+        SubMsgResult::Ok(_) => Ok(Response::new()
+            .add_attribute("action", "auto_refund")
+            .add_attribute("receiver", pending_refund.destination().to_string())
+            .add_attribute("pending_refund_id", pending_refund.id)
+            .add_attribute("amount", pending_refund.coin.to_string())),
+        // The funds could not be sent so we store them as a pending refund that the receiver can claim
+        SubMsgResult::Err(err) => {
+            store_pending_refund(
+                storage,
+                pending_refund.id.clone(),
+                pending_refund.xrpl_tx_hash.clone(),
+                pending_refund.address.clone(),
+                pending_refund.coin.clone(),
+                pending_refund.refund_to.clone(),
+            )?;
+
+            Ok(Response::new()
+                .add_attribute("action", "auto_refund_failed")
+                .add_event(
+                    Event::new("auto_refund_failed")
+                        .add_attribute("receiver", pending_refund.destination().to_string())
+                        .add_attribute("pending_refund_id", pending_refund.id)
+                        .add_attribute("amount", pending_refund.coin.to_string())
+                        .add_attribute("error", err),
+                ))
+        }
+    }
+}
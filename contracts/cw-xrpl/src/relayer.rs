@@ -1,47 +1,117 @@
 use std::collections::HashSet;
 
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Deps, Storage};
+use cosmwasm_std::{Addr, Deps, Event, Order, Response, Storage, Timestamp};
 
 use crate::{
     address::validate_xrpl_address,
     contract::MAX_RELAYERS,
     error::ContractError,
-    evidence::TransactionResult,
-    state::{CONFIG, PENDING_ROTATE_KEYS, TX_EVIDENCES},
+    evidence::{clear_evidences, TransactionResult},
+    operation::Operation,
+    state::{
+        XRPLNetwork, CONFIG, PENDING_OPERATIONS, PENDING_RELAYER_SET_CHANGE, PENDING_ROTATE_KEYS,
+        RELAYER_STATS,
+    },
 };
 
+// Weight of relayers that don't have one, which makes the threshold the number of relayers needed
+pub const DEFAULT_RELAYER_WEIGHT: u16 = 1;
+
 #[cw_serde]
 pub struct Relayer {
     pub cosmos_address: Addr,
     pub xrpl_address: String,
     pub xrpl_pub_key: String,
+    // Weight of the relayer for evidences, signatures and the XRPL signer list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u16>,
+}
+
+impl Relayer {
+    pub fn signer_weight(&self) -> u16 {
+        self.weight.unwrap_or(DEFAULT_RELAYER_WEIGHT)
+    }
 }
 
+// Activity of a relayer, used to find relayers that are lagging behind or offline
+#[cw_serde]
+#[derive(Default)]
+pub struct RelayerStats {
+    pub evidences_submitted: u64,
+    pub signatures_provided: u64,
+    // Times that the relayer provided one of the evidences that made the threshold be reached
+    pub threshold_completions: u64,
+    pub last_active: Option<Timestamp>,
+}
+
+pub enum RelayerActivity {
+    Evidence,
+    Signature,
+    ThresholdCompletion,
+}
+
+pub fn record_relayer_activity(
+    storage: &mut dyn Storage,
+    relayer: &Addr,
+    activity: RelayerActivity,
+    block_time: Timestamp,
+) -> Result<(), ContractError> {
+    let mut stats = RELAYER_STATS
+        .may_load(storage, relayer.clone())?
+        .unwrap_or_default();
+
+    match activity {
+        RelayerActivity::Evidence => {
+            stats.evidences_submitted += 1;
+            stats.last_active = Some(block_time);
+        }
+        RelayerActivity::Signature => {
+            stats.signatures_provided += 1;
+            stats.last_active = Some(block_time);
+        }
+        // Completing the threshold doesn't mean the relayer is active now, it might have provided its evidence earlier
+        RelayerActivity::ThresholdCompletion => stats.threshold_completions += 1,
+    }
+
+    RELAYER_STATS.save(storage, relayer.clone(), &stats)?;
+    Ok(())
+}
+
+// Validates the relayer set and returns it with the XRPL addresses normalized to classic addresses
 pub fn validate_relayers(
     deps: Deps,
     relayers: &Vec<Relayer>,
     evidence_threshold: u32,
-) -> Result<(), ContractError> {
+    network: &XRPLNetwork,
+) -> Result<Vec<Relayer>, ContractError> {
     let mut set_xrpl_addresses = HashSet::new();
     let mut set_xrpl_pubkeys = HashSet::new();
     let mut set_cosmos_addresses = HashSet::new();
 
-    // Threshold can't be 0 or more than number of relayers
-    if evidence_threshold == 0 || evidence_threshold as usize > relayers.len() {
-        return Err(ContractError::InvalidThreshold {});
-    }
-
     if relayers.len() > MAX_RELAYERS {
         return Err(ContractError::TooManyRelayers {});
     }
 
+    // Weights of the XRPL signer list must be greater than 0
+    if relayers.iter().any(|relayer| relayer.signer_weight() == 0) {
+        return Err(ContractError::InvalidRelayerWeight {});
+    }
+
+    // Threshold is the SignerQuorum of the XRPL signer list so it can't be 0 or more than the sum of all weights
+    if evidence_threshold == 0 || evidence_threshold > total_weight(relayers) {
+        return Err(ContractError::InvalidThreshold {});
+    }
+
+    let mut validated_relayers = vec![];
     for relayer in relayers {
         deps.api.addr_validate(relayer.cosmos_address.as_ref())?;
-        validate_xrpl_address(deps.storage, relayer.xrpl_address.clone())?;
+        let xrpl_address =
+            validate_xrpl_address(deps.storage, relayer.xrpl_address.clone(), network)?;
 
         // If the set returns false during insertion it means that the key already exists and therefore is duplicated
-        if !set_xrpl_addresses.insert(relayer.xrpl_address.clone()) {
+        // We use the classic address so that the same account can't be registered again as an X-address
+        if !set_xrpl_addresses.insert(xrpl_address.clone()) {
             return Err(ContractError::DuplicatedRelayer {});
         };
         if !set_xrpl_pubkeys.insert(relayer.xrpl_pub_key.clone()) {
@@ -50,9 +120,51 @@ pub fn validate_relayers(
         if !set_cosmos_addresses.insert(relayer.cosmos_address.clone()) {
             return Err(ContractError::DuplicatedRelayer {});
         };
+
+        validated_relayers.push(Relayer {
+            xrpl_address,
+            ..relayer.clone()
+        });
     }
 
-    Ok(())
+    Ok(validated_relayers)
+}
+
+pub fn total_weight(relayers: &[Relayer]) -> u32 {
+    relayers
+        .iter()
+        .map(|relayer| u32::from(relayer.signer_weight()))
+        .sum()
+}
+
+// Sum of the weights of the relayers with these cosmos addresses
+pub fn weight_of(relayers: &[Relayer], cosmos_addresses: &[Addr]) -> u32 {
+    relayers
+        .iter()
+        .filter(|relayer| cosmos_addresses.contains(&relayer.cosmos_address))
+        .map(|relayer| u32::from(relayer.signer_weight()))
+        .sum()
+}
+
+// Maximum number of signatures that can be needed to reach the threshold, which happens when the relayers with the
+// lowest weights sign. XRPL transaction fees are paid per signature so we use this amount to calculate them.
+pub fn max_signers_for_threshold(relayers: &[Relayer], evidence_threshold: u32) -> u32 {
+    let mut weights: Vec<u32> = relayers
+        .iter()
+        .map(|relayer| u32::from(relayer.signer_weight()))
+        .collect();
+    weights.sort_unstable();
+
+    let mut accumulated_weight = 0;
+    let mut signers = 0;
+    for weight in weights {
+        if accumulated_weight >= evidence_threshold {
+            break;
+        }
+        accumulated_weight += weight;
+        signers += 1;
+    }
+    signers
 }
 
 pub fn is_relayer(storage: &dyn Storage, sender: &Addr) -> Result<bool, ContractError> {
@@ -61,24 +173,79 @@ pub fn is_relayer(storage: &dyn Storage, sender: &Addr) -> Result<bool, Contract
     Ok(config.relayers.iter().any(|r| r.cosmos_address == sender))
 }
 
+// Returns true if the relayer is in both sets with the same XRPL account and public key
+fn key_unchanged(
+    previous_relayers: &[Relayer],
+    new_relayers: &[Relayer],
+    cosmos_address: &Addr,
+) -> bool {
+    let find = |relayers: &[Relayer]| {
+        relayers
+            .iter()
+            .find(|relayer| &relayer.cosmos_address == cosmos_address)
+            .map(|relayer| (relayer.xrpl_address.clone(), relayer.xrpl_pub_key.clone()))
+    };
+    let previous_key = find(previous_relayers);
+    previous_key.is_some() && previous_key == find(new_relayers)
+}
+
 pub fn handle_rotate_keys_confirmation(
     storage: &mut dyn Storage,
+    operation_id: u64,
     relayers: Vec<Relayer>,
     new_evidence_threshold: u32,
     transaction_result: &TransactionResult,
+    response: &mut Response,
 ) -> Result<(), ContractError> {
     // If transaction was accepted, update the relayers and evidence threshold and clear all current evidences
     // Bridge will stay halted until owner resumes it.
     // If it failed, the bridge will remain halted and relayers are not updated, waiting for another recovery by owner
     if transaction_result.eq(&TransactionResult::Accepted) {
         let mut config = CONFIG.load(storage)?;
+        let previous_relayers = config.relayers;
+        let previous_signers =
+            max_signers_for_threshold(&previous_relayers, config.evidence_threshold);
         config.relayers = relayers;
         config.evidence_threshold = new_evidence_threshold;
         CONFIG.save(storage, &config)?;
-        TX_EVIDENCES.clear(storage);
+        clear_evidences(storage);
+
+        // Signatures of the other pending operations were given with the previous relayer set.
+        // If the number of signers used for the fee changed, the transactions changed and no signature is valid anymore.
+        // Otherwise, only the signatures of relayers that were removed or whose XRPL key was replaced are invalid.
+        let all_signatures_invalid = previous_signers
+            != max_signers_for_threshold(&config.relayers, config.evidence_threshold);
+        let operations: Vec<(u64, Operation)> = PENDING_OPERATIONS
+            .range(storage, None, None, Order::Ascending)
+            .filter_map(Result::ok)
+            .filter(|(id, _)| *id != operation_id)
+            .collect();
+
+        // Same as when the XRPL base fee is updated, we increase the version so that relayers know they must sign again
+        let mut operation_ids = vec![];
+        for (id, mut operation) in operations {
+            operation.version += 1;
+            operation.signatures.retain(|signature| {
+                !all_signatures_invalid
+                    && key_unchanged(
+                        &previous_relayers,
+                        &config.relayers,
+                        &signature.relayer_cosmos_address,
+                    )
+            });
+            PENDING_OPERATIONS.save(storage, id, &operation)?;
+            operation_ids.push(id.to_string());
+        }
+
+        *response = response.to_owned().add_event(
+            Event::new("relayer_set_changed")
+                .add_attribute("operation_id", operation_id.to_string())
+                .add_attribute("updated_operation_ids", operation_ids.join(",")),
+        );
     }
 
     PENDING_ROTATE_KEYS.save(storage, &false)?;
+    PENDING_RELAYER_SET_CHANGE.remove(storage);
 
     Ok(())
 }
@@ -1,13 +1,18 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, DepsMut};
+use cosmwasm_std::{Addr, Api, DepsMut, HexBinary, Storage, Timestamp};
 
 use crate::{
     error::ContractError,
-    operation::check_valid_operation_if_halt,
-    state::{CONFIG, PENDING_OPERATIONS},
+    operation::{check_valid_operation_if_halt, is_operation_expired, Operation, OperationType},
+    relayer::{max_signers_for_threshold, record_relayer_activity, Relayer, RelayerActivity},
+    state::{Config, CONFIG, PENDING_OPERATIONS, PENDING_RELAYER_SET_CHANGE},
+    xrpl_tx::{multisigning_data, sha512_half},
 };
 
 const MAX_SIGNATURE_LENGTH: usize = 200;
+// Ed25519 public keys in XRPL are prefixed with 0xED to distinguish them from secp256k1 ones
+const ED25519_PUB_KEY_PREFIX: u8 = 0xED;
+const ED25519_PUB_KEY_LENGTH: usize = 33;
 
 #[cw_serde]
 pub struct Signature {
@@ -17,11 +22,12 @@ pub struct Signature {
 
 pub fn add_signature(
     deps: DepsMut,
+    block_time: Timestamp,
     operation_id: u64,
     operation_version: u64,
     sender: Addr,
     signature: String,
-) -> Result<(), ContractError> {
+) -> Result<bool, ContractError> {
     validate_signature(&signature)?;
 
     // We get the current signatures for this specific operation
@@ -38,28 +44,48 @@ pub fn add_signature(
     // If bridge is halted we prohibit all signatures except for allowed operations
     check_valid_operation_if_halt(deps.storage, &config, &pending_operation.operation_type)?;
 
-    let mut signatures = pending_operation.signatures;
+    // Expired operations will be refunded to their sender, so they must not be executed in XRPL
+    if is_operation_expired(&pending_operation, &config, block_time.seconds()) {
+        return Err(ContractError::OperationExpired {});
+    }
 
     // If this relayer already provided a signature he can't overwrite it
-    if signatures.clone().into_iter().any(
+    if pending_operation.signatures.iter().any(
         |Signature {
              relayer_cosmos_address,
              signature: _,
-         }| relayer_cosmos_address == sender,
+         }| *relayer_cosmos_address == sender,
     ) {
         return Err(ContractError::SignatureAlreadyProvided {});
     }
 
+    check_relayer_key_not_changing(deps.storage, &config, operation_id, &sender)?;
+
+    // Until verification is enforced, signatures that don't match are stored so that relayers can keep working
+    // if the serialization of the contract doesn't match theirs
+    let signature_verified =
+        match verify_signature(deps.api, &config, &pending_operation, &sender, &signature) {
+            Ok(()) => true,
+            Err(_) if !config.enforce_signature_verification => false,
+            Err(err) => return Err(err),
+        };
+
+    record_relayer_activity(
+        deps.storage,
+        &sender,
+        RelayerActivity::Signature,
+        block_time,
+    )?;
+
     // Add signature and store it
-    signatures.push(Signature {
+    pending_operation.signatures.push(Signature {
         relayer_cosmos_address: sender,
         signature,
     });
 
-    pending_operation.signatures = signatures;
     PENDING_OPERATIONS.save(deps.storage, operation_id, &pending_operation)?;
 
-    Ok(())
+    Ok(signature_verified)
 }
 
 fn validate_signature(signature: &str) -> Result<(), ContractError> {
@@ -71,3 +97,126 @@ fn validate_signature(signature: &str) -> Result<(), ContractError> {
     }
     Ok(())
 }
+
+// While a relayer set change is pending, relayers whose XRPL key is removed or replaced can only sign the change itself.
+// Their signatures on other operations would stop being valid once the new signer list is set in XRPL
+fn check_relayer_key_not_changing(
+    storage: &dyn Storage,
+    config: &Config,
+    operation_id: u64,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    let Some(change) = PENDING_RELAYER_SET_CHANGE.may_load(storage)? else {
+        return Ok(());
+    };
+    if change.operation_id == operation_id {
+        return Ok(());
+    }
+
+    let change_operation = PENDING_OPERATIONS.load(storage, change.operation_id)?;
+    if let OperationType::RotateKeys { new_relayers, .. } = change_operation.operation_type {
+        let find = |relayers: &[Relayer]| {
+            relayers
+                .iter()
+                .find(|relayer| &relayer.cosmos_address == sender)
+                .map(|relayer| (relayer.xrpl_address.clone(), relayer.xrpl_pub_key.clone()))
+        };
+        if find(&config.relayers) != find(&new_relayers) {
+            return Err(ContractError::RelayerKeyChangePending {});
+        }
+    }
+
+    Ok(())
+}
+
+// Verifies that the signature was produced by the XRPL key of the relayer over the multisigning data of the operation
+// This way relayers can't store signatures that will make the XRPL transaction fail
+fn verify_signature(
+    api: &dyn Api,
+    config: &Config,
+    operation: &Operation,
+    sender: &Addr,
+    signature: &str,
+) -> Result<(), ContractError> {
+    let relayer = config
+        .relayers
+        .iter()
+        .find(|relayer| &relayer.cosmos_address == sender)
+        .ok_or(ContractError::InvalidSignature {})?;
+
+    let data = multisigning_data(
+        operation,
+        &config.bridge_xrpl_address,
+        max_signers_for_threshold(&config.relayers, config.evidence_threshold),
+        &relayer.xrpl_address,
+    )?;
+
+    let signature =
+        HexBinary::from_hex(signature).map_err(|_| ContractError::InvalidSignature {})?;
+
+    if verify_with_relayer_key(api, relayer, &data, &signature)? {
+        Ok(())
+    } else {
+        Err(ContractError::InvalidSignature {})
+    }
+}
+
+fn verify_with_relayer_key(
+    api: &dyn Api,
+    relayer: &Relayer,
+    data: &[u8],
+    signature: &[u8],
+) -> Result<bool, ContractError> {
+    let pub_key = HexBinary::from_hex(&relayer.xrpl_pub_key)
+        .map_err(|_| ContractError::InvalidSignature {})?;
+
+    // Ed25519 signatures are over the data itself
+    if pub_key.len() == ED25519_PUB_KEY_LENGTH && pub_key[0] == ED25519_PUB_KEY_PREFIX {
+        return api
+            .ed25519_verify(data, signature, &pub_key[1..])
+            .map_err(|_| ContractError::InvalidSignature {});
+    }
+
+    // Secp256k1 signatures are DER encoded and over the SHA-512Half of the data
+    let signature = der_to_compact(signature).ok_or(ContractError::InvalidSignature {})?;
+    api.secp256k1_verify(&sha512_half(data), &signature, &pub_key)
+        .map_err(|_| ContractError::InvalidSignature {})
+}
+
+// Converts a DER encoded ECDSA signature (0x30 len 0x02 len R 0x02 len S) to the 64 bytes R || S format
+fn der_to_compact(der: &[u8]) -> Option<Vec<u8>> {
+    if der.len() < 2 || der[0] != 0x30 || der[1] as usize != der.len() - 2 {
+        return None;
+    }
+
+    let (r, rest) = der_integer(&der[2..])?;
+    let (s, rest) = der_integer(rest)?;
+    if !rest.is_empty() {
+        return None;
+    }
+
+    let mut compact = vec![0u8; 64];
+    compact[32 - r.len()..32].copy_from_slice(r);
+    compact[64 - s.len()..].copy_from_slice(s);
+    Some(compact)
+}
+
+// Returns the integer (without leading zeroes) and the remaining bytes
+fn der_integer(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    if data.len() < 2 || data[0] != 0x02 {
+        return None;
+    }
+    let length = data[1] as usize;
+    if data.len() < 2 + length {
+        return None;
+    }
+
+    let mut integer = &data[2..2 + length];
+    while integer.len() > 1 && integer[0] == 0 {
+        integer = &integer[1..];
+    }
+    if integer.len() > 32 {
+        return None;
+    }
+    Some((integer, &data[2 + length..]))
+}
@@ -4,7 +4,12 @@ use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{Addr, Coin, Empty, Uint128};
 use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex, UniqueIndex};
 
-use crate::{evidence::Evidences, operation::Operation, relayer::Relayer};
+use crate::{
+    evidence::Evidences,
+    operation::Operation,
+    relayer::{Relayer, RelayerStats},
+    universal_swap::UniversalSwapFallback,
+};
 
 /// Top level storage key. Values must not conflict.
 /// Each key is only one byte long to ensure we use the smallest possible storage keys.
@@ -25,6 +30,18 @@ pub enum TopKey {
     FeeRemainders = b'd',
     PendingRotateKeys = b'e',
     ProhibitedXRPLAddresses = b'f',
+    PendingUniversalSwaps = b'g',
+    UniversalSwapNonce = b'h',
+    PendingIBCForwards = b'i',
+    IBCForwardsInFlight = b'j',
+    PendingRelayerSetChange = b'k',
+    TxEvidencesFirstSeen = b'l',
+    TxHashEvidences = b'm',
+    RelayerStats = b'n',
+    AutoClaimRefunds = b'o',
+    PendingAutoRefunds = b'p',
+    UntrackedIBCForwards = b'q',
+    LegacyEvidencesCursor = b'r',
 }
 
 impl TopKey {
@@ -49,6 +66,59 @@ pub struct Config {
     pub token_factory_addr: Addr,
     pub rate_limit_addr: Option<Addr>,
     pub osor_entry_point: Option<Addr>,
+    // Configs saved before this field existed are mainnet bridges
+    #[serde(default)]
+    pub xrpl_network: XRPLNetwork,
+    // If enabled, the bridge is halted when relayers provide different evidences for the same XRPL transaction
+    #[serde(default)]
+    pub halt_on_evidence_conflict: bool,
+    // Configs saved before this field existed split fees equally
+    #[serde(default)]
+    pub fee_distribution: FeeDistribution,
+    // Seconds after which relayers stop working on a pending transfer to XRPL and its sender can expire it to get a refund
+    // Transfers don't expire if it's not set
+    #[serde(default)]
+    pub operation_timeout: Option<u64>,
+    // If enabled, refunds of rejected transfers to XRPL are sent to their senders right away instead of waiting for a claim
+    #[serde(default)]
+    pub auto_claim_refunds: bool,
+    // If enabled, signatures that don't match the XRPL transaction serialized by the contract are rejected.
+    // Otherwise they are stored and reported with an event, so that a serialization mismatch can't stop relayers from signing
+    #[serde(default)]
+    pub enforce_signature_verification: bool,
+}
+
+// How the bridging fees collected are split between relayers
+#[cw_serde]
+#[derive(Default)]
+pub enum FeeDistribution {
+    // All relayers get the same amount
+    #[default]
+    Equal,
+    // Fees of transfers from XRPL only go to the relayers whose evidences reached the threshold.
+    // Fees of transfers to XRPL are split equally because they are collected before any evidence is provided
+    ByParticipation,
+    // Relayers get an amount proportional to their weight
+    Weighted,
+}
+
+impl FeeDistribution {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Equal => "equal",
+            Self::ByParticipation => "by_participation",
+            Self::Weighted => "weighted",
+        }
+    }
+}
+
+// XRPL network that the bridge works with, X-addresses of other networks are rejected
+#[cw_serde]
+#[derive(Default)]
+pub enum XRPLNetwork {
+    #[default]
+    Mainnet,
+    Testnet,
 }
 
 #[cw_serde]
@@ -56,7 +126,10 @@ pub enum BridgeState {
     // Bridge is active and working
     Active,
     // Bridge is halted and no operations can be executed until it's reactivated by owner (if there are no pending rotate keys operation on going)
+    // Funds that the bridge already decided to return (refunds and relayer fees) can still be claimed
     Halted,
+    // Same as halted but no funds can leave the bridge, not even refunds. Only for emergencies
+    FundsFrozen,
 }
 
 #[cw_serde]
@@ -84,6 +157,7 @@ pub enum TokenState {
 
 #[cw_serde]
 pub struct CosmosToken {
+    // For CW20 tokens the denom is the address of the CW20 contract
     pub denom: String,
     pub decimals: u32,
     pub xrpl_currency: String,
@@ -91,6 +165,18 @@ pub struct CosmosToken {
     pub max_holding_amount: Uint128,
     pub state: TokenState,
     pub bridging_fee: Uint128,
+    // Tokens registered before CW20 support was added are all native
+    #[serde(default)]
+    pub token_type: CosmosTokenType,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum CosmosTokenType {
+    #[default]
+    Native,
+    #[serde(rename = "cw20")]
+    CW20,
 }
 
 #[cw_serde]
@@ -102,6 +188,16 @@ pub struct PendingRefund {
     // Optional because Invalid transactions don't have a transaction hash because they are never executed
     pub xrpl_tx_hash: Option<String>,
     pub coin: Coin,
+    // Address that gets the funds instead of the refunded address, if it was provided when sending
+    #[serde(default)]
+    pub refund_to: Option<Addr>,
+}
+
+impl PendingRefund {
+    // Address that gets the funds when the refund is claimed
+    pub fn destination(&self) -> &Addr {
+        self.refund_to.as_ref().unwrap_or(&self.address)
+    }
 }
 
 pub const CONFIG: Item<Config> = Item::new(TopKey::Config.as_str());
@@ -152,6 +248,16 @@ pub const COSMOS_TOKENS: IndexedMap<String, CosmosToken, CosmosTokensIndexes> =
 
 // Evidences, when enough evidences are collected, the transaction hashes are stored in PROCESSED_TXS.
 pub const TX_EVIDENCES: Map<String, Evidences> = Map::new(TopKey::TxEvidences.as_str());
+// Index of TX_EVIDENCES by the block time (in seconds) when each evidence was first seen, used to prune evidences that never reached the threshold.
+// Key is (first_seen, evidence_hash)
+pub const TX_EVIDENCES_FIRST_SEEN: Map<(u64, String), Empty> =
+    Map::new(TopKey::TxEvidencesFirstSeen.as_str());
+// Last evidence hash checked while indexing the evidences stored before TX_EVIDENCES_FIRST_SEEN existed
+pub const LEGACY_EVIDENCES_CURSOR: Item<String> = Item::new(TopKey::LegacyEvidencesCursor.as_str());
+// Index of TX_EVIDENCES by XRPL transaction hash, more than one evidence for the same transaction means relayers disagree on its content.
+// Key is (tx_hash, evidence_hash)
+pub const TX_HASH_EVIDENCES: Map<(String, String), Empty> =
+    Map::new(TopKey::TxHashEvidences.as_str());
 // This will contain the transaction hashes of operations that have been executed (reached threshold) so that when the same hash is sent again they aren't executed again
 pub const PROCESSED_TXS: Map<String, Empty> = Map::new(TopKey::ProcessedTxs.as_str());
 // Current tickets available
@@ -167,6 +273,17 @@ pub const PENDING_OPERATIONS: Map<u64, Operation> = Map::new(TopKey::PendingOper
 pub const PENDING_TICKET_UPDATE: Item<bool> = Item::new(TopKey::PendingTicketUpdate.as_str());
 // Flag to know if we are currently waiting for a rotate keys operation to be completed
 pub const PENDING_ROTATE_KEYS: Item<bool> = Item::new(TopKey::PendingRotateKeys.as_str());
+// Relayer set change (SignerListSet) that is waiting to be confirmed
+pub const PENDING_RELAYER_SET_CHANGE: Item<RelayerSetChange> =
+    Item::new(TopKey::PendingRelayerSetChange.as_str());
+
+#[cw_serde]
+pub struct RelayerSetChange {
+    pub operation_id: u64,
+    // Full key rotations halt the bridge, incremental changes (add, remove or replace a relayer) don't
+    pub halts_bridge: bool,
+}
+
 // Amounts for rejected/invalid transactions on XRPL for each Cosmos user that they can reclaim manually.
 // Key is the tuple (user_address, pending_refund_id)
 pub struct PendingRefundsIndexes<'a> {
@@ -193,6 +310,12 @@ pub const PENDING_REFUNDS: IndexedMap<(Addr, String), PendingRefund, PendingRefu
         },
     );
 
+// Senders that opted in to get the refunds of their rejected transfers to XRPL right away, even if the bridge doesn't do it for everyone
+pub const AUTO_CLAIM_REFUNDS: Map<Addr, Empty> = Map::new(TopKey::AutoClaimRefunds.as_str());
+// Refunds sent directly to their receiver that are waiting for the reply of the submessage. Key is the reply id
+pub const PENDING_AUTO_REFUNDS: Map<u64, PendingRefund> =
+    Map::new(TopKey::PendingAutoRefunds.as_str());
+
 // Fees collected that will be slowly accumulated here and relayers can individually claim them anytime
 pub const FEES_COLLECTED: Map<Addr, Vec<Coin>> = Map::new(TopKey::FeesCollected.as_str());
 // Fees Remainders in case that we have some small amounts left after dividing fees between our relayers we will keep them here until next time we collect fees and can add them to the new amount
@@ -202,12 +325,46 @@ pub const FEE_REMAINDERS: Map<String, Uint128> = Map::new(TopKey::FeeRemainders.
 pub const PROHIBITED_XRPL_ADDRESSES: Map<String, Empty> =
     Map::new(TopKey::ProhibitedXRPLAddresses.as_str());
 
-pub const TEMP_UNIVERSAL_SWAP: Item<TempUniversalSwap> = Item::new("temp_universal_swap");
+// Activity of each relayer. Key is the cosmos address of the relayer
+pub const RELAYER_STATS: Map<Addr, RelayerStats> = Map::new(TopKey::RelayerStats.as_str());
+
+// Universal swaps sent to the osor entry point that will be refunded to the recipient if they fail
+// Key is the swap nonce, which is also the reply id of the submessage
+pub const PENDING_UNIVERSAL_SWAPS: Map<u64, PendingUniversalSwap> =
+    Map::new(TopKey::PendingUniversalSwaps.as_str());
+// Last nonce used as reply id for a universal swap or an IBC forward. It only increases, so every submessage has its own entry
+pub const UNIVERSAL_SWAP_NONCE: Item<u64> = Item::new(TopKey::UniversalSwapNonce.as_str());
 
 #[cw_serde]
-pub struct TempUniversalSwap {
-    pub recovery_address: String,
-    pub return_amount: Coin,
+pub struct PendingUniversalSwap {
+    pub recipient: Addr,
+    pub coin: Coin,
+    // Transaction hash in XRPL of the transfer that triggered the swap
+    pub tx_hash: String,
+    pub fallback: UniversalSwapFallback,
+}
+
+// IBC forwards that were sent but we still didn't get the reply with their packet sequence. Key is the reply id of the submessage
+pub const PENDING_IBC_FORWARDS: Map<u64, IBCForward> =
+    Map::new(TopKey::PendingIBCForwards.as_str());
+// IBC forwards waiting for their acknowledgement or timeout. Key is (channel, packet sequence)
+pub const IBC_FORWARDS_IN_FLIGHT: Map<(String, u64), IBCForward> =
+    Map::new(TopKey::IBCForwardsInFlight.as_str());
+// IBC forwards that were sent but we couldn't get their packet sequence, so their acknowledgement or timeout can't be matched.
+// The owner can turn them into pending refunds if they are lost. Key is the XRPL transaction hash
+pub const UNTRACKED_IBC_FORWARDS: Map<String, IBCForward> =
+    Map::new(TopKey::UntrackedIBCForwards.as_str());
+
+#[cw_serde]
+pub struct IBCForward {
+    pub channel: String,
+    // Address in the destination chain
+    pub receiver: String,
+    // Recipient of the XRPL transfer, who will be able to claim the funds if the forward fails
+    pub recipient: Addr,
+    pub coin: Coin,
+    // Transaction hash in XRPL of the transfer that triggered the forward
+    pub tx_hash: String,
 }
 
 pub enum ContractActions {
@@ -221,6 +378,7 @@ pub enum ContractActions {
     SaveEvidence,
     SaveSignature,
     SendToXRPL,
+    SendToXRPLBatch,
     ClaimFees,
     UpdateXRPLToken,
     UpdateCosmosToken,
@@ -230,11 +388,29 @@ pub enum ContractActions {
     HaltBridge,
     ResumeBridge,
     RotateKeys,
+    AddRelayer,
+    RemoveRelayer,
+    ReplaceRelayerKey,
     CancelPendingOperation,
     UpdateUsedTicketSequenceThreshold,
     AddRateLimit,
     RemoveRateLimit,
     ResetRateLimitQuota,
+    SweepPendingUniversalSwaps,
+    RecoverUntrackedIBCForward,
+    PruneStaleEvidences,
+    IndexLegacyEvidences,
+    UpdateHaltOnEvidenceConflict,
+    UpdateSignatureVerification,
+    UpdateFeeDistribution,
+    UpdateOperationTimeout,
+    ExpireOperation,
+    CancelMyTransfer,
+    UpdateAutoClaimRefunds,
+    SetAutoClaimRefunds,
+    ClaimAllRefunds,
+    ClaimRefundFor,
+    FreezeFunds,
 }
 
 pub enum UserType {
@@ -255,6 +431,7 @@ impl UserType {
             ContractActions::RecoverXRPLTokenRegistration => matches!(self, Self::Owner),
             ContractActions::SaveSignature => matches!(self, Self::Relayer),
             ContractActions::SendToXRPL => true,
+            ContractActions::SendToXRPLBatch => true,
             ContractActions::ClaimFees => matches!(self, Self::Relayer),
             ContractActions::UpdateXRPLToken => matches!(self, Self::Owner),
             ContractActions::UpdateCosmosToken => matches!(self, Self::Owner),
@@ -264,11 +441,29 @@ impl UserType {
             ContractActions::HaltBridge => matches!(self, Self::Owner | Self::Relayer),
             ContractActions::ResumeBridge => matches!(self, Self::Owner),
             ContractActions::RotateKeys => matches!(self, Self::Owner),
+            ContractActions::AddRelayer => matches!(self, Self::Owner),
+            ContractActions::RemoveRelayer => matches!(self, Self::Owner),
+            ContractActions::ReplaceRelayerKey => matches!(self, Self::Owner),
             ContractActions::CancelPendingOperation => matches!(self, Self::Owner),
             ContractActions::UpdateUsedTicketSequenceThreshold => matches!(self, Self::Owner),
             ContractActions::AddRateLimit => matches!(self, Self::Owner),
             ContractActions::RemoveRateLimit => matches!(self, Self::Owner),
             ContractActions::ResetRateLimitQuota => matches!(self, Self::Owner),
+            ContractActions::SweepPendingUniversalSwaps => matches!(self, Self::Owner),
+            ContractActions::RecoverUntrackedIBCForward => matches!(self, Self::Owner),
+            ContractActions::PruneStaleEvidences => true,
+            ContractActions::IndexLegacyEvidences => matches!(self, Self::Owner),
+            ContractActions::UpdateHaltOnEvidenceConflict => matches!(self, Self::Owner),
+            ContractActions::UpdateSignatureVerification => matches!(self, Self::Owner),
+            ContractActions::UpdateFeeDistribution => matches!(self, Self::Owner),
+            ContractActions::UpdateOperationTimeout => matches!(self, Self::Owner),
+            ContractActions::ExpireOperation => true,
+            ContractActions::CancelMyTransfer => true,
+            ContractActions::UpdateAutoClaimRefunds => matches!(self, Self::Owner),
+            ContractActions::SetAutoClaimRefunds => true,
+            ContractActions::ClaimAllRefunds => true,
+            ContractActions::ClaimRefundFor => true,
+            ContractActions::FreezeFunds => matches!(self, Self::Owner),
         }
     }
 }
@@ -286,6 +481,7 @@ impl ContractActions {
             Self::SaveEvidence => "save_evidence",
             Self::SaveSignature => "save_signature",
             Self::SendToXRPL => "send_to_xrpl",
+            Self::SendToXRPLBatch => "send_to_xrpl_batch",
             Self::ClaimFees => "claim_fees",
             Self::ClaimRefunds => "claim_refunds",
             Self::UpdateXRPLToken => "update_xrpl_token",
@@ -295,11 +491,29 @@ impl ContractActions {
             Self::HaltBridge => "halt_bridge",
             Self::ResumeBridge => "resume_bridge",
             Self::RotateKeys => "rotate_keys",
+            Self::AddRelayer => "add_relayer",
+            Self::RemoveRelayer => "remove_relayer",
+            Self::ReplaceRelayerKey => "replace_relayer_key",
             Self::CancelPendingOperation => "cancel_pending_operation",
             Self::UpdateUsedTicketSequenceThreshold => "update_used_ticket_sequence_threshold",
             Self::AddRateLimit => "add_rate_limit",
             Self::RemoveRateLimit => "remove_rate_limit",
             Self::ResetRateLimitQuota => "reset_rate_limit_quota",
+            Self::SweepPendingUniversalSwaps => "sweep_pending_universal_swaps",
+            Self::RecoverUntrackedIBCForward => "recover_untracked_ibc_forward",
+            Self::PruneStaleEvidences => "prune_stale_evidences",
+            Self::IndexLegacyEvidences => "index_legacy_evidences",
+            Self::UpdateHaltOnEvidenceConflict => "update_halt_on_evidence_conflict",
+            Self::UpdateSignatureVerification => "update_signature_verification",
+            Self::UpdateFeeDistribution => "update_fee_distribution",
+            Self::UpdateOperationTimeout => "update_operation_timeout",
+            Self::ExpireOperation => "expire_operation",
+            Self::CancelMyTransfer => "cancel_my_transfer",
+            Self::UpdateAutoClaimRefunds => "update_auto_claim_refunds",
+            Self::SetAutoClaimRefunds => "set_auto_claim_refunds",
+            Self::ClaimAllRefunds => "claim_all_refunds",
+            Self::ClaimRefundFor => "claim_refund_for",
+            Self::FreezeFunds => "freeze_funds",
         }
     }
 }
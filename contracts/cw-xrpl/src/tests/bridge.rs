@@ -1,17 +1,22 @@
 use crate::error::ContractError;
 use crate::evidence::{Evidence, OperationResult, TransactionResult};
 use crate::msg::{
-    BridgeStateResponse, CosmosTokensResponse, ExecuteMsg, FeesCollectedResponse,
-    PendingOperationsResponse, PendingRefundsResponse, QueryMsg, XRPLTokensResponse,
+    AvailableTicketsResponse, BridgeStateResponse, ConflictingEvidencesResponse,
+    CosmosTokensResponse, ExecuteMsg, FeesCollectedResponse, PendingOperationsResponse,
+    PendingRefundsResponse, QueryMsg, XRPLTokensResponse,
 };
 use crate::operation::{Operation, OperationType};
-use crate::state::{BridgeState, Config, CosmosToken, TokenState};
+use crate::state::{BridgeState, Config, CosmosToken, CosmosTokenType, TokenState};
 use crate::tests::helper::{
-    generate_hash, generate_xrpl_address, generate_xrpl_pub_key, MockApp, FEE_DENOM,
-    TRUST_SET_LIMIT_AMOUNT,
+    generate_hash, generate_xrpl_address, generate_xrpl_pub_key, generate_xrpl_signer, MockApp,
+    FEE_DENOM, TRUST_SET_LIMIT_AMOUNT,
 };
 use crate::token::full_denom;
-use crate::{contract::XRP_CURRENCY, msg::InstantiateMsg, relayer::Relayer};
+use crate::{
+    contract::{XRP_CURRENCY, XRP_ISSUER, XRP_SYMBOL},
+    msg::InstantiateMsg,
+    relayer::Relayer,
+};
 use cosmwasm_std::{coin, coins, Addr, Uint128};
 use cw20::Cw20Coin;
 
@@ -41,6 +46,7 @@ fn bridge_fee_collection_and_claiming() {
             cosmos_address: Addr::unchecked(&accounts[i]),
             xrpl_address: xrpl_addresses[i as usize].to_string(),
             xrpl_pub_key: xrpl_pub_keys[i as usize].to_string(),
+            weight: None,
         });
     }
 
@@ -69,6 +75,7 @@ fn bridge_fee_collection_and_claiming() {
                 issue_token: true,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -142,6 +149,7 @@ fn bridge_fee_collection_and_claiming() {
         bridging_fee: Uint128::new(300000),            // 3e5
         xrpl_currency: XRP_CURRENCY.to_string(),
         state: TokenState::Enabled,
+        token_type: CosmosTokenType::Native,
     };
 
     // Register XRPL originated token and confirm trust set
@@ -204,6 +212,7 @@ fn bridge_fee_collection_and_claiming() {
             sending_precision: test_token_cosmos.sending_precision,
             max_holding_amount: test_token_cosmos.max_holding_amount,
             bridging_fee: test_token_cosmos.bridging_fee,
+            token_type: None,
         },
         &[],
     )
@@ -239,6 +248,8 @@ fn bridge_fee_collection_and_claiming() {
                     amount: Uint128::new(1000000000050000), // 1e15 + 5e4 --> This should take the bridging fee (5e4) and truncate nothing
                     recipient: Addr::unchecked(receiver),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -292,6 +303,8 @@ fn bridge_fee_collection_and_claiming() {
                     amount: Uint128::new(1000000000040000), // 1e15 + 4e4 --> This should take the bridging fee -> 1999999999990000 and truncate -> 1999999999900000
                     recipient: Addr::unchecked(receiver),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -333,6 +346,8 @@ fn bridge_fee_collection_and_claiming() {
                     amount: Uint128::new(1000000000000000), // 1e15 --> This should charge bridging fee -> 1999999999950000 and truncate -> 1999999999900000
                     recipient: Addr::unchecked(receiver),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -375,6 +390,8 @@ fn bridge_fee_collection_and_claiming() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(1000000000020000, xrpl_token.cosmos_denom.clone()), // This should charge the bridging fee -> 999999999970000 and then truncate the rest -> 999999999900000
     )
@@ -395,6 +412,7 @@ fn bridge_fee_collection_and_claiming() {
         query_pending_operations.operations[0],
         Operation {
             id: query_pending_operations.operations[0].id.clone(),
+            created_at: query_pending_operations.operations[0].created_at,
             version: 1,
             ticket_sequence: Some(2),
             account_sequence: None,
@@ -406,6 +424,8 @@ fn bridge_fee_collection_and_claiming() {
                 max_amount: Some(Uint128::new(999999999900000)),
                 sender: Addr::unchecked(receiver),
                 recipient: xrpl_receiver_address.clone(),
+                destination_tag: None,
+                refund_to: None,
             },
             xrpl_base_fee,
         }
@@ -458,6 +478,8 @@ fn bridge_fee_collection_and_claiming() {
             &ExecuteMsg::SendToXRPL {
                 recipient: xrpl_receiver_address.clone(),
                 deliver_amount: Some(Uint128::new(1000000000010000)),
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(1000000000020000, xrpl_token.cosmos_denom.clone()), // After fees and truncation -> 1000000000000000 > 999999999900000
         )
@@ -474,6 +496,8 @@ fn bridge_fee_collection_and_claiming() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount, // This will be truncated to 700000000000000
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(1000000000020000, xrpl_token.cosmos_denom.clone()), // This should charge the bridging fee -> 999999999970000 and then truncate the rest -> 999999999900000
     )
@@ -494,6 +518,7 @@ fn bridge_fee_collection_and_claiming() {
         query_pending_operations.operations[0],
         Operation {
             id: query_pending_operations.operations[0].id.clone(),
+            created_at: query_pending_operations.operations[0].created_at,
             version: 1,
             ticket_sequence: Some(3),
             account_sequence: None,
@@ -505,6 +530,8 @@ fn bridge_fee_collection_and_claiming() {
                 max_amount: Some(Uint128::new(999999999900000)),
                 sender: Addr::unchecked(receiver),
                 recipient: xrpl_receiver_address.clone(),
+                destination_tag: None,
+                refund_to: None,
             },
             xrpl_base_fee
         }
@@ -583,6 +610,8 @@ fn bridge_fee_collection_and_claiming() {
             &ExecuteMsg::SendToXRPL {
                 recipient: xrpl_receiver_address.clone(),
                 deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(100, denom.clone()),
         )
@@ -600,6 +629,8 @@ fn bridge_fee_collection_and_claiming() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(600010, denom.clone()), // This should charge briding fee -> 300010 and then truncate the rest -> 300000
     )
@@ -620,6 +651,7 @@ fn bridge_fee_collection_and_claiming() {
         query_pending_operations.operations[0],
         Operation {
             id: query_pending_operations.operations[0].id.clone(),
+            created_at: query_pending_operations.operations[0].created_at,
             version: 1,
             ticket_sequence: Some(4),
             account_sequence: None,
@@ -631,6 +663,8 @@ fn bridge_fee_collection_and_claiming() {
                 max_amount: Some(Uint128::new(300000000000000)),
                 sender: Addr::unchecked(receiver),
                 recipient: xrpl_receiver_address.clone(),
+                destination_tag: None,
+                refund_to: None,
             },
             xrpl_base_fee
         }
@@ -680,6 +714,8 @@ fn bridge_fee_collection_and_claiming() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(900000, denom.clone()), // This charge the entire bridging fee (300000) and truncate nothing
     )
@@ -700,6 +736,7 @@ fn bridge_fee_collection_and_claiming() {
         query_pending_operations.operations[0],
         Operation {
             id: query_pending_operations.operations[0].id.clone(),
+            created_at: query_pending_operations.operations[0].created_at,
             version: 1,
             ticket_sequence: Some(5),
             account_sequence: None,
@@ -711,6 +748,8 @@ fn bridge_fee_collection_and_claiming() {
                 max_amount: Some(Uint128::new(600000000000000)),
                 sender: Addr::unchecked(receiver),
                 recipient: xrpl_receiver_address.clone(),
+                destination_tag: None,
+                refund_to: None,
             },
             xrpl_base_fee,
         }
@@ -772,6 +811,8 @@ fn bridge_fee_collection_and_claiming() {
                     amount: Uint128::new(650010000000000), // 650010000000000 will convert to 650010, which after charging bridging fees (300000) and truncating (10) will send 350000 to the receiver
                     recipient: Addr::unchecked(receiver),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -978,10 +1019,12 @@ fn bridge_halting_and_resuming() {
     let signer = &accounts[0];
     let relayer_account = &accounts[1];
     let new_relayer_account = &accounts[2];
+    let xrpl_signer = generate_xrpl_signer();
     let relayer = Relayer {
         cosmos_address: Addr::unchecked(relayer_account),
-        xrpl_address: generate_xrpl_address(),
-        xrpl_pub_key: generate_xrpl_pub_key(),
+        xrpl_address: xrpl_signer.xrpl_address.clone(),
+        xrpl_pub_key: xrpl_signer.xrpl_pub_key.clone(),
+        weight: None,
     };
 
     let bridge_xrpl_address = generate_xrpl_address();
@@ -1005,6 +1048,7 @@ fn bridge_halting_and_resuming() {
                 issue_token: true,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -1066,6 +1110,7 @@ fn bridge_halting_and_resuming() {
                 sending_precision: 1,
                 max_holding_amount: Uint128::one(),
                 bridging_fee: Uint128::zero(),
+                token_type: None,
             },
             &[],
         )
@@ -1104,6 +1149,8 @@ fn bridge_halting_and_resuming() {
             &ExecuteMsg::SendToXRPL {
                 recipient: generate_xrpl_address(),
                 deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(1, FEE_DENOM),
         )
@@ -1156,8 +1203,8 @@ fn bridge_halting_and_resuming() {
         .to_string()
         .contains(ContractError::BridgeHalted {}.to_string().as_str()));
 
-    // Claiming pending refunds or relayers fees should fail
-    let bridge_halted_error = app
+    // Claiming pending refunds is still allowed while the bridge is halted
+    let claim_error = app
         .execute(
             Addr::unchecked(signer),
             contract_addr.clone(),
@@ -1168,12 +1215,107 @@ fn bridge_halting_and_resuming() {
         )
         .unwrap_err();
 
+    assert!(claim_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::PendingRefundNotFound {}.to_string().as_str()));
+
+    // Only the owner can freeze the funds of the bridge
+    let freeze_error = app
+        .execute(
+            Addr::unchecked(relayer_account),
+            contract_addr.clone(),
+            &ExecuteMsg::FreezeFunds {},
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(freeze_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::UnauthorizedSender {}.to_string().as_str()));
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::FreezeFunds {},
+        &[],
+    )
+    .unwrap();
+
+    let query_bridge_state: BridgeStateResponse = app
+        .query(contract_addr.clone(), &QueryMsg::BridgeState {})
+        .unwrap();
+
+    assert_eq!(query_bridge_state.state, BridgeState::FundsFrozen);
+
+    // Funds can't be frozen twice
+    let freeze_error = app
+        .execute(
+            Addr::unchecked(signer),
+            contract_addr.clone(),
+            &ExecuteMsg::FreezeFunds {},
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(freeze_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::FundsFrozen {}.to_string().as_str()));
+
+    // Sending from Cosmos to XRPL should still fail
+    let bridge_halted_error = app
+        .execute(
+            Addr::unchecked(signer),
+            contract_addr.clone(),
+            &ExecuteMsg::SendToXRPL {
+                recipient: generate_xrpl_address(),
+                deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
+            },
+            &coins(1, FEE_DENOM),
+        )
+        .unwrap_err();
+
     assert!(bridge_halted_error
         .root_cause()
         .to_string()
         .contains(ContractError::BridgeHalted {}.to_string().as_str()));
 
-    let bridge_halted_error = app
+    // Claiming pending refunds or relayers fees should fail
+    let funds_frozen_error = app
+        .execute(
+            Addr::unchecked(signer),
+            contract_addr.clone(),
+            &ExecuteMsg::ClaimRefund {
+                pending_refund_id: "any_id".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(funds_frozen_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::FundsFrozen {}.to_string().as_str()));
+
+    let funds_frozen_error = app
+        .execute(
+            Addr::unchecked(signer),
+            contract_addr.clone(),
+            &ExecuteMsg::ClaimAllRefunds { limit: None },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(funds_frozen_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::FundsFrozen {}.to_string().as_str()));
+
+    let funds_frozen_error = app
         .execute(
             Addr::unchecked(relayer_account),
             contract_addr.clone(),
@@ -1184,7 +1326,67 @@ fn bridge_halting_and_resuming() {
         )
         .unwrap_err();
 
-    assert!(bridge_halted_error
+    assert!(funds_frozen_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::FundsFrozen {}.to_string().as_str()));
+
+    // Only the owner can halt a bridge with frozen funds, because claims are allowed again
+    let halt_error = app
+        .execute(
+            Addr::unchecked(relayer_account),
+            contract_addr.clone(),
+            &ExecuteMsg::HaltBridge {},
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(halt_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::UnauthorizedSender {}.to_string().as_str()));
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::HaltBridge {},
+        &[],
+    )
+    .unwrap();
+
+    let query_bridge_state: BridgeStateResponse = app
+        .query(contract_addr.clone(), &QueryMsg::BridgeState {})
+        .unwrap();
+
+    assert_eq!(query_bridge_state.state, BridgeState::Halted);
+
+    let claim_error = app
+        .execute(
+            Addr::unchecked(signer),
+            contract_addr.clone(),
+            &ExecuteMsg::ClaimRefund {
+                pending_refund_id: "any_id".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(claim_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::PendingRefundNotFound {}.to_string().as_str()));
+
+    // A halted bridge can't be halted again
+    let halt_error = app
+        .execute(
+            Addr::unchecked(signer),
+            contract_addr.clone(),
+            &ExecuteMsg::HaltBridge {},
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(halt_error
         .root_cause()
         .to_string()
         .contains(ContractError::BridgeHalted {}.to_string().as_str()));
@@ -1219,6 +1421,7 @@ fn bridge_halting_and_resuming() {
         cosmos_address: Addr::unchecked(new_relayer_account),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     // We perform a key rotation
@@ -1249,6 +1452,7 @@ fn bridge_halting_and_resuming() {
         query_pending_operations.operations[0],
         Operation {
             id: query_pending_operations.operations[0].id.clone(),
+            created_at: query_pending_operations.operations[0].created_at,
             version: 1,
             ticket_sequence: Some(1),
             account_sequence: None,
@@ -1283,7 +1487,11 @@ fn bridge_halting_and_resuming() {
         &ExecuteMsg::SaveSignature {
             operation_id: 1,
             operation_version: 1,
-            signature: "signature".to_string(),
+            signature: xrpl_signer.sign_operation(
+                &query_pending_operations.operations[0],
+                &bridge_xrpl_address,
+                1,
+            ),
         },
         &[],
     )
@@ -1302,6 +1510,8 @@ fn bridge_halting_and_resuming() {
                     amount: Uint128::new(100),
                     recipient: Addr::unchecked(signer),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -1399,3 +1609,464 @@ fn bridge_halting_and_resuming() {
     )
     .unwrap();
 }
+
+#[test]
+fn bridge_halting_on_evidence_conflict() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[2];
+    let relayers: Vec<Relayer> = accounts[0..2]
+        .iter()
+        .map(|account| Relayer {
+            cosmos_address: Addr::unchecked(account),
+            xrpl_address: generate_xrpl_address(),
+            xrpl_pub_key: generate_xrpl_pub_key(),
+            weight: None,
+        })
+        .collect();
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: relayers.clone(),
+                evidence_threshold: 2,
+                used_ticket_sequence_threshold: 4,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: generate_xrpl_address(),
+                xrpl_base_fee: 10,
+                token_factory_addr: token_factory_addr.clone(),
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    let send_transfer_evidence = |app: &mut MockApp, relayer: &Relayer, tx_hash: &str, amount| {
+        app.execute(
+            relayer.cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveEvidence {
+                evidence: Evidence::XRPLToCosmosTransfer {
+                    tx_hash: tx_hash.to_string(),
+                    issuer: XRP_ISSUER.to_string(),
+                    currency: XRP_CURRENCY.to_string(),
+                    amount: Uint128::new(amount),
+                    recipient: Addr::unchecked(signer),
+                    memo: None,
+                    source_tag: None,
+                    raw_memos: None,
+                },
+            },
+            &[],
+        )
+        .unwrap()
+    };
+
+    // Relayers disagree on the amount of a transfer, the conflict is reported but the bridge keeps working
+    let tx_hash = generate_hash();
+    let result = send_transfer_evidence(&mut app, &relayers[0], &tx_hash, 100);
+    assert!(!result
+        .events
+        .iter()
+        .any(|e| e.ty == "wasm-evidence_conflict"));
+
+    let result = send_transfer_evidence(&mut app, &relayers[1], &tx_hash, 200);
+    assert!(result
+        .events
+        .iter()
+        .any(|e| e.ty == "wasm-evidence_conflict"
+            && e.attributes
+                .iter()
+                .any(|a| a.key == "tx_hash" && a.value == tx_hash.to_uppercase())
+            && e.attributes
+                .iter()
+                .any(|a| a.key == "bridge_halted" && a.value == false.to_string())));
+
+    let query_conflicting_evidences: ConflictingEvidencesResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::ConflictingEvidences {
+                tx_hash: tx_hash.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(query_conflicting_evidences.evidences.len(), 2);
+
+    let query_bridge_state: BridgeStateResponse = app
+        .query(contract_addr.clone(), &QueryMsg::BridgeState {})
+        .unwrap();
+    assert_eq!(query_bridge_state.state, BridgeState::Active);
+
+    // Only the owner can enable halting on conflicts
+    let unauthorized_error = app
+        .execute(
+            relayers[0].cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::UpdateHaltOnEvidenceConflict {
+                halt_on_evidence_conflict: true,
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(unauthorized_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::UnauthorizedSender {}.to_string().as_str()));
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::UpdateHaltOnEvidenceConflict {
+            halt_on_evidence_conflict: true,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let query_config: Config = app
+        .query(contract_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert!(query_config.halt_on_evidence_conflict);
+
+    // Now a conflict halts the bridge
+    let tx_hash = generate_hash();
+    send_transfer_evidence(&mut app, &relayers[0], &tx_hash, 100);
+    let result = send_transfer_evidence(&mut app, &relayers[1], &tx_hash, 300);
+    assert!(result
+        .events
+        .iter()
+        .any(|e| e.ty == "wasm-evidence_conflict"
+            && e.attributes
+                .iter()
+                .any(|a| a.key == "bridge_halted" && a.value == true.to_string())));
+
+    let query_bridge_state: BridgeStateResponse = app
+        .query(contract_addr.clone(), &QueryMsg::BridgeState {})
+        .unwrap();
+    assert_eq!(query_bridge_state.state, BridgeState::Halted);
+}
+
+#[test]
+fn evidence_conflict_that_halts_bridge_is_not_executed() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+        ("account3", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[2];
+    let receiver = &accounts[3];
+    // The second relayer reaches the threshold on its own
+    let relayers: Vec<Relayer> = accounts[0..2]
+        .iter()
+        .enumerate()
+        .map(|(i, account)| Relayer {
+            cosmos_address: Addr::unchecked(account),
+            xrpl_address: generate_xrpl_address(),
+            xrpl_pub_key: generate_xrpl_pub_key(),
+            weight: Some(i as u16 + 1),
+        })
+        .collect();
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: relayers.clone(),
+                evidence_threshold: 2,
+                used_ticket_sequence_threshold: 4,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: generate_xrpl_address(),
+                xrpl_base_fee: 10,
+                token_factory_addr: token_factory_addr.clone(),
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::UpdateHaltOnEvidenceConflict {
+            halt_on_evidence_conflict: true,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let tx_hash = generate_hash();
+    for (relayer, amount) in relayers.iter().zip([100, 200]) {
+        app.execute(
+            relayer.cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveEvidence {
+                evidence: Evidence::XRPLToCosmosTransfer {
+                    tx_hash: tx_hash.clone(),
+                    issuer: XRP_ISSUER.to_string(),
+                    currency: XRP_CURRENCY.to_string(),
+                    amount: Uint128::new(amount),
+                    recipient: Addr::unchecked(receiver),
+                    memo: None,
+                    source_tag: None,
+                    raw_memos: None,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    // The conflicting evidence halted the bridge so it's stored instead of being executed
+    let query_bridge_state: BridgeStateResponse = app
+        .query(contract_addr.clone(), &QueryMsg::BridgeState {})
+        .unwrap();
+    assert_eq!(query_bridge_state.state, BridgeState::Halted);
+
+    let receiver_balance = app
+        .query_balance(
+            Addr::unchecked(receiver),
+            full_denom(&token_factory_addr, XRP_SYMBOL),
+        )
+        .unwrap();
+    assert!(receiver_balance.is_zero());
+
+    let query_conflicting_evidences: ConflictingEvidencesResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::ConflictingEvidences {
+                tx_hash: tx_hash.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(query_conflicting_evidences.evidences.len(), 2);
+}
+
+#[test]
+fn later_evidences_for_conflicting_transaction_are_not_executed() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+        ("account3", &coins(100_000_000_000, FEE_DENOM)),
+        ("account4", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[3];
+    let receiver = &accounts[4];
+    let relayers: Vec<Relayer> = accounts[0..3]
+        .iter()
+        .map(|account| Relayer {
+            cosmos_address: Addr::unchecked(account),
+            xrpl_address: generate_xrpl_address(),
+            xrpl_pub_key: generate_xrpl_pub_key(),
+            weight: None,
+        })
+        .collect();
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+
+    // The bridge is not halted on conflicts, so it stays active
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: relayers.clone(),
+                evidence_threshold: 2,
+                used_ticket_sequence_threshold: 4,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: generate_xrpl_address(),
+                xrpl_base_fee: 10,
+                token_factory_addr: token_factory_addr.clone(),
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    // The third relayer agrees with the first one, which would reach the threshold for an existing evidence
+    let tx_hash = generate_hash();
+    for (relayer, amount) in relayers.iter().zip([100, 200, 100]) {
+        app.execute(
+            relayer.cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveEvidence {
+                evidence: Evidence::XRPLToCosmosTransfer {
+                    tx_hash: tx_hash.clone(),
+                    issuer: XRP_ISSUER.to_string(),
+                    currency: XRP_CURRENCY.to_string(),
+                    amount: Uint128::new(amount),
+                    recipient: Addr::unchecked(receiver),
+                    memo: None,
+                    source_tag: None,
+                    raw_memos: None,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    let query_bridge_state: BridgeStateResponse = app
+        .query(contract_addr.clone(), &QueryMsg::BridgeState {})
+        .unwrap();
+    assert_eq!(query_bridge_state.state, BridgeState::Active);
+
+    let receiver_balance = app
+        .query_balance(
+            Addr::unchecked(receiver),
+            full_denom(&token_factory_addr, XRP_SYMBOL),
+        )
+        .unwrap();
+    assert!(receiver_balance.is_zero());
+
+    let query_conflicting_evidences: ConflictingEvidencesResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::ConflictingEvidences {
+                tx_hash: tx_hash.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(query_conflicting_evidences.evidences.len(), 2);
+}
+
+#[test]
+fn evidence_conflict_while_bridge_halted_is_not_executed() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[2];
+    // The second relayer reaches the threshold on its own
+    let relayers: Vec<Relayer> = accounts[0..2]
+        .iter()
+        .enumerate()
+        .map(|(i, account)| Relayer {
+            cosmos_address: Addr::unchecked(account),
+            xrpl_address: generate_xrpl_address(),
+            xrpl_pub_key: generate_xrpl_pub_key(),
+            weight: Some(i as u16 + 1),
+        })
+        .collect();
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: relayers.clone(),
+                evidence_threshold: 2,
+                used_ticket_sequence_threshold: 4,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: generate_xrpl_address(),
+                xrpl_base_fee: 10,
+                token_factory_addr,
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::UpdateHaltOnEvidenceConflict {
+            halt_on_evidence_conflict: true,
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::HaltBridge {},
+        &[],
+    )
+    .unwrap();
+
+    // Ticket allocations are still processed while the bridge is halted
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RecoverTickets {
+            account_sequence: 1,
+            number_of_tickets: Some(5),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let tx_hash = generate_hash();
+    for (relayer, first_ticket) in relayers.iter().zip([1, 2]) {
+        app.execute(
+            relayer.cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveEvidence {
+                evidence: Evidence::XRPLTransactionResult {
+                    tx_hash: Some(tx_hash.clone()),
+                    account_sequence: Some(1),
+                    ticket_sequence: None,
+                    transaction_result: TransactionResult::Accepted,
+                    operation_result: Some(OperationResult::TicketsAllocation {
+                        tickets: Some((first_ticket..first_ticket + 5).collect()),
+                    }),
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    // The bridge was already halted, but the conflicting evidence is still only stored
+    let query_available_tickets: AvailableTicketsResponse = app
+        .query(contract_addr.clone(), &QueryMsg::AvailableTickets {})
+        .unwrap();
+    assert!(query_available_tickets.tickets.is_empty());
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(query_pending_operations.operations.len(), 1);
+
+    let query_conflicting_evidences: ConflictingEvidencesResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::ConflictingEvidences { tx_hash },
+        )
+        .unwrap();
+    assert_eq!(query_conflicting_evidences.evidences.len(), 2);
+}
@@ -0,0 +1,376 @@
+use cosmwasm_std::{coin, coins, to_json_binary, Addr, Uint128};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
+
+use crate::evidence::{Evidence, OperationResult, TransactionResult};
+use crate::msg::{
+    Cw20HookMsg, ExecuteMsg, FeesCollectedResponse, InstantiateMsg, PendingOperationsResponse,
+    PendingRefundsResponse, QueryMsg,
+};
+use crate::operation::OperationType;
+use crate::relayer::Relayer;
+use crate::state::{CosmosToken, CosmosTokenType};
+use crate::tests::helper::{
+    generate_hash, generate_xrpl_address, generate_xrpl_pub_key, MockApp, FEE_DENOM,
+    TRUST_SET_LIMIT_AMOUNT,
+};
+
+#[test]
+#[cfg(not(feature = "test-tube"))]
+fn bridge_cw20_tokens() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[0];
+    let sender = &accounts[1];
+    let relayer_account = &accounts[2];
+
+    let relayer = Relayer {
+        cosmos_address: Addr::unchecked(relayer_account),
+        xrpl_address: generate_xrpl_address(),
+        xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
+    };
+
+    let xrpl_receiver_address = generate_xrpl_address();
+    let bridge_xrpl_address = generate_xrpl_address();
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: vec![relayer],
+                evidence_threshold: 1,
+                used_ticket_sequence_threshold: 2,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: bridge_xrpl_address.clone(),
+                xrpl_base_fee: 10,
+                token_factory_addr,
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    // Add enough tickets for all our test operations
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RecoverTickets {
+            account_sequence: 1,
+            number_of_tickets: Some(5),
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute(
+        Addr::unchecked(relayer_account),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveEvidence {
+            evidence: Evidence::XRPLTransactionResult {
+                tx_hash: Some(generate_hash()),
+                account_sequence: Some(1),
+                ticket_sequence: None,
+                transaction_result: TransactionResult::Accepted,
+                operation_result: Some(OperationResult::TicketsAllocation {
+                    tickets: Some((1..6).collect()),
+                }),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let initial_amount = Uint128::new(100_000_000);
+    let cw20_addr = app
+        .create_cw20_token(
+            Addr::unchecked(signer),
+            vec![Cw20Coin {
+                address: sender.to_string(),
+                amount: initial_amount,
+            }],
+        )
+        .unwrap();
+
+    // Registering an address that is not a CW20 contract fails
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RegisterCosmosToken {
+            denom: signer.to_string(),
+            decimals: 6,
+            sending_precision: 6,
+            max_holding_amount: initial_amount,
+            bridging_fee: Uint128::zero(),
+            token_type: Some(CosmosTokenType::CW20),
+        },
+        &[],
+    )
+    .unwrap_err();
+
+    let bridging_fee = Uint128::new(1_000);
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RegisterCosmosToken {
+            denom: cw20_addr.to_string(),
+            decimals: 6,
+            sending_precision: 6,
+            max_holding_amount: initial_amount,
+            bridging_fee,
+            token_type: Some(CosmosTokenType::CW20),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let cosmos_token: CosmosToken = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::CosmosToken {
+                key: cw20_addr.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(cosmos_token.token_type, CosmosTokenType::CW20);
+
+    // Send the CW20 token to XRPL with the hook message
+    let amount_to_send = Uint128::new(1_001_000);
+    app.execute(
+        Addr::unchecked(sender),
+        cw20_addr.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: contract_addr.to_string(),
+            amount: amount_to_send,
+            msg: to_json_binary(&Cw20HookMsg::SendToXRPL {
+                recipient: xrpl_receiver_address.clone(),
+                destination_tag: None,
+                refund_to: None,
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let bridge_balance: BalanceResponse = app
+        .query(
+            cw20_addr.clone(),
+            &Cw20QueryMsg::Balance {
+                address: contract_addr.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(bridge_balance.balance, amount_to_send);
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(query_pending_operations.operations.len(), 1);
+    let operation = &query_pending_operations.operations[0];
+    assert_eq!(
+        operation.operation_type,
+        OperationType::CosmosToXRPLTransfer {
+            issuer: bridge_xrpl_address.clone(),
+            currency: cosmos_token.xrpl_currency.clone(),
+            // 1_000_000 in 6 decimals is 1e15 in XRPL decimals
+            amount: Uint128::new(1_000_000_000_000_000),
+            max_amount: Some(Uint128::new(1_000_000_000_000_000)),
+            sender: Addr::unchecked(sender),
+            recipient: xrpl_receiver_address.clone(),
+            destination_tag: None,
+            refund_to: None,
+        }
+    );
+
+    // Confirm the operation so that the tokens stay in escrow
+    app.execute(
+        Addr::unchecked(relayer_account),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveEvidence {
+            evidence: Evidence::XRPLTransactionResult {
+                tx_hash: Some(generate_hash()),
+                account_sequence: None,
+                ticket_sequence: operation.ticket_sequence,
+                transaction_result: TransactionResult::Accepted,
+                operation_result: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Bring the tokens back from XRPL, the recipient gets them in CW20 tokens after paying the bridging fee
+    let cosmos_recipient = Addr::unchecked("cosmos_recipient");
+    app.execute(
+        Addr::unchecked(relayer_account),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveEvidence {
+            evidence: Evidence::XRPLToCosmosTransfer {
+                tx_hash: generate_hash(),
+                issuer: bridge_xrpl_address.clone(),
+                currency: cosmos_token.xrpl_currency.clone(),
+                amount: Uint128::new(1_000_000_000_000_000),
+                recipient: cosmos_recipient.clone(),
+                memo: None,
+                source_tag: None,
+                raw_memos: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let recipient_balance: BalanceResponse = app
+        .query(
+            cw20_addr.clone(),
+            &Cw20QueryMsg::Balance {
+                address: cosmos_recipient.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(recipient_balance.balance, Uint128::new(999_000));
+
+    // Send again and reject the operation so that the sender can claim the refund in CW20 tokens
+    app.execute(
+        Addr::unchecked(sender),
+        cw20_addr.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: contract_addr.to_string(),
+            amount: amount_to_send,
+            msg: to_json_binary(&Cw20HookMsg::SendToXRPL {
+                recipient: xrpl_receiver_address.clone(),
+                destination_tag: None,
+                refund_to: None,
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(query_pending_operations.operations.len(), 1);
+
+    app.execute(
+        Addr::unchecked(relayer_account),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveEvidence {
+            evidence: Evidence::XRPLTransactionResult {
+                tx_hash: Some(generate_hash()),
+                account_sequence: None,
+                ticket_sequence: query_pending_operations.operations[0].ticket_sequence,
+                transaction_result: TransactionResult::Rejected,
+                operation_result: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let query_pending_refunds: PendingRefundsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingRefunds {
+                address: Addr::unchecked(sender),
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(query_pending_refunds.pending_refunds.len(), 1);
+    assert_eq!(
+        query_pending_refunds.pending_refunds[0].coin,
+        coin(1_000_000, cw20_addr.to_string())
+    );
+
+    app.execute(
+        Addr::unchecked(sender),
+        contract_addr.clone(),
+        &ExecuteMsg::ClaimRefund {
+            pending_refund_id: query_pending_refunds.pending_refunds[0].id.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let sender_balance: BalanceResponse = app
+        .query(
+            cw20_addr.clone(),
+            &Cw20QueryMsg::Balance {
+                address: sender.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        sender_balance.balance,
+        initial_amount - amount_to_send - amount_to_send + Uint128::new(1_000_000)
+    );
+
+    // The relayer claims the fees collected in CW20 tokens
+    let query_fees_collected: FeesCollectedResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::FeesCollected {
+                relayer_address: Addr::unchecked(relayer_account),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        query_fees_collected.fees_collected,
+        vec![coin(bridging_fee.u128() * 3, cw20_addr.to_string())]
+    );
+
+    app.execute(
+        Addr::unchecked(relayer_account),
+        contract_addr.clone(),
+        &ExecuteMsg::ClaimRelayerFees {
+            amounts: query_fees_collected.fees_collected,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let relayer_balance: BalanceResponse = app
+        .query(
+            cw20_addr.clone(),
+            &Cw20QueryMsg::Balance {
+                address: relayer_account.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(relayer_balance.balance, bridging_fee * Uint128::new(3));
+
+    let bridge_balance: BalanceResponse = app
+        .query(
+            cw20_addr,
+            &Cw20QueryMsg::Balance {
+                address: contract_addr.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(bridge_balance.balance, Uint128::zero());
+}
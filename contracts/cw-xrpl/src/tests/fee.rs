@@ -1,13 +1,15 @@
 use crate::error::ContractError;
 use crate::evidence::{Evidence, OperationResult, TransactionResult};
-use crate::msg::{ExecuteMsg, PendingOperationsResponse, QueryMsg};
-use crate::state::Config;
+use crate::msg::{
+    ExecuteMsg, FeesCollectedResponse, PendingOperationsResponse, QueryMsg, XRPLTokensResponse,
+};
+use crate::state::{Config, FeeDistribution};
 use crate::tests::helper::{
-    generate_hash, generate_xrpl_address, generate_xrpl_pub_key, MockApp, FEE_DENOM,
+    generate_hash, generate_xrpl_address, generate_xrpl_signer, MockApp, XRPLSigner, FEE_DENOM,
     TRUST_SET_LIMIT_AMOUNT,
 };
 use crate::{msg::InstantiateMsg, relayer::Relayer};
-use cosmwasm_std::{coins, Addr, Uint128};
+use cosmwasm_std::{coin, coins, Addr, Uint128};
 
 #[test]
 fn updating_xrpl_base_fee() {
@@ -20,8 +22,7 @@ fn updating_xrpl_base_fee() {
     let accounts_number = accounts.len();
 
     let signer = &accounts[accounts_number - 1];
-    let xrpl_addresses: Vec<String> = (0..3).map(|_| generate_xrpl_address()).collect();
-    let xrpl_pub_keys: Vec<String> = (0..3).map(|_| generate_xrpl_pub_key()).collect();
+    let xrpl_signers: Vec<XRPLSigner> = (0..3).map(|_| generate_xrpl_signer()).collect();
 
     let mut relayer_accounts = vec![];
     let mut relayers = vec![];
@@ -30,12 +31,14 @@ fn updating_xrpl_base_fee() {
         relayer_accounts.push(accounts[i].to_string());
         relayers.push(Relayer {
             cosmos_address: Addr::unchecked(&accounts[i]),
-            xrpl_address: xrpl_addresses[i as usize].to_string(),
-            xrpl_pub_key: xrpl_pub_keys[i as usize].to_string(),
+            xrpl_address: xrpl_signers[i].xrpl_address.to_string(),
+            xrpl_pub_key: xrpl_signers[i].xrpl_pub_key.to_string(),
+            weight: None,
         });
     }
 
     let xrpl_base_fee = 10;
+    let bridge_xrpl_address = generate_xrpl_address();
 
     let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
 
@@ -48,12 +51,13 @@ fn updating_xrpl_base_fee() {
                 evidence_threshold: 3,
                 used_ticket_sequence_threshold: 9,
                 trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
-                bridge_xrpl_address: generate_xrpl_address(),
+                bridge_xrpl_address: bridge_xrpl_address.clone(),
                 xrpl_base_fee,
                 token_factory_addr: token_factory_addr.clone(),
                 issue_token: true,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -116,6 +120,7 @@ fn updating_xrpl_base_fee() {
             sending_precision: 6,
             max_holding_amount: Uint128::new(100000),
             bridging_fee: Uint128::zero(),
+            token_type: None,
         },
         &[],
     )
@@ -129,6 +134,8 @@ fn updating_xrpl_base_fee() {
             &ExecuteMsg::SendToXRPL {
                 recipient: generate_xrpl_address(),
                 deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(1, FEE_DENOM.to_string()),
         )
@@ -174,7 +181,6 @@ fn updating_xrpl_base_fee() {
     assert_eq!(query_pending_operations.operations.len(), 248);
 
     // Halt the bridge to verify that we can't send signatures of pending operations that are not allowed
-    let correct_signature_example = "3045022100DFA01DA5D6C9877F9DAA59A06032247F3D7ED6444EAD5C90A3AC33CCB7F19B3F02204D8D50E4D085BB1BC9DFB8281B8F35BDAEB7C74AE4B825F8CAE1217CFBDF4EA1".to_string();
     app.execute(
         Addr::unchecked(signer),
         contract_addr.clone(),
@@ -192,7 +198,11 @@ fn updating_xrpl_base_fee() {
                     .ticket_sequence
                     .unwrap(),
                 operation_version: 1,
-                signature: correct_signature_example.clone(),
+                signature: xrpl_signers[0].sign_operation(
+                    &query_pending_operations.operations[0],
+                    &bridge_xrpl_address,
+                    3,
+                ),
             },
             &[],
         )
@@ -214,14 +224,18 @@ fn updating_xrpl_base_fee() {
 
     // Add some signatures to each pending operation
     for pending_operation in query_pending_operations.operations.iter() {
-        for relayer in &relayer_accounts {
+        for (relayer, xrpl_signer) in relayer_accounts.iter().zip(&xrpl_signers) {
             app.execute(
                 Addr::unchecked(relayer),
                 contract_addr.clone(),
                 &ExecuteMsg::SaveSignature {
                     operation_id: pending_operation.ticket_sequence.unwrap(),
                     operation_version: 1,
-                    signature: correct_signature_example.clone(),
+                    signature: xrpl_signer.sign_operation(
+                        pending_operation,
+                        &bridge_xrpl_address,
+                        3,
+                    ),
                 },
                 &[],
             )
@@ -257,7 +271,7 @@ fn updating_xrpl_base_fee() {
 
     // Sign this last operation with the 3 relayers
 
-    for relayer in &relayer_accounts {
+    for (relayer, xrpl_signer) in relayer_accounts.iter().zip(&xrpl_signers) {
         app.execute(
             Addr::unchecked(relayer),
             contract_addr.clone(),
@@ -266,7 +280,11 @@ fn updating_xrpl_base_fee() {
                     .ticket_sequence
                     .unwrap(),
                 operation_version: 1,
-                signature: correct_signature_example.clone(),
+                signature: xrpl_signer.sign_operation(
+                    &query_pending_operations.operations[248],
+                    &bridge_xrpl_address,
+                    3,
+                ),
             },
             &[],
         )
@@ -340,3 +358,217 @@ fn updating_xrpl_base_fee() {
 
     assert_eq!(query_config.xrpl_base_fee, new_xrpl_base_fee);
 }
+
+#[test]
+fn fee_distribution_modes() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+        ("account3", &coins(100_000_000_000, FEE_DENOM)),
+        ("account4", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[4];
+    let receiver = &accounts[3];
+    // The last relayer has weight 2, so it can reach the threshold alone
+    let relayers: Vec<Relayer> = (0..3)
+        .map(|i| Relayer {
+            cosmos_address: Addr::unchecked(&accounts[i]),
+            xrpl_address: generate_xrpl_address(),
+            xrpl_pub_key: generate_xrpl_signer().xrpl_pub_key,
+            weight: Some(if i == 2 { 2 } else { 1 }),
+        })
+        .collect();
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: relayers.clone(),
+                evidence_threshold: 2,
+                used_ticket_sequence_threshold: 4,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: generate_xrpl_address(),
+                xrpl_base_fee: 10,
+                token_factory_addr: token_factory_addr.clone(),
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    let save_evidence = |app: &mut MockApp, relayers: &[Relayer], evidence: Evidence| {
+        for relayer in relayers {
+            app.execute(
+                relayer.cosmos_address.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::SaveEvidence {
+                    evidence: evidence.clone(),
+                },
+                &[],
+            )
+            .unwrap();
+        }
+    };
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RecoverTickets {
+            account_sequence: 1,
+            number_of_tickets: Some(5),
+        },
+        &[],
+    )
+    .unwrap();
+
+    save_evidence(
+        &mut app,
+        &relayers[0..2],
+        Evidence::XRPLTransactionResult {
+            tx_hash: Some(generate_hash()),
+            account_sequence: Some(1),
+            ticket_sequence: None,
+            transaction_result: TransactionResult::Accepted,
+            operation_result: Some(OperationResult::TicketsAllocation {
+                tickets: Some((1..6).collect()),
+            }),
+        },
+    );
+
+    // Register an XRPL token with a bridging fee
+    let issuer = generate_xrpl_address();
+    let currency = "USD".to_string();
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RegisterXRPLToken {
+            issuer: issuer.clone(),
+            currency: currency.clone(),
+            sending_precision: 10,
+            max_holding_amount: Uint128::new(5000000000000000),
+            bridging_fee: Uint128::new(60000),
+        },
+        &coins(10_000_000u128, FEE_DENOM),
+    )
+    .unwrap();
+
+    save_evidence(
+        &mut app,
+        &relayers[0..2],
+        Evidence::XRPLTransactionResult {
+            tx_hash: Some(generate_hash()),
+            account_sequence: None,
+            ticket_sequence: Some(1),
+            transaction_result: TransactionResult::Accepted,
+            operation_result: None,
+        },
+    );
+
+    let query_xrpl_tokens: XRPLTokensResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::XRPLTokens {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let denom = query_xrpl_tokens
+        .tokens
+        .iter()
+        .find(|t| t.issuer == issuer && t.currency == currency)
+        .unwrap()
+        .cosmos_denom
+        .clone();
+
+    let transfer = || Evidence::XRPLToCosmosTransfer {
+        tx_hash: generate_hash(),
+        issuer: issuer.clone(),
+        currency: currency.clone(),
+        amount: Uint128::new(1000000000060000),
+        recipient: Addr::unchecked(receiver),
+        memo: None,
+        source_tag: None,
+        raw_memos: None,
+    };
+
+    let assert_fees_collected = |app: &MockApp, expected: [u128; 3]| {
+        for (relayer, amount) in relayers.iter().zip(expected) {
+            let query_fees_collected: FeesCollectedResponse = app
+                .query(
+                    contract_addr.clone(),
+                    &QueryMsg::FeesCollected {
+                        relayer_address: relayer.cosmos_address.clone(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                query_fees_collected.fees_collected,
+                vec![coin(amount, denom.clone())]
+            );
+        }
+    };
+
+    // By default fees are split equally between all relayers
+    save_evidence(&mut app, &relayers[0..2], transfer());
+    assert_fees_collected(&app, [20000, 20000, 20000]);
+
+    // Only the owner can change the fee distribution
+    let unauthorized_error = app
+        .execute(
+            relayers[0].cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::UpdateFeeDistribution {
+                fee_distribution: FeeDistribution::ByParticipation,
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(unauthorized_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::UnauthorizedSender {}.to_string().as_str()));
+
+    // With participation, only the relayers that provided the evidence get the fee
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::UpdateFeeDistribution {
+            fee_distribution: FeeDistribution::ByParticipation,
+        },
+        &[],
+    )
+    .unwrap();
+
+    save_evidence(&mut app, &relayers[0..2], transfer());
+    assert_fees_collected(&app, [50000, 50000, 20000]);
+
+    save_evidence(&mut app, &relayers[2..3], transfer());
+    assert_fees_collected(&app, [50000, 50000, 80000]);
+
+    // With weights, each relayer gets a part proportional to its weight
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::UpdateFeeDistribution {
+            fee_distribution: FeeDistribution::Weighted,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let query_config: Config = app
+        .query(contract_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(query_config.fee_distribution, FeeDistribution::Weighted);
+
+    save_evidence(&mut app, &relayers[0..2], transfer());
+    assert_fees_collected(&app, [65000, 65000, 110000]);
+}
@@ -1,8 +1,13 @@
-use cosmwasm_std::{coins, Addr, Coin};
+use cosmwasm_std::{
+    coins, Addr, Binary, Coin, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError,
+    StdResult,
+};
 use cosmwasm_testing_util::MockResult;
 use derive_more::{Deref, DerefMut};
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
-use ripple_keypairs::Seed;
+use ripple_keypairs::{PrivateKey, Seed};
+
+use crate::{operation::Operation, xrpl_tx::multisigning_data};
 
 pub const FEE_DENOM: &str = "orai";
 pub const TRUST_SET_LIMIT_AMOUNT: u128 = 1000000000000000000; // 1e18
@@ -39,7 +44,9 @@ impl MockApp {
                     crate::contract::execute,
                     crate::contract::instantiate,
                     crate::contract::query,
-                ),
+                )
+                .with_reply(crate::contract::reply)
+                .with_sudo(crate::contract::sudo),
             ));
             rate_limit_id = app.upload(Box::new(
                 cosmwasm_testing_util::ContractWrapper::new_with_empty(
@@ -89,6 +96,89 @@ impl MockApp {
             "cw-xrpl-bridge",
         )
     }
+
+    /// Mock of the osor entry point contract, that either accepts or fails every universal swap
+    #[cfg(not(feature = "test-tube"))]
+    pub fn create_mock_entry_point(&mut self, sender: Addr, fail: bool) -> MockResult<Addr> {
+        let execute = if fail {
+            failing_entry_point_execute
+        } else {
+            successful_entry_point_execute
+        };
+        let code_id = self.upload(Box::new(
+            cosmwasm_testing_util::ContractWrapper::new_with_empty(
+                execute,
+                empty_instantiate,
+                empty_query,
+            ),
+        ));
+        self.instantiate(code_id, sender, &Empty {}, &[], "mock-entry-point")
+    }
+
+    /// CW20 token with 6 decimals that can be registered in the bridge as a Cosmos originated token
+    #[cfg(not(feature = "test-tube"))]
+    pub fn create_cw20_token(
+        &mut self,
+        sender: Addr,
+        initial_balances: Vec<cw20::Cw20Coin>,
+    ) -> MockResult<Addr> {
+        let code_id = self.upload(Box::new(
+            cosmwasm_testing_util::ContractWrapper::new_with_empty(
+                cw20_base::contract::execute,
+                cw20_base::contract::instantiate,
+                cw20_base::contract::query,
+            ),
+        ));
+        self.instantiate(
+            code_id,
+            sender,
+            &cw20_base::msg::InstantiateMsg {
+                name: "Test Token".to_string(),
+                symbol: "TEST".to_string(),
+                decimals: 6,
+                initial_balances,
+                mint: None,
+                marketing: None,
+            },
+            &[],
+            "cw20-token",
+        )
+    }
+}
+
+#[cfg(not(feature = "test-tube"))]
+fn failing_entry_point_execute(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: skip::entry_point::ExecuteMsg,
+) -> StdResult<Response> {
+    Err(StdError::generic_err("universal swap failed"))
+}
+
+#[cfg(not(feature = "test-tube"))]
+fn successful_entry_point_execute(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: skip::entry_point::ExecuteMsg,
+) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+#[cfg(not(feature = "test-tube"))]
+fn empty_instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+#[cfg(not(feature = "test-tube"))]
+fn empty_query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    Ok(Binary::default())
 }
 
 pub fn generate_hash() -> String {
@@ -127,6 +217,41 @@ pub fn generate_invalid_xrpl_address() -> String {
     address
 }
 
+// XRPL keys of a relayer so that tests can provide valid signatures for operations
+pub struct XRPLSigner {
+    private_key: PrivateKey,
+    pub xrpl_address: String,
+    pub xrpl_pub_key: String,
+}
+
+impl XRPLSigner {
+    pub fn sign_operation(
+        &self,
+        operation: &Operation,
+        bridge_xrpl_address: &str,
+        number_of_signers: u32,
+    ) -> String {
+        let data = multisigning_data(
+            operation,
+            bridge_xrpl_address,
+            number_of_signers,
+            &self.xrpl_address,
+        )
+        .unwrap();
+        self.private_key.sign(&data).to_string()
+    }
+}
+
+pub fn generate_xrpl_signer() -> XRPLSigner {
+    let seed = Seed::random();
+    let (private_key, public_key) = seed.derive_keypair().unwrap();
+    XRPLSigner {
+        private_key,
+        xrpl_address: public_key.derive_address(),
+        xrpl_pub_key: public_key.to_string(),
+    }
+}
+
 pub fn generate_xrpl_pub_key() -> String {
     String::from_utf8(
         thread_rng()
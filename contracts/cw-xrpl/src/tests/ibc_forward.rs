@@ -0,0 +1,429 @@
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{
+    coin, coins, to_json_string, Addr, Binary, CosmosMsg, Order, Reply, ReplyOn, SubMsgResponse,
+    SubMsgResult, Uint128,
+};
+
+use crate::contract::{reply, sudo, XRP_CURRENCY, XRP_ISSUER, XRP_SYMBOL};
+use crate::error::ContractError;
+use crate::evidence::Evidence;
+use crate::ibc_forward::{
+    create_ibc_forward_submsg, park_untracked_ibc_forward, IBC_TRANSFER_TYPE_URL,
+};
+use crate::memo::{BridgeMemo, MemoAction, MEMO_VERSION};
+use crate::msg::{
+    ExecuteMsg, IBCLifecycleComplete, InstantiateMsg, PendingRefundsResponse, QueryMsg, SudoMsg,
+};
+use crate::relayer::Relayer;
+use crate::state::{
+    IBCForward, IBC_FORWARDS_IN_FLIGHT, PENDING_IBC_FORWARDS, PENDING_REFUNDS,
+    UNTRACKED_IBC_FORWARDS,
+};
+use crate::tests::helper::{
+    generate_hash, generate_xrpl_address, generate_xrpl_pub_key, MockApp, FEE_DENOM,
+    TRUST_SET_LIMIT_AMOUNT,
+};
+use crate::token::full_denom;
+
+#[test]
+#[cfg(not(feature = "test-tube"))]
+fn failed_ibc_forward_is_parked_as_pending_refund() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[0];
+    let relayer_account = &accounts[1];
+    let receiver = &accounts[2];
+
+    let relayer = Relayer {
+        cosmos_address: Addr::unchecked(relayer_account),
+        xrpl_address: generate_xrpl_address(),
+        xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
+    };
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: vec![relayer],
+                evidence_threshold: 1,
+                used_ticket_sequence_threshold: 50,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: generate_xrpl_address(),
+                xrpl_base_fee: 10,
+                token_factory_addr: token_factory_addr.clone(),
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    // The mock app can't send IBC transfers, so the forward fails and the funds must be parked for the recipient
+    let tx_hash = generate_hash();
+    let amount = Uint128::new(1_000_000);
+    let forward_memo = to_json_string(&BridgeMemo {
+        version: MEMO_VERSION,
+        action: MemoAction::Forward {
+            channel: "channel-0".to_string(),
+            receiver: "osmo1receiver".to_string(),
+            timeout_seconds: None,
+        },
+    })
+    .unwrap();
+
+    let result = app
+        .execute(
+            Addr::unchecked(relayer_account),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveEvidence {
+                evidence: Evidence::XRPLToCosmosTransfer {
+                    tx_hash: tx_hash.clone(),
+                    issuer: XRP_ISSUER.to_string(),
+                    currency: XRP_CURRENCY.to_string(),
+                    amount,
+                    recipient: Addr::unchecked(receiver),
+                    memo: Some(forward_memo),
+                    source_tag: None,
+                    raw_memos: None,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+    assert!(result
+        .events
+        .iter()
+        .any(|e| e.ty == "wasm-refund_ibc_forward"));
+
+    let xrp_denom = full_denom(&token_factory_addr, XRP_SYMBOL);
+    let contract_balance = app
+        .query_balance(contract_addr.clone(), xrp_denom.clone())
+        .unwrap();
+    assert_eq!(contract_balance.to_string(), amount.to_string());
+
+    let query_pending_refunds: PendingRefundsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingRefunds {
+                address: Addr::unchecked(receiver),
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(query_pending_refunds.pending_refunds.len(), 1);
+    assert_eq!(
+        query_pending_refunds.pending_refunds[0].xrpl_tx_hash,
+        Some(tx_hash)
+    );
+    assert_eq!(
+        query_pending_refunds.pending_refunds[0].coin,
+        coin(amount.u128(), xrp_denom.clone())
+    );
+
+    app.execute(
+        Addr::unchecked(receiver),
+        contract_addr,
+        &ExecuteMsg::ClaimRefund {
+            pending_refund_id: query_pending_refunds.pending_refunds[0].id.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let receiver_balance = app
+        .query_balance(Addr::unchecked(receiver), xrp_denom)
+        .unwrap();
+    assert_eq!(receiver_balance.to_string(), amount.to_string());
+}
+
+#[test]
+fn ibc_forward_lifecycle() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    let forward = IBCForward {
+        channel: "channel-0".to_string(),
+        receiver: "osmo1receiver".to_string(),
+        recipient: Addr::unchecked("recipient"),
+        coin: coin(1_000_000, "denom"),
+        tx_hash: generate_hash(),
+    };
+
+    // The reply of the transfer gives us the packet sequence (MsgTransferResponse { sequence: 300 })
+    PENDING_IBC_FORWARDS
+        .save(deps.as_mut().storage, 1, &forward)
+        .unwrap();
+    reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: 1,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(Binary::from(vec![0x08, 0xac, 0x02])),
+            }),
+        },
+    )
+    .unwrap();
+
+    assert!(!PENDING_IBC_FORWARDS.has(deps.as_ref().storage, 1));
+    assert_eq!(
+        IBC_FORWARDS_IN_FLIGHT
+            .load(deps.as_ref().storage, ("channel-0".to_string(), 300))
+            .unwrap(),
+        forward
+    );
+
+    // A successful acknowledgement removes the forward without refunds
+    sudo(
+        deps.as_mut(),
+        env.clone(),
+        SudoMsg::IBCLifecycleComplete(IBCLifecycleComplete::IBCAck {
+            channel: "channel-0".to_string(),
+            sequence: 300,
+            ack: "AQ==".to_string(),
+            success: true,
+        }),
+    )
+    .unwrap();
+
+    assert!(!IBC_FORWARDS_IN_FLIGHT.has(deps.as_ref().storage, ("channel-0".to_string(), 300)));
+    assert_eq!(
+        PENDING_REFUNDS
+            .idx
+            .address
+            .prefix(forward.recipient.clone())
+            .range(deps.as_ref().storage, None, None, Order::Ascending)
+            .count(),
+        0
+    );
+
+    // Failed acknowledgements and timeouts park the funds as pending refunds
+    IBC_FORWARDS_IN_FLIGHT
+        .save(
+            deps.as_mut().storage,
+            ("channel-0".to_string(), 301),
+            &forward,
+        )
+        .unwrap();
+    IBC_FORWARDS_IN_FLIGHT
+        .save(
+            deps.as_mut().storage,
+            ("channel-0".to_string(), 302),
+            &forward,
+        )
+        .unwrap();
+
+    sudo(
+        deps.as_mut(),
+        env.clone(),
+        SudoMsg::IBCLifecycleComplete(IBCLifecycleComplete::IBCAck {
+            channel: "channel-0".to_string(),
+            sequence: 301,
+            ack: "error".to_string(),
+            success: false,
+        }),
+    )
+    .unwrap();
+
+    sudo(
+        deps.as_mut(),
+        env.clone(),
+        SudoMsg::IBCLifecycleComplete(IBCLifecycleComplete::IBCTimeout {
+            channel: "channel-0".to_string(),
+            sequence: 302,
+        }),
+    )
+    .unwrap();
+
+    let pending_refunds: Vec<_> = PENDING_REFUNDS
+        .idx
+        .address
+        .prefix(forward.recipient.clone())
+        .range(deps.as_ref().storage, None, None, Order::Ascending)
+        .map(|item| item.unwrap().1)
+        .collect();
+    assert_eq!(pending_refunds.len(), 2);
+    for pending_refund in pending_refunds {
+        assert_eq!(pending_refund.coin, forward.coin);
+        assert_eq!(pending_refund.xrpl_tx_hash, Some(forward.tx_hash.clone()));
+    }
+
+    // A packet that we are not tracking can't be processed
+    let error = sudo(
+        deps.as_mut(),
+        env,
+        SudoMsg::IBCLifecycleComplete(IBCLifecycleComplete::IBCTimeout {
+            channel: "channel-0".to_string(),
+            sequence: 302,
+        }),
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        ContractError::UnknownIBCForward {
+            channel: "channel-0".to_string(),
+            sequence: 302,
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn ibc_forward_registers_callback() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    let forward = IBCForward {
+        channel: "channel-0".to_string(),
+        receiver: "osmo1receiver".to_string(),
+        recipient: Addr::unchecked("recipient"),
+        coin: coin(1_000_000, "denom"),
+        tx_hash: generate_hash(),
+    };
+
+    let sub_msg = create_ibc_forward_submsg(
+        deps.as_mut().storage,
+        &env.contract.address,
+        env.block.time.plus_seconds(600),
+        forward.clone(),
+    )
+    .unwrap();
+
+    assert_eq!(sub_msg.reply_on, ReplyOn::Always);
+    assert_eq!(
+        PENDING_IBC_FORWARDS
+            .load(deps.as_ref().storage, sub_msg.id)
+            .unwrap(),
+        forward
+    );
+
+    // The transfer must carry the ibc_callback memo so that we get the acknowledgement or timeout through sudo
+    let CosmosMsg::Stargate { type_url, value } = sub_msg.msg else {
+        panic!("IBC forwards must be sent as a MsgTransfer");
+    };
+    assert_eq!(type_url, IBC_TRANSFER_TYPE_URL);
+
+    let contains = |expected: &str| {
+        value
+            .as_slice()
+            .windows(expected.len())
+            .any(|window| window == expected.as_bytes())
+    };
+    assert!(contains(&format!(
+        r#"{{"ibc_callback":"{}"}}"#,
+        env.contract.address
+    )));
+    assert!(contains(&forward.channel));
+    assert!(contains(&forward.receiver));
+    assert!(contains(&forward.coin.denom));
+    assert!(contains(&forward.coin.amount.to_string()));
+}
+
+#[test]
+fn untracked_ibc_forward_can_be_refunded() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    let forward = IBCForward {
+        channel: "channel-0".to_string(),
+        receiver: "osmo1receiver".to_string(),
+        recipient: Addr::unchecked("recipient"),
+        coin: coin(1_000_000, "denom"),
+        tx_hash: generate_hash(),
+    };
+
+    // If we can't read the packet sequence the transfer was still sent, so the evidence must not be reverted
+    PENDING_IBC_FORWARDS
+        .save(deps.as_mut().storage, 1, &forward)
+        .unwrap();
+    let response = reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: 1,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+
+    assert!(response
+        .events
+        .iter()
+        .any(|e| e.ty == "untracked_ibc_forward"));
+    assert!(!PENDING_IBC_FORWARDS.has(deps.as_ref().storage, 1));
+    assert_eq!(
+        IBC_FORWARDS_IN_FLIGHT
+            .range(deps.as_ref().storage, None, None, Order::Ascending)
+            .count(),
+        0
+    );
+    assert_eq!(
+        PENDING_REFUNDS
+            .idx
+            .address
+            .prefix(forward.recipient.clone())
+            .range(deps.as_ref().storage, None, None, Order::Ascending)
+            .count(),
+        0
+    );
+
+    // The forward is kept so that it can be refunded if the packet is lost
+    assert_eq!(
+        UNTRACKED_IBC_FORWARDS
+            .load(deps.as_ref().storage, forward.tx_hash.clone())
+            .unwrap(),
+        forward
+    );
+
+    park_untracked_ibc_forward(
+        deps.as_mut().storage,
+        env.block.time.seconds(),
+        forward.tx_hash.clone(),
+    )
+    .unwrap();
+
+    assert!(!UNTRACKED_IBC_FORWARDS.has(deps.as_ref().storage, forward.tx_hash.clone()));
+    let pending_refunds: Vec<_> = PENDING_REFUNDS
+        .idx
+        .address
+        .prefix(forward.recipient.clone())
+        .range(deps.as_ref().storage, None, None, Order::Ascending)
+        .map(|item| item.unwrap().1)
+        .collect();
+    assert_eq!(pending_refunds.len(), 1);
+    assert_eq!(pending_refunds[0].coin, forward.coin);
+    assert_eq!(
+        pending_refunds[0].xrpl_tx_hash,
+        Some(forward.tx_hash.clone())
+    );
+
+    // It can only be refunded once
+    let error = park_untracked_ibc_forward(
+        deps.as_mut().storage,
+        env.block.time.seconds(),
+        forward.tx_hash,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        ContractError::UntrackedIBCForwardNotFound {}.to_string()
+    );
+}
@@ -25,36 +25,42 @@ fn contract_instantiation() {
         cosmos_address: Addr::unchecked(signer),
         xrpl_address: xrpl_address.clone(),
         xrpl_pub_key: xrpl_pub_key.clone(),
+        weight: None,
     };
 
     let relayer_duplicated_xrpl_address = Relayer {
         cosmos_address: Addr::unchecked(signer),
         xrpl_address,
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let relayer_duplicated_xrpl_pub_key = Relayer {
         cosmos_address: Addr::unchecked(signer),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key,
+        weight: None,
     };
 
     let relayer_duplicated_cosmos_address = Relayer {
         cosmos_address: Addr::unchecked(signer),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let relayer_prohibited_xrpl_address = Relayer {
         cosmos_address: Addr::unchecked(relayer_addr),
         xrpl_address: "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh".to_string(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let relayer_correct = Relayer {
         cosmos_address: Addr::unchecked(relayer_addr),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
@@ -74,6 +80,7 @@ fn contract_instantiation() {
             issue_token: true,
             rate_limit_addr: None,
             osor_entry_point: None,
+            xrpl_network: None,
         },
     )
     .unwrap();
@@ -94,6 +101,7 @@ fn contract_instantiation() {
                 issue_token: false,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap_err();
@@ -119,6 +127,7 @@ fn contract_instantiation() {
                 issue_token: false,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap_err();
@@ -144,6 +153,7 @@ fn contract_instantiation() {
                 issue_token: false,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap_err();
@@ -168,6 +178,7 @@ fn contract_instantiation() {
                 issue_token: false,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap_err();
@@ -194,6 +205,7 @@ fn contract_instantiation() {
                 issue_token: false,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap_err();
@@ -222,6 +234,7 @@ fn contract_instantiation() {
                 issue_token: false,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap_err();
@@ -247,6 +260,7 @@ fn contract_instantiation() {
                 issue_token: false,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap_err();
@@ -263,6 +277,7 @@ fn contract_instantiation() {
             cosmos_address: Addr::unchecked(format!("cosmos_address_{}", i)),
             xrpl_address: generate_xrpl_address(),
             xrpl_pub_key: generate_xrpl_pub_key(),
+            weight: None,
         });
     }
 
@@ -281,6 +296,7 @@ fn contract_instantiation() {
                 issue_token: false,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap_err();
@@ -306,6 +322,7 @@ fn contract_instantiation() {
                 issue_token: false,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap_err();
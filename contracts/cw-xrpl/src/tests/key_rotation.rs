@@ -1,13 +1,17 @@
 use crate::contract::XRP_ISSUER;
 use crate::error::ContractError;
 use crate::evidence::{Evidence, OperationResult, TransactionResult};
-use crate::msg::PendingOperationsResponse;
+use crate::msg::{
+    AvailableTicketsResponse, BridgeStateResponse, PendingOperationsResponse, PendingRelayerSet,
+    RelayerSetChangeResponse, SignedTransactionResponse, SigningPayloadResponse,
+};
 use crate::operation::{Operation, OperationType};
 use crate::state::{BridgeState, Config};
 use crate::tests::helper::{
-    generate_hash, generate_xrpl_address, generate_xrpl_pub_key, MockApp, FEE_DENOM,
-    TRUST_SET_LIMIT_AMOUNT,
+    generate_hash, generate_xrpl_address, generate_xrpl_pub_key, generate_xrpl_signer, MockApp,
+    XRPLSigner, FEE_DENOM, TRUST_SET_LIMIT_AMOUNT,
 };
+use crate::xrpl_tx::{decode_account_id, to_hex};
 use crate::{
     contract::XRP_CURRENCY,
     msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
@@ -39,6 +43,7 @@ fn key_rotation() {
             cosmos_address: Addr::unchecked(&accounts[i]),
             xrpl_address: xrpl_addresses[i as usize].to_string(),
             xrpl_pub_key: xrpl_pub_keys[i as usize].to_string(),
+            weight: None,
         });
     }
 
@@ -66,6 +71,7 @@ fn key_rotation() {
                 issue_token: true,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -116,6 +122,8 @@ fn key_rotation() {
                 amount: Uint128::one(),
                 recipient: Addr::unchecked(signer),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -135,6 +143,8 @@ fn key_rotation() {
                     amount: Uint128::one(),
                     recipient: Addr::unchecked(signer),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -193,6 +203,7 @@ fn key_rotation() {
         query_pending_operations.operations[0],
         Operation {
             id: query_pending_operations.operations[0].id.clone(),
+            created_at: query_pending_operations.operations[0].created_at,
             version: 1,
             ticket_sequence: Some(1),
             account_sequence: None,
@@ -218,6 +229,8 @@ fn key_rotation() {
                     amount: Uint128::one(),
                     recipient: Addr::unchecked(signer),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -299,6 +312,7 @@ fn key_rotation() {
         query_pending_operations.operations[0],
         Operation {
             id: query_pending_operations.operations[0].id.clone(),
+            created_at: query_pending_operations.operations[0].created_at,
             version: 1,
             ticket_sequence: Some(2),
             account_sequence: None,
@@ -364,6 +378,8 @@ fn key_rotation() {
                 amount: Uint128::one(),
                 recipient: Addr::unchecked(signer),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -383,6 +399,8 @@ fn key_rotation() {
                     amount: Uint128::one(),
                     recipient: Addr::unchecked(signer),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -394,3 +412,842 @@ fn key_rotation() {
         .to_string()
         .contains(ContractError::UnauthorizedSender {}.to_string().as_str()));
 }
+
+#[test]
+fn weighted_relayers() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+        ("account3", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[3];
+    let xrpl_signers: Vec<XRPLSigner> = (0..3).map(|_| generate_xrpl_signer()).collect();
+    let weights = [2, 1, 1];
+
+    let relayers: Vec<Relayer> = (0..3)
+        .map(|i| Relayer {
+            cosmos_address: Addr::unchecked(&accounts[i]),
+            xrpl_address: xrpl_signers[i].xrpl_address.clone(),
+            xrpl_pub_key: xrpl_signers[i].xrpl_pub_key.clone(),
+            weight: Some(weights[i]),
+        })
+        .collect();
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+    let bridge_xrpl_address = generate_xrpl_address();
+    let instantiate_msg = InstantiateMsg {
+        owner: Addr::unchecked(signer),
+        relayers: relayers.clone(),
+        evidence_threshold: 3,
+        used_ticket_sequence_threshold: 4,
+        trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+        bridge_xrpl_address: bridge_xrpl_address.clone(),
+        xrpl_base_fee: 10,
+        token_factory_addr: token_factory_addr.clone(),
+        issue_token: true,
+        rate_limit_addr: None,
+        osor_entry_point: None,
+        xrpl_network: None,
+    };
+
+    // Relayers can't have a weight of 0
+    let mut invalid_relayers = relayers.clone();
+    invalid_relayers[1].weight = Some(0);
+    let instantiate_error = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                relayers: invalid_relayers,
+                ..instantiate_msg.clone()
+            },
+        )
+        .unwrap_err();
+
+    assert!(instantiate_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::InvalidRelayerWeight {}.to_string().as_str()));
+
+    // The threshold must be reachable with the weights of all relayers (2 + 1 + 1)
+    let instantiate_error = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                evidence_threshold: 5,
+                ..instantiate_msg.clone()
+            },
+        )
+        .unwrap_err();
+
+    assert!(instantiate_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::InvalidThreshold {}.to_string().as_str()));
+
+    let contract_addr = app
+        .create_bridge(Addr::unchecked(signer), &instantiate_msg)
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RecoverTickets {
+            account_sequence: 1,
+            number_of_tickets: Some(5),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let operation = &query_pending_operations.operations[0];
+
+    // The signature of the relayer with weight 2 is not enough to reach the threshold, the one of another relayer is.
+    // Fees are calculated for the worst case, which is the 3 relayers signing
+    for i in 0..2 {
+        let query_signed_transaction: SignedTransactionResponse = app
+            .query(
+                contract_addr.clone(),
+                &QueryMsg::SignedTransaction { operation_id: 1 },
+            )
+            .unwrap();
+        assert!(query_signed_transaction.tx_blob.is_none());
+
+        app.execute(
+            Addr::unchecked(&accounts[i]),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveSignature {
+                operation_id: 1,
+                operation_version: 1,
+                signature: xrpl_signers[i].sign_operation(operation, &bridge_xrpl_address, 3),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    let query_signed_transaction: SignedTransactionResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::SignedTransaction { operation_id: 1 },
+        )
+        .unwrap();
+    assert!(query_signed_transaction.tx_blob.is_some());
+
+    // Same for evidences
+    let tx_hash = generate_hash();
+    for i in 0..2 {
+        let query_available_tickets: AvailableTicketsResponse = app
+            .query(contract_addr.clone(), &QueryMsg::AvailableTickets {})
+            .unwrap();
+        assert!(query_available_tickets.tickets.is_empty());
+
+        app.execute(
+            Addr::unchecked(&accounts[i]),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveEvidence {
+                evidence: Evidence::XRPLTransactionResult {
+                    tx_hash: Some(tx_hash.clone()),
+                    account_sequence: Some(1),
+                    ticket_sequence: None,
+                    transaction_result: TransactionResult::Accepted,
+                    operation_result: Some(OperationResult::TicketsAllocation {
+                        tickets: Some((1..6).collect()),
+                    }),
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    let query_available_tickets: AvailableTicketsResponse = app
+        .query(contract_addr.clone(), &QueryMsg::AvailableTickets {})
+        .unwrap();
+    assert_eq!(query_available_tickets.tickets, vec![1, 2, 3, 4, 5]);
+
+    // The signer list set in XRPL must have the weights of the new relayers
+    let new_relayers = vec![
+        Relayer {
+            weight: None,
+            ..relayers[0].clone()
+        },
+        Relayer {
+            weight: Some(3),
+            ..relayers[2].clone()
+        },
+    ];
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RotateKeys {
+            new_relayers: new_relayers.clone(),
+            new_evidence_threshold: 4,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        query_pending_operations.operations[0].operation_type,
+        OperationType::RotateKeys {
+            new_relayers: new_relayers.clone(),
+            new_evidence_threshold: 4,
+        }
+    );
+
+    let query_signing_payload: SigningPayloadResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::SigningPayload {
+                operation_id: query_pending_operations.operations[0]
+                    .ticket_sequence
+                    .unwrap(),
+            },
+        )
+        .unwrap();
+    for (relayer, weight) in new_relayers.iter().zip(["0001", "0003"]) {
+        assert!(query_signing_payload.transaction.contains(&format!(
+            "EB13{}8114{}E1",
+            weight,
+            to_hex(&decode_account_id(&relayer.xrpl_address).unwrap())
+        )));
+    }
+}
+
+#[test]
+fn incremental_relayer_set_changes() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+        ("account3", &coins(100_000_000_000, FEE_DENOM)),
+        ("account4", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[4];
+    let xrpl_signers: Vec<XRPLSigner> = (0..4).map(|_| generate_xrpl_signer()).collect();
+    let relayers: Vec<Relayer> = (0..4)
+        .map(|i| Relayer {
+            cosmos_address: Addr::unchecked(&accounts[i]),
+            xrpl_address: xrpl_signers[i].xrpl_address.clone(),
+            xrpl_pub_key: xrpl_signers[i].xrpl_pub_key.clone(),
+            weight: None,
+        })
+        .collect();
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+    let bridge_xrpl_address = generate_xrpl_address();
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: relayers[0..3].to_vec(),
+                evidence_threshold: 2,
+                used_ticket_sequence_threshold: 4,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: bridge_xrpl_address.clone(),
+                xrpl_base_fee: 10,
+                token_factory_addr: token_factory_addr.clone(),
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RecoverTickets {
+            account_sequence: 1,
+            number_of_tickets: Some(5),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let tx_hash = generate_hash();
+    for relayer in &relayers[0..2] {
+        app.execute(
+            relayer.cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveEvidence {
+                evidence: Evidence::XRPLTransactionResult {
+                    tx_hash: Some(tx_hash.clone()),
+                    account_sequence: Some(1),
+                    ticket_sequence: None,
+                    transaction_result: TransactionResult::Accepted,
+                    operation_result: Some(OperationResult::TicketsAllocation {
+                        tickets: Some((1..6).collect()),
+                    }),
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    // Removing a relayer that is not in the set fails
+    let remove_error = app
+        .execute(
+            Addr::unchecked(signer),
+            contract_addr.clone(),
+            &ExecuteMsg::RemoveRelayer {
+                cosmos_address: relayers[3].cosmos_address.clone(),
+                new_evidence_threshold: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(remove_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::RelayerNotFound {}.to_string().as_str()));
+
+    // Only the owner can change the relayer set
+    app.execute(
+        relayers[0].cosmos_address.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::AddRelayer {
+            relayer: relayers[3].clone(),
+            new_evidence_threshold: Some(3),
+        },
+        &[],
+    )
+    .unwrap_err();
+
+    // Add a relayer and increase the threshold, the bridge stays active
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::AddRelayer {
+            relayer: relayers[3].clone(),
+            new_evidence_threshold: Some(3),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let query_bridge_state: BridgeStateResponse = app
+        .query(contract_addr.clone(), &QueryMsg::BridgeState {})
+        .unwrap();
+    assert_eq!(query_bridge_state.state, BridgeState::Active);
+
+    let query_relayer_set_change: RelayerSetChangeResponse = app
+        .query(contract_addr.clone(), &QueryMsg::RelayerSetChange {})
+        .unwrap();
+    assert_eq!(
+        query_relayer_set_change,
+        RelayerSetChangeResponse {
+            relayers: relayers[0..3].to_vec(),
+            evidence_threshold: 2,
+            pending: Some(PendingRelayerSet {
+                operation_id: 1,
+                halts_bridge: false,
+                new_relayers: relayers.clone(),
+                new_evidence_threshold: 3,
+                added: vec![relayers[3].clone()],
+                removed: vec![],
+                updated: vec![],
+            }),
+        }
+    );
+
+    // Only one change can be pending at a time
+    let pending_rotation_error = app
+        .execute(
+            Addr::unchecked(signer),
+            contract_addr.clone(),
+            &ExecuteMsg::RemoveRelayer {
+                cosmos_address: relayers[2].cosmos_address.clone(),
+                new_evidence_threshold: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(pending_rotation_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::RotateKeysOngoing {}.to_string().as_str()));
+
+    let confirm_rotation = |app: &mut MockApp, relayers: &[Relayer], ticket_sequence: u64| {
+        let tx_hash = generate_hash();
+        for relayer in relayers {
+            app.execute(
+                relayer.cosmos_address.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::SaveEvidence {
+                    evidence: Evidence::XRPLTransactionResult {
+                        tx_hash: Some(tx_hash.clone()),
+                        account_sequence: None,
+                        ticket_sequence: Some(ticket_sequence),
+                        transaction_result: TransactionResult::Accepted,
+                        operation_result: None,
+                    },
+                },
+                &[],
+            )
+            .unwrap();
+        }
+    };
+
+    confirm_rotation(&mut app, &relayers[0..2], 1);
+
+    let query_config: Config = app
+        .query(contract_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(query_config.relayers, relayers);
+    assert_eq!(query_config.evidence_threshold, 3);
+    assert_eq!(query_config.bridge_state, BridgeState::Active);
+
+    // Remove a relayer keeping the threshold
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RemoveRelayer {
+            cosmos_address: relayers[2].cosmos_address.clone(),
+            new_evidence_threshold: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let query_relayer_set_change: RelayerSetChangeResponse = app
+        .query(contract_addr.clone(), &QueryMsg::RelayerSetChange {})
+        .unwrap();
+    let pending = query_relayer_set_change.pending.unwrap();
+    assert_eq!(pending.new_evidence_threshold, 3);
+    assert_eq!(pending.removed, vec![relayers[2].clone()]);
+    assert!(pending.added.is_empty());
+
+    // Other operations can still be created while the change is pending
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RegisterXRPLToken {
+            issuer: generate_xrpl_address(),
+            currency: "USD".to_string(),
+            sending_precision: 4,
+            max_holding_amount: Uint128::new(50000),
+            bridging_fee: Uint128::zero(),
+        },
+        &coins(10_000_000u128, FEE_DENOM),
+    )
+    .unwrap();
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(query_pending_operations.operations.len(), 2);
+    let trust_set_operation = &query_pending_operations.operations[1];
+    let trust_set_operation_id = trust_set_operation.ticket_sequence.unwrap();
+
+    // The relayer that is being removed can only sign the change, the others can sign everything
+    let signature_error = app
+        .execute(
+            relayers[2].cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveSignature {
+                operation_id: trust_set_operation_id,
+                operation_version: 1,
+                signature: xrpl_signers[2].sign_operation(
+                    trust_set_operation,
+                    &bridge_xrpl_address,
+                    3,
+                ),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(signature_error.root_cause().to_string().contains(
+        ContractError::RelayerKeyChangePending {}
+            .to_string()
+            .as_str()
+    ));
+
+    app.execute(
+        relayers[0].cosmos_address.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveSignature {
+            operation_id: trust_set_operation_id,
+            operation_version: 1,
+            signature: xrpl_signers[0].sign_operation(trust_set_operation, &bridge_xrpl_address, 3),
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute(
+        relayers[2].cosmos_address.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveSignature {
+            operation_id: 2,
+            operation_version: 1,
+            signature: xrpl_signers[2].sign_operation(
+                &query_pending_operations.operations[0],
+                &bridge_xrpl_address,
+                3,
+            ),
+        },
+        &[],
+    )
+    .unwrap();
+
+    confirm_rotation(&mut app, &relayers[0..3], 2);
+
+    let query_config: Config = app
+        .query(contract_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(
+        query_config.relayers,
+        vec![
+            relayers[0].clone(),
+            relayers[1].clone(),
+            relayers[3].clone()
+        ]
+    );
+
+    // The trust set must be signed again but the signature of the relayer that stayed is kept
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(query_pending_operations.operations.len(), 1);
+    assert_eq!(query_pending_operations.operations[0].version, 2);
+    assert_eq!(query_pending_operations.operations[0].signatures.len(), 1);
+    assert_eq!(
+        query_pending_operations.operations[0].signatures[0].relayer_cosmos_address,
+        relayers[0].cosmos_address
+    );
+
+    // Replace the key of a relayer
+    let new_xrpl_signer = generate_xrpl_signer();
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::ReplaceRelayerKey {
+            cosmos_address: relayers[1].cosmos_address.clone(),
+            xrpl_address: new_xrpl_signer.xrpl_address.clone(),
+            xrpl_pub_key: new_xrpl_signer.xrpl_pub_key.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let query_relayer_set_change: RelayerSetChangeResponse = app
+        .query(contract_addr.clone(), &QueryMsg::RelayerSetChange {})
+        .unwrap();
+    let pending = query_relayer_set_change.pending.unwrap();
+    assert!(pending.added.is_empty());
+    assert!(pending.removed.is_empty());
+    assert_eq!(
+        pending.updated,
+        vec![Relayer {
+            xrpl_address: new_xrpl_signer.xrpl_address.clone(),
+            xrpl_pub_key: new_xrpl_signer.xrpl_pub_key.clone(),
+            ..relayers[1].clone()
+        }]
+    );
+
+    // The relayer with the key being replaced can't sign other operations with the old key
+    let signature_error = app
+        .execute(
+            relayers[1].cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveSignature {
+                operation_id: trust_set_operation_id,
+                operation_version: 2,
+                signature: xrpl_signers[1].sign_operation(
+                    trust_set_operation,
+                    &bridge_xrpl_address,
+                    3,
+                ),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(signature_error.root_cause().to_string().contains(
+        ContractError::RelayerKeyChangePending {}
+            .to_string()
+            .as_str()
+    ));
+}
+
+#[test]
+fn pending_operations_after_relayer_set_change() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+        ("account3", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[3];
+    let xrpl_signers: Vec<XRPLSigner> = (0..3).map(|_| generate_xrpl_signer()).collect();
+    let relayers: Vec<Relayer> = (0..3)
+        .map(|i| Relayer {
+            cosmos_address: Addr::unchecked(&accounts[i]),
+            xrpl_address: xrpl_signers[i].xrpl_address.clone(),
+            xrpl_pub_key: xrpl_signers[i].xrpl_pub_key.clone(),
+            weight: None,
+        })
+        .collect();
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+    let bridge_xrpl_address = generate_xrpl_address();
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: relayers.clone(),
+                evidence_threshold: 2,
+                used_ticket_sequence_threshold: 4,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: bridge_xrpl_address.clone(),
+                xrpl_base_fee: 10,
+                token_factory_addr: token_factory_addr.clone(),
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RecoverTickets {
+            account_sequence: 1,
+            number_of_tickets: Some(5),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let tx_hash = generate_hash();
+    for relayer in &relayers[0..2] {
+        app.execute(
+            relayer.cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveEvidence {
+                evidence: Evidence::XRPLTransactionResult {
+                    tx_hash: Some(tx_hash.clone()),
+                    account_sequence: Some(1),
+                    ticket_sequence: None,
+                    transaction_result: TransactionResult::Accepted,
+                    operation_result: Some(OperationResult::TicketsAllocation {
+                        tickets: Some((1..6).collect()),
+                    }),
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    // Create a trust set operation and have all relayers sign it
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RegisterXRPLToken {
+            issuer: generate_xrpl_address(),
+            currency: "USD".to_string(),
+            sending_precision: 4,
+            max_holding_amount: Uint128::new(50000),
+            bridging_fee: Uint128::zero(),
+        },
+        &coins(10_000_000u128, FEE_DENOM),
+    )
+    .unwrap();
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let trust_set_operation = query_pending_operations.operations[0].clone();
+    let trust_set_operation_id = trust_set_operation.ticket_sequence.unwrap();
+
+    for (relayer, xrpl_signer) in relayers.iter().zip(xrpl_signers.iter()) {
+        app.execute(
+            relayer.cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveSignature {
+                operation_id: trust_set_operation_id,
+                operation_version: 1,
+                signature: xrpl_signer.sign_operation(
+                    &trust_set_operation,
+                    &bridge_xrpl_address,
+                    2,
+                ),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    // Remove a relayer keeping the threshold, so the transaction fee doesn't change
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RemoveRelayer {
+            cosmos_address: relayers[2].cosmos_address.clone(),
+            new_evidence_threshold: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let confirm_rotation = |app: &mut MockApp, ticket_sequence: u64| {
+        let tx_hash = generate_hash();
+        let mut result = None;
+        for relayer in &relayers[0..2] {
+            result = Some(
+                app.execute(
+                    relayer.cosmos_address.clone(),
+                    contract_addr.clone(),
+                    &ExecuteMsg::SaveEvidence {
+                        evidence: Evidence::XRPLTransactionResult {
+                            tx_hash: Some(tx_hash.clone()),
+                            account_sequence: None,
+                            ticket_sequence: Some(ticket_sequence),
+                            transaction_result: TransactionResult::Accepted,
+                            operation_result: None,
+                        },
+                    },
+                    &[],
+                )
+                .unwrap(),
+            );
+        }
+        result.unwrap()
+    };
+
+    let result = confirm_rotation(&mut app, 2);
+
+    assert!(result
+        .events
+        .iter()
+        .any(|e| e.ty == "wasm-relayer_set_changed"
+            && e.attributes.iter().any(|a| a.key == "updated_operation_ids"
+                && a.value == trust_set_operation_id.to_string())));
+
+    // The signature of the removed relayer is dropped and the version increased
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(query_pending_operations.operations.len(), 1);
+    let trust_set_operation = &query_pending_operations.operations[0];
+    assert_eq!(trust_set_operation.version, 2);
+    assert_eq!(
+        trust_set_operation
+            .signatures
+            .iter()
+            .map(|signature| signature.relayer_cosmos_address.clone())
+            .collect::<Vec<Addr>>(),
+        vec![
+            relayers[0].cosmos_address.clone(),
+            relayers[1].cosmos_address.clone()
+        ]
+    );
+
+    // Signatures for the previous version are rejected
+    let version_error = app
+        .execute(
+            relayers[0].cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveSignature {
+                operation_id: trust_set_operation_id,
+                operation_version: 1,
+                signature: xrpl_signers[0].sign_operation(
+                    trust_set_operation,
+                    &bridge_xrpl_address,
+                    2,
+                ),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(version_error.root_cause().to_string().contains(
+        ContractError::OperationVersionMismatch {}
+            .to_string()
+            .as_str()
+    ));
+
+    // Increasing the threshold changes the fee of the transaction so all signatures are dropped
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::AddRelayer {
+            relayer: relayers[2].clone(),
+            new_evidence_threshold: Some(3),
+        },
+        &[],
+    )
+    .unwrap();
+
+    confirm_rotation(&mut app, 3);
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(query_pending_operations.operations.len(), 1);
+    assert_eq!(query_pending_operations.operations[0].version, 3);
+    assert!(query_pending_operations.operations[0].signatures.is_empty());
+}
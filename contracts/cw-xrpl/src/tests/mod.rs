@@ -1,6 +1,8 @@
 mod bridge;
+mod cw20;
 mod fee;
 mod helper;
+mod ibc_forward;
 mod instantiation;
 mod key_rotation;
 mod operation;
@@ -8,8 +10,11 @@ mod ownership;
 mod precision;
 mod queries;
 mod rate_limit;
+mod refund;
 mod register;
 mod send;
 mod ticket;
 mod token;
+mod universal_swap;
 mod validate;
+mod xrpl_tx;
@@ -1,13 +1,15 @@
+use crate::contract::OPERATION_EXPIRATION_GRACE_PERIOD;
 use crate::error::ContractError;
 use crate::evidence::{Evidence, OperationResult, TransactionResult};
 use crate::msg::{
-    AvailableTicketsResponse, PendingOperationsResponse, PendingRefundsResponse, XRPLTokensResponse,
+    AvailableTicketsResponse, PendingOperationsResponse, PendingRefundsResponse,
+    SignedTransactionResponse, XRPLTokensResponse,
 };
 
 use crate::state::{BridgeState, Config};
 use crate::tests::helper::{
-    generate_hash, generate_xrpl_address, generate_xrpl_pub_key, MockApp, FEE_DENOM,
-    TRUST_SET_LIMIT_AMOUNT,
+    generate_hash, generate_xrpl_address, generate_xrpl_pub_key, generate_xrpl_signer, MockApp,
+    FEE_DENOM, TRUST_SET_LIMIT_AMOUNT,
 };
 use crate::{
     msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
@@ -30,12 +32,14 @@ fn cancel_pending_operation() {
         cosmos_address: Addr::unchecked(signer),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let new_relayer = Relayer {
         cosmos_address: Addr::unchecked(not_owner),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
@@ -55,6 +59,7 @@ fn cancel_pending_operation() {
                 token_factory_addr: token_factory_addr.clone(),
                 issue_token: true,
                 rate_limit_addr: None,osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -69,6 +74,7 @@ fn cancel_pending_operation() {
             sending_precision: 6,
             max_holding_amount: Uint128::new(1000000000000),
             bridging_fee: Uint128::zero(),
+            token_type: None,
         },
         &[],
     )
@@ -185,6 +191,8 @@ fn cancel_pending_operation() {
         &ExecuteMsg::SendToXRPL {
             recipient: generate_xrpl_address(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(1, FEE_DENOM.to_string()),
     )
@@ -408,6 +416,7 @@ fn invalid_transaction_evidences() {
         cosmos_address: Addr::unchecked(signer),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
@@ -427,6 +436,7 @@ fn invalid_transaction_evidences() {
                 token_factory_addr: token_factory_addr.clone(),
                 issue_token: true,
                 rate_limit_addr: None,osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -543,6 +553,7 @@ fn unauthorized_access() {
         cosmos_address: Addr::unchecked(signer),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
@@ -562,6 +573,7 @@ fn unauthorized_access() {
                 token_factory_addr: token_factory_addr.clone(),
                 issue_token: true,
                 rate_limit_addr: None,osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -596,6 +608,7 @@ fn unauthorized_access() {
                 sending_precision: 1,
                 max_holding_amount: Uint128::one(),
                 bridging_fee: Uint128::zero(),
+                token_type: None,
             },
             &[],
         )
@@ -639,6 +652,8 @@ fn unauthorized_access() {
                     currency: "USD".to_string(),
                     amount: Uint128::new(100),
                     recipient: Addr::unchecked(signer),memo: None
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -668,3 +683,784 @@ fn unauthorized_access() {
         .to_string()
         .contains(ContractError::UnauthorizedSender {}.to_string().as_str()));
 }
+
+#[test]
+fn expire_operation() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[0];
+    let not_owner = &accounts[1];
+
+    let relayer = Relayer {
+        cosmos_address: Addr::unchecked(signer),
+        xrpl_address: generate_xrpl_address(),
+        xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
+    };
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: vec![relayer.clone()],
+                evidence_threshold: 1,
+                used_ticket_sequence_threshold: 3,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: generate_xrpl_address(),
+                xrpl_base_fee: 10,
+                token_factory_addr: token_factory_addr.clone(),
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    // Register COSMOS Token
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RegisterCosmosToken {
+            denom: FEE_DENOM.to_string(),
+            decimals: 6,
+            sending_precision: 6,
+            max_holding_amount: Uint128::new(1000000000000),
+            bridging_fee: Uint128::zero(),
+            token_type: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Set up enough tickets
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RecoverTickets {
+            account_sequence: 1,
+            number_of_tickets: Some(10),
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveEvidence {
+            evidence: Evidence::XRPLTransactionResult {
+                tx_hash: Some(generate_hash()),
+                account_sequence: Some(1),
+                ticket_sequence: None,
+                transaction_result: TransactionResult::Accepted,
+                operation_result: Some(OperationResult::TicketsAllocation {
+                    tickets: Some((1..11).collect()),
+                }),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Send a token to XRPL from an account that is not the owner
+    app.execute(
+        Addr::unchecked(not_owner),
+        contract_addr.clone(),
+        &ExecuteMsg::SendToXRPL {
+            recipient: generate_xrpl_address(),
+            deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
+        },
+        &coins(1, FEE_DENOM.to_string()),
+    )
+    .unwrap();
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(query_pending_operations.operations.len(), 1);
+    assert!(query_pending_operations.operations[0].created_at.is_some());
+    let operation_id = query_pending_operations.operations[0]
+        .ticket_sequence
+        .unwrap();
+
+    // Without an operation timeout, operations never expire
+    let expire_error = app
+        .execute(
+            Addr::unchecked(not_owner),
+            contract_addr.clone(),
+            &ExecuteMsg::ExpireOperation { operation_id },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(expire_error.root_cause().to_string().contains(
+        ContractError::OperationCannotExpire {}
+            .to_string()
+            .as_str()
+    ));
+
+    // Only the owner can set the operation timeout
+    let timeout_error = app
+        .execute(
+            Addr::unchecked(not_owner),
+            contract_addr.clone(),
+            &ExecuteMsg::UpdateOperationTimeout {
+                operation_timeout: Some(100),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(timeout_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::UnauthorizedSender {}.to_string().as_str()));
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::UpdateOperationTimeout {
+            operation_timeout: Some(100),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let query_config: Config = app
+        .query(contract_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+
+    assert_eq!(query_config.operation_timeout, Some(100));
+
+    // Before the timeout the operation can't be expired
+    let expire_error = app
+        .execute(
+            Addr::unchecked(not_owner),
+            contract_addr.clone(),
+            &ExecuteMsg::ExpireOperation { operation_id },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(expire_error.root_cause().to_string().contains(
+        ContractError::OperationNotExpired {}
+            .to_string()
+            .as_str()
+    ));
+
+    app.increase_time(100);
+
+    // Relayers can't sign an expired operation anymore
+    let signature_error = app
+        .execute(
+            Addr::unchecked(signer),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveSignature {
+                operation_id,
+                operation_version: 1,
+                signature: "signature".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(signature_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::OperationExpired {}.to_string().as_str()));
+
+    // Only the sender of the transfer can expire it
+    let expire_error = app
+        .execute(
+            Addr::unchecked(signer),
+            contract_addr.clone(),
+            &ExecuteMsg::ExpireOperation { operation_id },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(expire_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::UnauthorizedSender {}.to_string().as_str()));
+
+    // The sender must still wait for the grace period
+    let expire_error = app
+        .execute(
+            Addr::unchecked(not_owner),
+            contract_addr.clone(),
+            &ExecuteMsg::ExpireOperation { operation_id },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(expire_error.root_cause().to_string().contains(
+        ContractError::OperationNotExpired {}
+            .to_string()
+            .as_str()
+    ));
+
+    app.increase_time(OPERATION_EXPIRATION_GRACE_PERIOD);
+
+    app.execute(
+        Addr::unchecked(not_owner),
+        contract_addr.clone(),
+        &ExecuteMsg::ExpireOperation { operation_id },
+        &[],
+    )
+    .unwrap();
+
+    // The operation is removed, the ticket returned and the funds can be claimed by the sender
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert!(query_pending_operations.operations.is_empty());
+
+    let query_available_tickets: AvailableTicketsResponse = app
+        .query(contract_addr.clone(), &QueryMsg::AvailableTickets {})
+        .unwrap();
+
+    assert_eq!(query_available_tickets.tickets.len(), 10);
+
+    let query_pending_refunds: PendingRefundsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingRefunds {
+                address: Addr::unchecked(not_owner),
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(query_pending_refunds.pending_refunds.len(), 1);
+    assert_eq!(
+        query_pending_refunds.pending_refunds[0].coin,
+        coin(1, FEE_DENOM)
+    );
+}
+
+#[test]
+fn cancel_my_transfer() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[0];
+    let sender = &accounts[1];
+
+    let xrpl_signer = generate_xrpl_signer();
+    let relayer = Relayer {
+        cosmos_address: Addr::unchecked(signer),
+        xrpl_address: xrpl_signer.xrpl_address.clone(),
+        xrpl_pub_key: xrpl_signer.xrpl_pub_key.clone(),
+        weight: None,
+    };
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+
+    let bridge_xrpl_address = generate_xrpl_address();
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: vec![relayer.clone()],
+                evidence_threshold: 1,
+                used_ticket_sequence_threshold: 3,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: bridge_xrpl_address.clone(),
+                xrpl_base_fee: 10,
+                token_factory_addr: token_factory_addr.clone(),
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    // Register COSMOS Token
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RegisterCosmosToken {
+            denom: FEE_DENOM.to_string(),
+            decimals: 6,
+            sending_precision: 6,
+            max_holding_amount: Uint128::new(1000000000000),
+            bridging_fee: Uint128::zero(),
+            token_type: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Set up enough tickets
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RecoverTickets {
+            account_sequence: 1,
+            number_of_tickets: Some(10),
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveEvidence {
+            evidence: Evidence::XRPLTransactionResult {
+                tx_hash: Some(generate_hash()),
+                account_sequence: Some(1),
+                ticket_sequence: None,
+                transaction_result: TransactionResult::Accepted,
+                operation_result: Some(OperationResult::TicketsAllocation {
+                    tickets: Some((1..11).collect()),
+                }),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Create 2 transfers to XRPL
+    for _ in 0..2 {
+        app.execute(
+            Addr::unchecked(sender),
+            contract_addr.clone(),
+            &ExecuteMsg::SendToXRPL {
+                recipient: generate_xrpl_address(),
+                deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
+            },
+            &coins(1, FEE_DENOM.to_string()),
+        )
+        .unwrap();
+    }
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(query_pending_operations.operations.len(), 2);
+    let first_operation_id = query_pending_operations.operations[0]
+        .ticket_sequence
+        .unwrap();
+    let second_operation_id = query_pending_operations.operations[1]
+        .ticket_sequence
+        .unwrap();
+
+    // Only the sender of the transfer can cancel it, not even the owner
+    let cancel_error = app
+        .execute(
+            Addr::unchecked(signer),
+            contract_addr.clone(),
+            &ExecuteMsg::CancelMyTransfer {
+                operation_id: first_operation_id,
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(cancel_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::UnauthorizedSender {}.to_string().as_str()));
+
+    // Once the relayer signed the second transfer it can't be cancelled anymore
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveSignature {
+            operation_id: second_operation_id,
+            operation_version: 1,
+            signature: xrpl_signer.sign_operation(
+                &query_pending_operations.operations[1],
+                &bridge_xrpl_address,
+                1,
+            ),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let cancel_error = app
+        .execute(
+            Addr::unchecked(sender),
+            contract_addr.clone(),
+            &ExecuteMsg::CancelMyTransfer {
+                operation_id: second_operation_id,
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(cancel_error.root_cause().to_string().contains(
+        ContractError::OperationAlreadySigned {}
+            .to_string()
+            .as_str()
+    ));
+
+    // The first transfer has no signatures so the sender can cancel it
+    app.execute(
+        Addr::unchecked(sender),
+        contract_addr.clone(),
+        &ExecuteMsg::CancelMyTransfer {
+            operation_id: first_operation_id,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(query_pending_operations.operations.len(), 1);
+    assert_eq!(
+        query_pending_operations.operations[0].ticket_sequence,
+        Some(second_operation_id)
+    );
+
+    // Ticket was returned
+    let query_available_tickets: AvailableTicketsResponse = app
+        .query(contract_addr.clone(), &QueryMsg::AvailableTickets {})
+        .unwrap();
+
+    assert_eq!(query_available_tickets.tickets.len(), 9);
+
+    let query_pending_refunds: PendingRefundsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingRefunds {
+                address: Addr::unchecked(sender),
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(query_pending_refunds.pending_refunds.len(), 1);
+    assert_eq!(
+        query_pending_refunds.pending_refunds[0].coin,
+        coin(1, FEE_DENOM)
+    );
+}
+
+#[test]
+fn expire_operation_signed_by_quorum() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[0];
+    let sender = &accounts[2];
+
+    let xrpl_signers = vec![generate_xrpl_signer(), generate_xrpl_signer()];
+    let relayers: Vec<Relayer> = accounts[0..2]
+        .iter()
+        .zip(xrpl_signers.iter())
+        .map(|(account, xrpl_signer)| Relayer {
+            cosmos_address: Addr::unchecked(account),
+            xrpl_address: xrpl_signer.xrpl_address.clone(),
+            xrpl_pub_key: xrpl_signer.xrpl_pub_key.clone(),
+            weight: None,
+        })
+        .collect();
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+
+    let bridge_xrpl_address = generate_xrpl_address();
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: relayers.clone(),
+                evidence_threshold: 2,
+                used_ticket_sequence_threshold: 3,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: bridge_xrpl_address.clone(),
+                xrpl_base_fee: 10,
+                token_factory_addr: token_factory_addr.clone(),
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    // Register COSMOS Token
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RegisterCosmosToken {
+            denom: FEE_DENOM.to_string(),
+            decimals: 6,
+            sending_precision: 6,
+            max_holding_amount: Uint128::new(1000000000000),
+            bridging_fee: Uint128::zero(),
+            token_type: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Set up enough tickets
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RecoverTickets {
+            account_sequence: 1,
+            number_of_tickets: Some(10),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let tx_hash = generate_hash();
+    for relayer in &relayers {
+        app.execute(
+            relayer.cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveEvidence {
+                evidence: Evidence::XRPLTransactionResult {
+                    tx_hash: Some(tx_hash.clone()),
+                    account_sequence: Some(1),
+                    ticket_sequence: None,
+                    transaction_result: TransactionResult::Accepted,
+                    operation_result: Some(OperationResult::TicketsAllocation {
+                        tickets: Some((1..11).collect()),
+                    }),
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::UpdateOperationTimeout {
+            operation_timeout: Some(100),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Create 2 transfers to XRPL
+    for _ in 0..2 {
+        app.execute(
+            Addr::unchecked(sender),
+            contract_addr.clone(),
+            &ExecuteMsg::SendToXRPL {
+                recipient: generate_xrpl_address(),
+                deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
+            },
+            &coins(1, FEE_DENOM.to_string()),
+        )
+        .unwrap();
+    }
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(query_pending_operations.operations.len(), 2);
+    let partially_signed_operation = &query_pending_operations.operations[0];
+    let fully_signed_operation = &query_pending_operations.operations[1];
+
+    // The first transfer is signed by one relayer and the second one by both, which reaches the threshold
+    app.execute(
+        relayers[0].cosmos_address.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveSignature {
+            operation_id: partially_signed_operation.ticket_sequence.unwrap(),
+            operation_version: 1,
+            signature: xrpl_signers[0].sign_operation(
+                partially_signed_operation,
+                &bridge_xrpl_address,
+                2,
+            ),
+        },
+        &[],
+    )
+    .unwrap();
+
+    for (relayer, xrpl_signer) in relayers.iter().zip(xrpl_signers.iter()) {
+        app.execute(
+            relayer.cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveSignature {
+                operation_id: fully_signed_operation.ticket_sequence.unwrap(),
+                operation_version: 1,
+                signature: xrpl_signer.sign_operation(
+                    fully_signed_operation,
+                    &bridge_xrpl_address,
+                    2,
+                ),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    app.increase_time(100 + OPERATION_EXPIRATION_GRACE_PERIOD);
+
+    // The transaction with all the signatures needed can still be executed in XRPL, so it can't expire
+    let expire_error = app
+        .execute(
+            Addr::unchecked(sender),
+            contract_addr.clone(),
+            &ExecuteMsg::ExpireOperation {
+                operation_id: fully_signed_operation.ticket_sequence.unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(expire_error.root_cause().to_string().contains(
+        ContractError::OperationSignedByQuorum {}
+            .to_string()
+            .as_str()
+    ));
+
+    // Signatures below the threshold are not enough to execute the transaction, so it can expire
+    app.execute(
+        Addr::unchecked(sender),
+        contract_addr.clone(),
+        &ExecuteMsg::ExpireOperation {
+            operation_id: partially_signed_operation.ticket_sequence.unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(query_pending_operations.operations.len(), 1);
+    assert_eq!(
+        query_pending_operations.operations[0].ticket_sequence,
+        fully_signed_operation.ticket_sequence
+    );
+
+    // Relayers can still get the signed transaction after its expiration, so that they complete it in XRPL
+    let query_signed_transaction: SignedTransactionResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::SignedTransaction {
+                operation_id: fully_signed_operation.ticket_sequence.unwrap(),
+            },
+        )
+        .unwrap();
+    assert!(query_signed_transaction.tx_blob.is_some());
+
+    // XRPL rejects it, so the funds of both transfers can be claimed back
+    let tx_hash = generate_hash();
+    for relayer in &relayers {
+        app.execute(
+            relayer.cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveEvidence {
+                evidence: Evidence::XRPLTransactionResult {
+                    tx_hash: Some(tx_hash.clone()),
+                    account_sequence: None,
+                    ticket_sequence: fully_signed_operation.ticket_sequence,
+                    transaction_result: TransactionResult::Rejected,
+                    operation_result: None,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    let query_pending_refunds: PendingRefundsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingRefunds {
+                address: Addr::unchecked(sender),
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(query_pending_refunds.pending_refunds.len(), 2);
+
+    for pending_refund in query_pending_refunds.pending_refunds {
+        app.execute(
+            Addr::unchecked(sender),
+            contract_addr.clone(),
+            &ExecuteMsg::ClaimRefund {
+                pending_refund_id: pending_refund.id,
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    let sender_balance = app
+        .query_balance(Addr::unchecked(sender), FEE_DENOM.to_string())
+        .unwrap();
+    assert_eq!(sender_balance, Uint128::new(100_000_000_000));
+}
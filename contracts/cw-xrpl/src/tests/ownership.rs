@@ -24,6 +24,7 @@ fn transfer_ownership() {
         cosmos_address: Addr::unchecked(signer),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
@@ -42,6 +43,7 @@ fn transfer_ownership() {
                 token_factory_addr: token_factory_addr.clone(),
                 issue_token: true,
                 rate_limit_addr: None,osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
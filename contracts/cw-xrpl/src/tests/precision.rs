@@ -5,7 +5,7 @@ use crate::msg::{
     CosmosTokensResponse, ExecuteMsg, PendingOperationsResponse, QueryMsg, XRPLTokensResponse,
 };
 
-use crate::state::{CosmosToken, TokenState, XRPLToken};
+use crate::state::{CosmosToken, CosmosTokenType, TokenState, XRPLToken};
 use crate::tests::helper::{
     generate_hash, generate_xrpl_address, generate_xrpl_pub_key, MockApp, FEE_DENOM,
     TRUST_SET_LIMIT_AMOUNT,
@@ -30,6 +30,7 @@ fn precisions() {
         cosmos_address: Addr::unchecked(signer),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
@@ -48,6 +49,7 @@ fn precisions() {
                 token_factory_addr: token_factory_addr.clone(),
                 issue_token: true,
                 rate_limit_addr: None,osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -187,6 +189,8 @@ fn precisions() {
                     // Sending less than 100000000000000000, in this case 99999999999999999 (1 less digit) should return an error because it will truncate to zero
                     amount: Uint128::new(99999999999999999),
                     recipient: Addr::unchecked(receiver), memo: None
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -210,6 +214,8 @@ fn precisions() {
                 // Sending more than 199999999999999999 will truncate to 100000000000000000 and send it to the user and keep the remainder in the contract as fees to collect.
                 amount: Uint128::new(199999999999999999),
                 recipient: Addr::unchecked(receiver), memo: None
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -234,6 +240,8 @@ fn precisions() {
                     currency: test_token1.currency.clone(),
                     amount: Uint128::new(100000000000000000),
                     recipient: Addr::unchecked(receiver), memo: None
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -327,6 +335,8 @@ fn precisions() {
                     // Sending more than 499 should fail because maximum holding amount is 499
                     amount: Uint128::new(500),
                     recipient: Addr::unchecked(receiver), memo: None
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -351,6 +361,8 @@ fn precisions() {
                     // Sending less than 100 will truncate to 0 so should fail
                     amount: Uint128::new(99),
                     recipient: Addr::unchecked(receiver), memo: None
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -374,6 +386,8 @@ fn precisions() {
                 // Sending 299 should truncate the amount to 200 and keep the 99 in the contract as fees to collect
                 amount: Uint128::new(299),
                 recipient: Addr::unchecked(receiver), memo: None
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -397,6 +411,8 @@ fn precisions() {
                 currency: test_token2.currency.clone(),
                 amount: Uint128::new(200),
                 recipient: Addr::unchecked(receiver), memo: None
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -427,6 +443,8 @@ fn precisions() {
                     currency: test_token2.currency.clone(),
                     amount: Uint128::new(199),
                     recipient: Addr::unchecked(receiver), memo: None
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -513,6 +531,8 @@ fn precisions() {
                     // Sending more than 5000000000000000 should fail because maximum holding amount is 5000000000000000
                     amount: Uint128::new(6000000000000000),
                     recipient: Addr::unchecked(receiver), memo: None
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -537,6 +557,8 @@ fn precisions() {
                     // Sending less than 1000000000000000 will truncate to 0 so should fail
                     amount: Uint128::new(900000000000000),
                     recipient: Addr::unchecked(receiver), memo: None
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -560,6 +582,8 @@ fn precisions() {
                 // Sending 1111111111111111 should truncate the amount to 1000000000000000 and keep 111111111111111 as fees to collect
                 amount: Uint128::new(1111111111111111),
                 recipient: Addr::unchecked(receiver), memo: None
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -583,6 +607,8 @@ fn precisions() {
                 // Sending 3111111111111111 should truncate the amount to 3000000000000000 and keep another 111111111111111 as fees to collect
                 amount: Uint128::new(3111111111111111),
                 recipient: Addr::unchecked(receiver), memo: None
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -613,6 +639,8 @@ fn precisions() {
                     // Sending 1111111111111111 should truncate the amount to 1000000000000000 and should fail because bridge is already holding maximum
                     amount: Uint128::new(1111111111111111),
                     recipient: Addr::unchecked(receiver), memo: None
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -646,6 +674,8 @@ fn precisions() {
                     // Sending more than 100000000000000000 should fail because maximum holding amount is 10000000000000000 (1 less zero)
                     amount: Uint128::new(100000000000000000),
                     recipient: Addr::unchecked(receiver), memo: None
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -669,6 +699,8 @@ fn precisions() {
                 // There should never be truncation because we allow full precision for XRP initially
                 amount: Uint128::one(),
                 recipient: Addr::unchecked(receiver), memo: None
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -692,6 +724,8 @@ fn precisions() {
                 // This should work because we are sending the rest to reach the maximum amount
                 amount: Uint128::new(9999999999999999),
                 recipient: Addr::unchecked(receiver), memo: None
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -716,6 +750,8 @@ fn precisions() {
                     // Sending 1 more token would surpass the maximum so should fail
                     amount: Uint128::one(),
                     recipient: Addr::unchecked(receiver), memo: None
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -764,6 +800,7 @@ fn precisions() {
             bridging_fee: Uint128::zero(),
             xrpl_currency: XRP_CURRENCY.to_string(),
             state: TokenState::Enabled,
+            token_type: CosmosTokenType::Native,
         },
         CosmosToken {
             denom: denom2.clone(),
@@ -773,6 +810,7 @@ fn precisions() {
             bridging_fee: Uint128::zero(),
             xrpl_currency: XRP_CURRENCY.to_string(),
             state: TokenState::Enabled,
+            token_type: CosmosTokenType::Native,
         },
         CosmosToken {
             denom: denom3.clone(),
@@ -782,6 +820,7 @@ fn precisions() {
             bridging_fee: Uint128::zero(),
             xrpl_currency: XRP_CURRENCY.to_string(),
             state: TokenState::Enabled,
+            token_type: CosmosTokenType::Native,
         },
     ];
 
@@ -797,6 +836,7 @@ fn precisions() {
                 sending_precision: token.sending_precision,
                 max_holding_amount: token.max_holding_amount,
                 bridging_fee: token.bridging_fee,
+                token_type: None,
             },
             &[],
         )
@@ -826,6 +866,8 @@ fn precisions() {
         &ExecuteMsg::SendToXRPL {
             recipient: generate_xrpl_address(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(2, denom1.clone()),
     )
@@ -838,6 +880,8 @@ fn precisions() {
         &ExecuteMsg::SendToXRPL {
             recipient: generate_xrpl_address(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(1, denom1.clone()),
     )
@@ -851,6 +895,8 @@ fn precisions() {
             &ExecuteMsg::SendToXRPL {
                 recipient: generate_xrpl_address(),
                 deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(1, denom1.clone()),
         )
@@ -878,6 +924,8 @@ fn precisions() {
             &ExecuteMsg::SendToXRPL {
                 recipient: generate_xrpl_address(),
                 deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(100000, denom2.clone()),
         )
@@ -896,6 +944,8 @@ fn precisions() {
         &ExecuteMsg::SendToXRPL {
             recipient: generate_xrpl_address(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(3990000, denom2.clone()),
     )
@@ -909,6 +959,8 @@ fn precisions() {
             &ExecuteMsg::SendToXRPL {
                 recipient: generate_xrpl_address(),
                 deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(100000, denom2.clone()),
         )
@@ -928,6 +980,8 @@ fn precisions() {
             &ExecuteMsg::SendToXRPL {
                 recipient: generate_xrpl_address(),
                 deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(1000000, denom2.clone()),
         )
@@ -954,6 +1008,8 @@ fn precisions() {
         &ExecuteMsg::SendToXRPL {
             recipient: generate_xrpl_address(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(2000000000000, denom3.clone()),
     )
@@ -967,6 +1023,8 @@ fn precisions() {
             &ExecuteMsg::SendToXRPL {
                 recipient: generate_xrpl_address(),
                 deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(200000000000, denom3.clone()),
         )
@@ -986,6 +1044,8 @@ fn precisions() {
             &ExecuteMsg::SendToXRPL {
                 recipient: generate_xrpl_address(),
                 deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(1000000000000, denom3.clone()),
         )
@@ -1,23 +1,31 @@
-use cosmwasm_std::{coins, Addr, Uint128};
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{coins, Addr, Empty, Order, Timestamp, Uint128};
 
 use crate::contract::{
-    INITIAL_PROHIBITED_XRPL_ADDRESSES, XRP_CURRENCY, XRP_DEFAULT_MAX_HOLDING_AMOUNT,
+    INITIAL_PROHIBITED_XRPL_ADDRESSES, MIN_STALE_EVIDENCE_AGE, XRP_CURRENCY, XRP_DEFAULT_MAX_HOLDING_AMOUNT,
     XRP_DEFAULT_SENDING_PRECISION, XRP_ISSUER, XRP_SYMBOL,
 };
-use crate::evidence::{Evidence, OperationResult, TransactionResult};
+use crate::error::ContractError;
+use crate::evidence::{
+    index_evidences_first_seen, Evidence, Evidences, OperationResult, TransactionResult,
+};
 use crate::msg::{
-    ExecuteMsg, ProhibitedXRPLAddressesResponse, TransactionEvidence, TransactionEvidencesResponse,
+    AllRelayerStatsResponse, ConflictingEvidencesResponse, ExecuteMsg, PendingOperationsResponse,
+    RelayerStatsResponse, ProhibitedXRPLAddressesResponse, TransactionEvidence, TransactionEvidencesResponse,
     XRPLTokensResponse,
 };
-use crate::state::{BridgeState, Config, TokenState, XRPLToken};
+use crate::state::{
+    BridgeState, Config, FeeDistribution, TokenState, XRPLNetwork, XRPLToken,
+    LEGACY_EVIDENCES_CURSOR, TX_EVIDENCES, TX_EVIDENCES_FIRST_SEEN,
+};
 use crate::tests::helper::{
-    generate_hash, generate_xrpl_address, generate_xrpl_pub_key, MockApp, FEE_DENOM,
+    generate_hash, generate_xrpl_address, generate_xrpl_pub_key, generate_xrpl_signer, MockApp, FEE_DENOM,
     TRUST_SET_LIMIT_AMOUNT,
 };
 use crate::token::full_denom;
 use crate::{
     msg::{InstantiateMsg, QueryMsg},
-    relayer::Relayer,
+    relayer::{Relayer, RelayerStats},
 };
 
 #[test]
@@ -43,6 +51,7 @@ fn queries() {
             cosmos_address: Addr::unchecked(account),
             xrpl_address: xrpl_addresses[i].to_string(),
             xrpl_pub_key: xrpl_pub_keys[i].to_string(),
+            weight: None,
         });
     }
 
@@ -67,6 +76,7 @@ fn queries() {
                 token_factory_addr: token_factory_addr.clone(),
                 issue_token: true,
                 rate_limit_addr: None,osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -92,6 +102,12 @@ fn queries() {
             xrpl_base_fee: 10,
             token_factory_addr: token_factory_addr.clone(),
             rate_limit_addr: None,osor_entry_point: None,
+            xrpl_network: XRPLNetwork::Mainnet,
+            halt_on_evidence_conflict: false,
+            fee_distribution: FeeDistribution::Equal,
+            operation_timeout: None,
+            auto_claim_refunds: false,
+            enforce_signature_verification: false,
         }
     );
 
@@ -235,6 +251,104 @@ fn queries() {
 
     assert!(!query_transaction_evidence.relayer_addresses.is_empty());
 
+    // Relayers disagreed on the result of the transaction, so both evidences are returned as conflicting
+    let query_conflicting_evidences: ConflictingEvidencesResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::ConflictingEvidences {
+                tx_hash: tx_hash.to_lowercase(),
+            },
+        )
+        .unwrap();
+
+    assert_eq!(query_conflicting_evidences.tx_hash, tx_hash.to_uppercase());
+    assert_eq!(query_conflicting_evidences.evidences.len(), 2);
+
+    // Only the owner can prune evidences that are recent
+    let prune_error = app
+        .execute(
+            Addr::unchecked(&relayer_accounts[0]),
+            contract_addr.clone(),
+            &ExecuteMsg::PruneStaleEvidences {
+                older_than_seconds: 0,
+                limit: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(prune_error.root_cause().to_string().contains(
+        ContractError::StaleEvidenceAgeTooLow {
+            min_age: MIN_STALE_EVIDENCE_AGE
+        }
+        .to_string()
+        .as_str()
+    ));
+
+    // Anyone can prune old evidences but these ones are not old enough, so nothing is removed
+    app.execute(
+        Addr::unchecked(&relayer_accounts[0]),
+        contract_addr.clone(),
+        &ExecuteMsg::PruneStaleEvidences {
+            older_than_seconds: MIN_STALE_EVIDENCE_AGE,
+            limit: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let query_transaction_evidences: TransactionEvidencesResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::TransactionEvidences {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(query_transaction_evidences.transaction_evidences.len(), 2);
+
+    // The owner prunes them in pages
+    for remaining in [1, 0] {
+        app.execute(
+            Addr::unchecked(signer),
+            contract_addr.clone(),
+            &ExecuteMsg::PruneStaleEvidences {
+                older_than_seconds: 0,
+                limit: Some(1),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let query_transaction_evidences: TransactionEvidencesResponse = app
+            .query(
+                contract_addr.clone(),
+                &QueryMsg::TransactionEvidences {
+                    start_after_key: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            query_transaction_evidences.transaction_evidences.len(),
+            remaining
+        );
+    }
+
+    let query_conflicting_evidences: ConflictingEvidencesResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::ConflictingEvidences {
+                tx_hash: tx_hash.clone(),
+            },
+        )
+        .unwrap();
+
+    assert!(query_conflicting_evidences.evidences.is_empty());
+
     // Let's query the prohibited addresses
     let query_prohibited_addresses: ProhibitedXRPLAddressesResponse = app
         .query(contract_addr.clone(), &QueryMsg::ProhibitedXRPLAddresses {})
@@ -289,3 +403,252 @@ fn queries() {
     )
     .unwrap_err();
 }
+
+#[test]
+fn relayer_stats() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[2];
+    let xrpl_signers = [generate_xrpl_signer(), generate_xrpl_signer()];
+    let relayers: Vec<Relayer> = (0..2)
+        .map(|i| Relayer {
+            cosmos_address: Addr::unchecked(&accounts[i]),
+            xrpl_address: xrpl_signers[i].xrpl_address.clone(),
+            xrpl_pub_key: xrpl_signers[i].xrpl_pub_key.clone(),
+            weight: None,
+        })
+        .collect();
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+    let bridge_xrpl_address = generate_xrpl_address();
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: relayers.clone(),
+                evidence_threshold: 2,
+                used_ticket_sequence_threshold: 4,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: bridge_xrpl_address.clone(),
+                xrpl_base_fee: 10,
+                token_factory_addr: token_factory_addr.clone(),
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    // Relayers without activity have empty stats
+    let query_relayer_stats: RelayerStatsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::RelayerStats {
+                address: relayers[0].cosmos_address.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(query_relayer_stats.stats, RelayerStats::default());
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RecoverTickets {
+            account_sequence: 1,
+            number_of_tickets: Some(5),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Both relayers provide the evidence that completes the threshold
+    let tx_hash = generate_hash();
+    for relayer in &relayers {
+        app.execute(
+            relayer.cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveEvidence {
+                evidence: Evidence::XRPLTransactionResult {
+                    tx_hash: Some(tx_hash.clone()),
+                    account_sequence: Some(1),
+                    ticket_sequence: None,
+                    transaction_result: TransactionResult::Accepted,
+                    operation_result: Some(OperationResult::TicketsAllocation {
+                        tickets: Some((1..6).collect()),
+                    }),
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    // Only the first relayer signs the trust set of a new token
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RegisterXRPLToken {
+            issuer: generate_xrpl_address(),
+            currency: "USD".to_string(),
+            sending_precision: 4,
+            max_holding_amount: Uint128::new(50000),
+            bridging_fee: Uint128::zero(),
+        },
+        &coins(10_000_000u128, FEE_DENOM),
+    )
+    .unwrap();
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let trust_set_operation = &query_pending_operations.operations[0];
+
+    app.execute(
+        relayers[0].cosmos_address.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveSignature {
+            operation_id: trust_set_operation.ticket_sequence.unwrap(),
+            operation_version: 1,
+            signature: xrpl_signers[0].sign_operation(
+                trust_set_operation,
+                &bridge_xrpl_address,
+                2,
+            ),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let query_all_relayer_stats: AllRelayerStatsResponse = app
+        .query(contract_addr.clone(), &QueryMsg::AllRelayerStats {})
+        .unwrap();
+    assert_eq!(query_all_relayer_stats.relayer_stats.len(), 2);
+
+    let stats_of = |address: &Addr| {
+        query_all_relayer_stats
+            .relayer_stats
+            .iter()
+            .find(|relayer_stats| &relayer_stats.address == address)
+            .unwrap()
+            .stats
+            .clone()
+    };
+
+    let first_relayer_stats = stats_of(&relayers[0].cosmos_address);
+    assert_eq!(first_relayer_stats.evidences_submitted, 1);
+    assert_eq!(first_relayer_stats.signatures_provided, 1);
+    assert_eq!(first_relayer_stats.threshold_completions, 1);
+    assert!(first_relayer_stats.last_active.is_some());
+
+    let second_relayer_stats = stats_of(&relayers[1].cosmos_address);
+    assert_eq!(second_relayer_stats.evidences_submitted, 1);
+    assert_eq!(second_relayer_stats.signatures_provided, 0);
+    assert_eq!(second_relayer_stats.threshold_completions, 1);
+
+    let query_relayer_stats: RelayerStatsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::RelayerStats {
+                address: relayers[1].cosmos_address.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(query_relayer_stats.stats, second_relayer_stats);
+}
+
+#[test]
+fn index_legacy_evidences_in_pages() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    // Evidences stored before the first seen index existed have time 0
+    let unindexed_hash = generate_hash();
+    let unindexed_evidences = Evidences {
+        relayer_cosmos_addresses: vec![Addr::unchecked("relayer")],
+        first_seen: Timestamp::default(),
+        tx_hash: None,
+    };
+    TX_EVIDENCES
+        .save(
+            deps.as_mut().storage,
+            unindexed_hash.clone(),
+            &unindexed_evidences,
+        )
+        .unwrap();
+
+    let indexed_hash = generate_hash();
+    let indexed_evidences = Evidences {
+        relayer_cosmos_addresses: vec![Addr::unchecked("relayer")],
+        first_seen: Timestamp::from_seconds(100),
+        tx_hash: None,
+    };
+    TX_EVIDENCES
+        .save(
+            deps.as_mut().storage,
+            indexed_hash.clone(),
+            &indexed_evidences,
+        )
+        .unwrap();
+    TX_EVIDENCES_FIRST_SEEN
+        .save(
+            deps.as_mut().storage,
+            (100, indexed_hash.clone()),
+            &Empty {},
+        )
+        .unwrap();
+
+    // Evidences are checked one page at a time, continuing from the stored cursor
+    let mut indexed = 0;
+    for _ in 0..2 {
+        let (page_indexed, completed) =
+            index_evidences_first_seen(deps.as_mut().storage, env.block.time, 1).unwrap();
+        assert!(!completed);
+        assert!(LEGACY_EVIDENCES_CURSOR.exists(deps.as_ref().storage));
+        indexed += page_indexed;
+    }
+    let (page_indexed, completed) =
+        index_evidences_first_seen(deps.as_mut().storage, env.block.time, 1).unwrap();
+    assert!(completed);
+    assert!(!LEGACY_EVIDENCES_CURSOR.exists(deps.as_ref().storage));
+    assert_eq!(indexed + page_indexed, 1);
+
+    // The old evidence is indexed at the time it was checked so that it can be pruned once it's stale
+    assert_eq!(
+        TX_EVIDENCES
+            .load(deps.as_ref().storage, unindexed_hash.clone())
+            .unwrap()
+            .first_seen,
+        env.block.time
+    );
+    assert!(TX_EVIDENCES_FIRST_SEEN.has(
+        deps.as_ref().storage,
+        (env.block.time.seconds(), unindexed_hash)
+    ));
+
+    // Evidences that were already indexed are not touched
+    assert_eq!(
+        TX_EVIDENCES
+            .load(deps.as_ref().storage, indexed_hash.clone())
+            .unwrap(),
+        indexed_evidences
+    );
+    assert!(TX_EVIDENCES_FIRST_SEEN.has(deps.as_ref().storage, (100, indexed_hash)));
+    assert_eq!(
+        TX_EVIDENCES_FIRST_SEEN
+            .keys(deps.as_ref().storage, None, None, Order::Ascending)
+            .count(),
+        2
+    );
+}
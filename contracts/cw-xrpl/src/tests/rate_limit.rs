@@ -53,6 +53,7 @@ fn test_register_rate_limit() {
             cosmos_address: Addr::unchecked(account),
             xrpl_address: xrpl_addresses[i].to_string(),
             xrpl_pub_key: xrpl_pub_keys[i].to_string(),
+            weight: None,
         });
     }
 
@@ -78,6 +79,7 @@ fn test_register_rate_limit() {
                 issue_token: true,
                 rate_limit_addr: Some(rate_limit_addr.clone()),
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -284,6 +286,7 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos_with_rate_limit() {
             cosmos_address: Addr::unchecked(account),
             xrpl_address: xrpl_addresses[i].to_string(),
             xrpl_pub_key: xrpl_pub_keys[i].to_string(),
+            weight: None,
         });
     }
 
@@ -310,6 +313,7 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos_with_rate_limit() {
                 issue_token: true,
                 rate_limit_addr: Some(rate_limit_addr),
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -444,6 +448,8 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos_with_rate_limit() {
                 amount: amount.clone(),
                 recipient: Addr::unchecked(receiver),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -469,6 +475,8 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos_with_rate_limit() {
                 amount: amount.clone(),
                 recipient: Addr::unchecked(receiver),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -488,6 +496,8 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos_with_rate_limit() {
                 amount: amount.clone(),
                 recipient: Addr::unchecked(receiver),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -510,6 +520,7 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos_with_rate_limit() {
         cosmos_address: Addr::unchecked(relayer_account),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let xrpl_receiver_address = generate_xrpl_address();
@@ -535,6 +546,7 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos_with_rate_limit() {
                 issue_token: true,
                 rate_limit_addr: Some(rate_limit_addr.clone()),
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -608,6 +620,7 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos_with_rate_limit() {
             sending_precision: 5,
             max_holding_amount: Uint128::new(100000000000000000000),
             bridging_fee: Uint128::zero(),
+            token_type: None,
         },
         &[],
     )
@@ -659,6 +672,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos_with_rate_limit() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(amount_to_send.u128(), denom.clone()),
     )
@@ -713,6 +728,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos_with_rate_limit() {
             max_amount: Some(amount_truncated_and_converted),
             sender: Addr::unchecked(sender),
             recipient: xrpl_receiver_address.clone(),
+            destination_tag: None,
+            refund_to: None,
         }
     );
 
@@ -833,6 +850,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos_with_rate_limit() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(amount_to_send.u128(), denom.clone()),
     )
@@ -897,6 +916,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos_with_rate_limit() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(amount_to_send.u128(), denom.clone()),
     )
@@ -918,6 +939,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos_with_rate_limit() {
                 amount: amount_to_send_back.clone(),
                 recipient: Addr::unchecked(sender),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -973,6 +996,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos_with_rate_limit() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(amount_to_send.u128(), denom.clone()),
     )
@@ -994,6 +1019,7 @@ fn send_from_cosmos_to_xrp_with_rate_limit() {
         cosmos_address: Addr::unchecked(relayer_account),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let xrpl_base_fee = 10;
@@ -1019,6 +1045,7 @@ fn send_from_cosmos_to_xrp_with_rate_limit() {
                 issue_token: true,
                 rate_limit_addr: Some(rate_limit_addr.clone()),
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -1129,6 +1156,8 @@ fn send_from_cosmos_to_xrp_with_rate_limit() {
                 amount: amount_to_send_xrp.clone(),
                 recipient: Addr::unchecked(sender),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -1150,6 +1179,8 @@ fn send_from_cosmos_to_xrp_with_rate_limit() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(amount_to_send_back.u128(), denom_xrp.clone()),
     )
@@ -1171,6 +1202,7 @@ fn send_from_cosmos_to_xrp_with_rate_limit() {
         query_pending_operations.operations[0],
         Operation {
             id: query_pending_operations.operations[0].id.clone(),
+            created_at: query_pending_operations.operations[0].created_at,
             version: 1,
             ticket_sequence: Some(1),
             account_sequence: None,
@@ -1182,6 +1214,8 @@ fn send_from_cosmos_to_xrp_with_rate_limit() {
                 max_amount: None,
                 sender: Addr::unchecked(sender),
                 recipient: xrpl_receiver_address.clone(),
+                destination_tag: None,
+                refund_to: None,
             },
             xrpl_base_fee,
         }
@@ -1249,6 +1283,8 @@ fn send_from_cosmos_to_xrp_with_rate_limit() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(amount_to_send_back.u128(), denom_xrp.clone()),
     )
@@ -1406,6 +1442,8 @@ fn send_from_cosmos_to_xrp_with_rate_limit() {
                 amount: amount_to_send.clone(),
                 recipient: Addr::unchecked(sender),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -1424,6 +1462,8 @@ fn send_from_cosmos_to_xrp_with_rate_limit() {
                 amount: amount_to_send.clone(),
                 recipient: Addr::unchecked(sender),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -1461,6 +1501,8 @@ fn send_from_cosmos_to_xrp_with_rate_limit() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(amount_to_send_back.u128(), denom_xrpl_origin_token.clone()),
     )
@@ -1483,6 +1525,7 @@ fn send_from_cosmos_to_xrp_with_rate_limit() {
         query_pending_operations.operations[0],
         Operation {
             id: query_pending_operations.operations[0].id.clone(),
+            created_at: query_pending_operations.operations[0].created_at,
             version: 1,
             ticket_sequence: Some(4),
             account_sequence: None,
@@ -1494,6 +1537,8 @@ fn send_from_cosmos_to_xrp_with_rate_limit() {
                 max_amount: Some(amount_to_send_back),
                 sender: Addr::unchecked(sender),
                 recipient: xrpl_receiver_address.clone(),
+                destination_tag: None,
+                refund_to: None,
             },
             xrpl_base_fee
         }
@@ -1552,6 +1597,8 @@ fn send_from_cosmos_to_xrp_with_rate_limit() {
                 amount: amount_to_send.clone(),
                 recipient: Addr::unchecked(sender),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
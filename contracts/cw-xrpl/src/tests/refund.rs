@@ -0,0 +1,557 @@
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{
+    coin, coins, to_json_binary, Addr, Order, Reply, SubMsgResponse, SubMsgResult, Uint128,
+};
+use cw20::{Cw20Coin, Cw20ExecuteMsg};
+
+use crate::contract::reply;
+use crate::error::ContractError;
+use crate::evidence::{Evidence, OperationResult, TransactionResult};
+use crate::msg::{
+    AutoClaimRefundsResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PendingOperationsResponse,
+    PendingRefundsResponse, QueryMsg, XRPLTransfer,
+};
+use crate::relayer::Relayer;
+use crate::state::{CosmosTokenType, PendingRefund, PENDING_AUTO_REFUNDS, PENDING_REFUNDS};
+use crate::tests::helper::{
+    generate_hash, generate_xrpl_address, generate_xrpl_pub_key, MockApp, FEE_DENOM,
+    TRUST_SET_LIMIT_AMOUNT,
+};
+
+// Sends a transfer to XRPL and makes the relayer reject it
+fn send_and_reject(
+    app: &mut MockApp,
+    contract_addr: &Addr,
+    relayer: &Addr,
+    sender: &Addr,
+    refund_to: Option<&Addr>,
+) {
+    app.execute(
+        sender.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::SendToXRPL {
+            recipient: generate_xrpl_address(),
+            deliver_amount: None,
+            destination_tag: None,
+            refund_to: refund_to.map(Addr::to_string),
+        },
+        &coins(1_000_000, FEE_DENOM),
+    )
+    .unwrap();
+
+    reject_pending_operation(app, contract_addr, relayer);
+}
+
+// Makes the relayer reject the only pending operation
+fn reject_pending_operation(app: &mut MockApp, contract_addr: &Addr, relayer: &Addr) {
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    app.execute(
+        relayer.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveEvidence {
+            evidence: Evidence::XRPLTransactionResult {
+                tx_hash: Some(generate_hash()),
+                account_sequence: None,
+                ticket_sequence: query_pending_operations.operations[0].ticket_sequence,
+                transaction_result: TransactionResult::Rejected,
+                operation_result: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+fn pending_refunds(app: &MockApp, contract_addr: &Addr, address: &Addr) -> PendingRefundsResponse {
+    app.query(
+        contract_addr.clone(),
+        &QueryMsg::PendingRefunds {
+            address: address.clone(),
+            start_after_key: None,
+            limit: None,
+        },
+    )
+    .unwrap()
+}
+
+// Bridge with a single relayer (the owner), the fee denom registered and tickets allocated
+fn setup_bridge() -> (MockApp, Addr, Vec<Addr>) {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = Addr::unchecked(&accounts[0]);
+
+    let relayer = Relayer {
+        cosmos_address: signer.clone(),
+        xrpl_address: generate_xrpl_address(),
+        xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
+    };
+
+    let token_factory_addr = app.create_tokenfactory(signer.clone()).unwrap();
+
+    let contract_addr = app
+        .create_bridge(
+            signer.clone(),
+            &InstantiateMsg {
+                owner: signer.clone(),
+                relayers: vec![relayer],
+                evidence_threshold: 1,
+                used_ticket_sequence_threshold: 3,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: generate_xrpl_address(),
+                xrpl_base_fee: 10,
+                token_factory_addr,
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    app.execute(
+        signer.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::RegisterCosmosToken {
+            denom: FEE_DENOM.to_string(),
+            decimals: 6,
+            sending_precision: 6,
+            max_holding_amount: Uint128::new(1000000000000),
+            bridging_fee: Uint128::zero(),
+            token_type: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute(
+        signer.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::RecoverTickets {
+            account_sequence: 1,
+            number_of_tickets: Some(10),
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute(
+        signer.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveEvidence {
+            evidence: Evidence::XRPLTransactionResult {
+                tx_hash: Some(generate_hash()),
+                account_sequence: Some(1),
+                ticket_sequence: None,
+                transaction_result: TransactionResult::Accepted,
+                operation_result: Some(OperationResult::TicketsAllocation {
+                    tickets: Some((1..11).collect()),
+                }),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    (
+        app,
+        contract_addr,
+        accounts.iter().map(Addr::unchecked).collect(),
+    )
+}
+
+#[test]
+fn auto_claim_refunds() {
+    let (mut app, contract_addr, accounts) = setup_bridge();
+    let signer = accounts[0].clone();
+    let sender = accounts[1].clone();
+
+    // Sender opts in, so the refund of a rejected transfer is sent right away
+    app.execute(
+        sender.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::SetAutoClaimRefunds { enabled: true },
+        &[],
+    )
+    .unwrap();
+
+    let query_auto_claim: AutoClaimRefundsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::AutoClaimRefunds {
+                address: sender.clone(),
+            },
+        )
+        .unwrap();
+    assert!(query_auto_claim.enabled);
+
+    send_and_reject(&mut app, &contract_addr, &signer, &sender, None);
+
+    assert!(pending_refunds(&app, &contract_addr, &sender)
+        .pending_refunds
+        .is_empty());
+    assert_eq!(
+        app.query_balance(contract_addr.clone(), FEE_DENOM.to_string())
+            .unwrap(),
+        Uint128::zero()
+    );
+
+    // After opting out, refunds go to the pending refunds again
+    app.execute(
+        sender.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::SetAutoClaimRefunds { enabled: false },
+        &[],
+    )
+    .unwrap();
+
+    send_and_reject(&mut app, &contract_addr, &signer, &sender, None);
+    send_and_reject(&mut app, &contract_addr, &signer, &sender, None);
+
+    assert_eq!(
+        pending_refunds(&app, &contract_addr, &sender)
+            .pending_refunds
+            .len(),
+        2
+    );
+    assert_eq!(
+        app.query_balance(contract_addr.clone(), FEE_DENOM.to_string())
+            .unwrap(),
+        Uint128::new(2_000_000)
+    );
+
+    // All pending refunds can be claimed at once, up to the limit
+    app.execute(
+        sender.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::ClaimAllRefunds { limit: Some(1) },
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        pending_refunds(&app, &contract_addr, &sender)
+            .pending_refunds
+            .len(),
+        1
+    );
+
+    app.execute(
+        sender.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::ClaimAllRefunds { limit: None },
+        &[],
+    )
+    .unwrap();
+
+    assert!(pending_refunds(&app, &contract_addr, &sender)
+        .pending_refunds
+        .is_empty());
+    assert_eq!(
+        app.query_balance(contract_addr.clone(), FEE_DENOM.to_string())
+            .unwrap(),
+        Uint128::zero()
+    );
+
+    // Only the owner can enable auto claim for everyone
+    let update_error = app
+        .execute(
+            sender.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::UpdateAutoClaimRefunds {
+                auto_claim_refunds: true,
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(update_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::UnauthorizedSender {}.to_string().as_str()));
+
+    app.execute(
+        signer.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::UpdateAutoClaimRefunds {
+            auto_claim_refunds: true,
+        },
+        &[],
+    )
+    .unwrap();
+
+    send_and_reject(&mut app, &contract_addr, &signer, &sender, None);
+
+    assert!(pending_refunds(&app, &contract_addr, &sender)
+        .pending_refunds
+        .is_empty());
+    assert_eq!(
+        app.query_balance(contract_addr.clone(), FEE_DENOM.to_string())
+            .unwrap(),
+        Uint128::zero()
+    );
+}
+
+#[test]
+fn failed_auto_refund_is_stored_as_pending_refund() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    let pending_refund = PendingRefund {
+        address: Addr::unchecked("sender"),
+        id: "1-1".to_string(),
+        xrpl_tx_hash: Some(generate_hash()),
+        coin: coin(1_000_000, "denom"),
+        refund_to: None,
+    };
+
+    // A successful send only cleans up
+    PENDING_AUTO_REFUNDS
+        .save(deps.as_mut().storage, 1, &pending_refund)
+        .unwrap();
+    reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: 1,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+
+    assert!(!PENDING_AUTO_REFUNDS.has(deps.as_ref().storage, 1));
+    assert_eq!(
+        PENDING_REFUNDS
+            .idx
+            .address
+            .prefix(pending_refund.address.clone())
+            .range(deps.as_ref().storage, None, None, Order::Ascending)
+            .count(),
+        0
+    );
+
+    // If the send fails, the funds are stored so that the sender can claim them
+    PENDING_AUTO_REFUNDS
+        .save(deps.as_mut().storage, 2, &pending_refund)
+        .unwrap();
+    reply(
+        deps.as_mut(),
+        env,
+        Reply {
+            id: 2,
+            result: SubMsgResult::Err("blocked address".to_string()),
+        },
+    )
+    .unwrap();
+
+    assert!(!PENDING_AUTO_REFUNDS.has(deps.as_ref().storage, 2));
+    assert_eq!(
+        PENDING_REFUNDS
+            .load(
+                deps.as_ref().storage,
+                (pending_refund.address.clone(), pending_refund.id.clone())
+            )
+            .unwrap(),
+        pending_refund
+    );
+}
+
+#[test]
+fn refund_destination() {
+    let (mut app, contract_addr, accounts) = setup_bridge();
+    let signer = accounts[0].clone();
+    let sender = accounts[1].clone();
+    let refund_to = accounts[2].clone();
+
+    // An invalid refund address is rejected when sending
+    app.execute(
+        sender.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::SendToXRPL {
+            recipient: generate_xrpl_address(),
+            deliver_amount: None,
+            destination_tag: None,
+            refund_to: Some("INVALID".to_string()),
+        },
+        &coins(1_000_000, FEE_DENOM),
+    )
+    .unwrap_err();
+
+    send_and_reject(&mut app, &contract_addr, &signer, &sender, Some(&refund_to));
+    send_and_reject(&mut app, &contract_addr, &signer, &sender, None);
+
+    // Refunds stay under the sender but show where the funds will go
+    let query_pending_refunds = pending_refunds(&app, &contract_addr, &sender);
+    assert_eq!(query_pending_refunds.pending_refunds.len(), 2);
+    let redirected_refund = query_pending_refunds
+        .pending_refunds
+        .iter()
+        .find(|pending_refund| pending_refund.destination == refund_to)
+        .unwrap()
+        .clone();
+    assert!(query_pending_refunds
+        .pending_refunds
+        .iter()
+        .any(|pending_refund| pending_refund.destination == sender));
+
+    // A refund that doesn't exist can't be claimed
+    let claim_error = app
+        .execute(
+            signer.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::ClaimRefundFor {
+                address: sender.clone(),
+                id: "random_id".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(claim_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::PendingRefundNotFound {}.to_string().as_str()));
+
+    // Anyone can claim it for the sender and the funds go to the refund destination
+    let refund_to_balance = app
+        .query_balance(refund_to.clone(), FEE_DENOM.to_string())
+        .unwrap();
+
+    app.execute(
+        signer.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::ClaimRefundFor {
+            address: sender.clone(),
+            id: redirected_refund.id,
+        },
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.query_balance(refund_to.clone(), FEE_DENOM.to_string())
+            .unwrap(),
+        refund_to_balance + Uint128::new(1_000_000)
+    );
+    assert_eq!(
+        pending_refunds(&app, &contract_addr, &sender)
+            .pending_refunds
+            .len(),
+        1
+    );
+}
+
+#[test]
+#[cfg(not(feature = "test-tube"))]
+fn refund_destination_of_batch_and_cw20_transfers() {
+    let (mut app, contract_addr, accounts) = setup_bridge();
+    let signer = accounts[0].clone();
+    let sender = accounts[1].clone();
+    let refund_to = accounts[2].clone();
+
+    let batch_transfer = |address: &str| XRPLTransfer {
+        recipient: generate_xrpl_address(),
+        coin: coin(1_000_000, FEE_DENOM),
+        deliver_amount: None,
+        destination_tag: None,
+        refund_to: Some(address.to_string()),
+    };
+
+    // An invalid refund address of any transfer of the batch is rejected
+    app.execute(
+        sender.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::SendToXRPLBatch {
+            transfers: vec![batch_transfer("INVALID")],
+        },
+        &coins(1_000_000, FEE_DENOM),
+    )
+    .unwrap_err();
+
+    app.execute(
+        sender.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::SendToXRPLBatch {
+            transfers: vec![batch_transfer(refund_to.as_str())],
+        },
+        &coins(1_000_000, FEE_DENOM),
+    )
+    .unwrap();
+    reject_pending_operation(&mut app, &contract_addr, &signer);
+
+    // CW20 transfers take the refund address from the hook message
+    let cw20_addr = app
+        .create_cw20_token(
+            signer.clone(),
+            vec![Cw20Coin {
+                address: sender.to_string(),
+                amount: Uint128::new(100_000_000),
+            }],
+        )
+        .unwrap();
+
+    app.execute(
+        signer.clone(),
+        contract_addr.clone(),
+        &ExecuteMsg::RegisterCosmosToken {
+            denom: cw20_addr.to_string(),
+            decimals: 6,
+            sending_precision: 6,
+            max_holding_amount: Uint128::new(100_000_000),
+            bridging_fee: Uint128::zero(),
+            token_type: Some(CosmosTokenType::CW20),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let send_cw20 = |app: &mut MockApp, address: &str| {
+        app.execute(
+            sender.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: contract_addr.to_string(),
+                amount: Uint128::new(1_000_000),
+                msg: to_json_binary(&Cw20HookMsg::SendToXRPL {
+                    recipient: generate_xrpl_address(),
+                    destination_tag: None,
+                    refund_to: Some(address.to_string()),
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+    };
+
+    send_cw20(&mut app, "INVALID").unwrap_err();
+    send_cw20(&mut app, refund_to.as_str()).unwrap();
+    reject_pending_operation(&mut app, &contract_addr, &signer);
+
+    // Both refunds stay under the sender and go to the refund address
+    let query_pending_refunds = pending_refunds(&app, &contract_addr, &sender);
+    assert_eq!(query_pending_refunds.pending_refunds.len(), 2);
+    assert!(query_pending_refunds
+        .pending_refunds
+        .iter()
+        .all(|pending_refund| pending_refund.destination == refund_to));
+    assert!(query_pending_refunds
+        .pending_refunds
+        .iter()
+        .any(|pending_refund| pending_refund.coin == coin(1_000_000, cw20_addr.to_string())));
+}
@@ -4,7 +4,7 @@ use crate::error::ContractError;
 use crate::evidence::{Evidence, OperationResult, TransactionResult};
 use crate::msg::{PendingOperationsResponse, XRPLTokensResponse};
 use crate::operation::{Operation, OperationType};
-use crate::state::{  CosmosToken, XRPLToken};
+use crate::state::{  CosmosToken, CosmosTokenType, XRPLToken};
 use crate::tests::helper::{
     generate_hash, generate_xrpl_address, generate_xrpl_pub_key, MockApp, FEE_DENOM, TRUST_SET_LIMIT_AMOUNT
 };
@@ -29,6 +29,7 @@ fn register_cosmos_token() {
         cosmos_address: Addr::unchecked(signer),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
@@ -47,6 +48,7 @@ fn register_cosmos_token() {
                 token_factory_addr: token_factory_addr.clone(),
                 issue_token: true,
                 rate_limit_addr: None,osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -60,6 +62,7 @@ fn register_cosmos_token() {
             bridging_fee: Uint128::zero(),
             xrpl_currency: XRP_CURRENCY.to_string(),
             state: TokenState::Enabled,
+            token_type: CosmosTokenType::Native,
         },
         CosmosToken {
             denom: "denom2".to_string(),
@@ -69,6 +72,7 @@ fn register_cosmos_token() {
             bridging_fee: Uint128::zero(),
             xrpl_currency: XRP_CURRENCY.to_string(),
             state: TokenState::Enabled,
+            token_type: CosmosTokenType::Native,
         },
     ];
 
@@ -83,6 +87,7 @@ fn register_cosmos_token() {
                 sending_precision: token.sending_precision,
                 max_holding_amount: token.max_holding_amount,
                 bridging_fee: token.bridging_fee,
+                token_type: None,
             },
             &[],
         )
@@ -99,6 +104,7 @@ fn register_cosmos_token() {
             sending_precision: 6,
             max_holding_amount: Uint128::one(),
             bridging_fee: test_tokens[0].bridging_fee,
+            token_type: None,
         },
         &[],
     )
@@ -114,6 +120,7 @@ fn register_cosmos_token() {
             sending_precision: -17,
             max_holding_amount: Uint128::one(),
             bridging_fee: test_tokens[0].bridging_fee,
+            token_type: None,
         },
         &[],
     )
@@ -129,6 +136,7 @@ fn register_cosmos_token() {
             sending_precision: test_tokens[0].sending_precision,
             max_holding_amount: Uint128::one(),
             bridging_fee: test_tokens[0].bridging_fee,
+            token_type: None,
         },
         &[],
     )
@@ -144,6 +152,7 @@ fn register_cosmos_token() {
             sending_precision: test_tokens[0].sending_precision,
             max_holding_amount: test_tokens[0].max_holding_amount,
             bridging_fee: test_tokens[0].bridging_fee,
+            token_type: None,
         },
         &[],
     )
@@ -158,6 +167,7 @@ fn register_cosmos_token() {
             sending_precision: test_tokens[0].sending_precision,
             max_holding_amount: test_tokens[0].max_holding_amount,
             bridging_fee: test_tokens[0].bridging_fee,
+            token_type: None,
         },
         &[],
     )
@@ -173,6 +183,7 @@ fn register_cosmos_token() {
                     sending_precision: test_tokens[0].sending_precision,
                     max_holding_amount: test_tokens[0].max_holding_amount,
                     bridging_fee: test_tokens[0].bridging_fee,
+                    token_type: None,
                 },
                 &[],
                 
@@ -191,6 +202,7 @@ fn register_cosmos_token() {
                 sending_precision: test_tokens[0].sending_precision,
                 max_holding_amount: test_tokens[0].max_holding_amount,
                 bridging_fee: test_tokens[0].bridging_fee,
+                token_type: None,
             },
             &[],
             
@@ -259,6 +271,7 @@ fn register_xrpl_token() {
         cosmos_address: Addr::unchecked(signer),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let xrpl_bridge_address = generate_xrpl_address();
@@ -279,6 +292,7 @@ fn register_xrpl_token() {
                 token_factory_addr: token_factory_addr.clone(),
                 issue_token: true,
                 rate_limit_addr: None,osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -667,6 +681,7 @@ fn xrpl_token_registration_recovery() {
         cosmos_address: Addr::unchecked(signer),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let token_issuer = generate_xrpl_address();
@@ -690,6 +705,7 @@ fn xrpl_token_registration_recovery() {
                 token_factory_addr: token_factory_addr.clone(),
                 issue_token: true,
                 rate_limit_addr: None,osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();    
@@ -859,6 +875,7 @@ fn xrpl_token_registration_recovery() {
         query_pending_operations.operations[0],
         Operation {
             id: query_pending_operations.operations[0].id.clone(),
+            created_at: query_pending_operations.operations[0].created_at,
             version: 1,
             ticket_sequence: Some(
                 query_pending_operations.operations[0]
@@ -2,18 +2,18 @@ use crate::contract::{INITIAL_PROHIBITED_XRPL_ADDRESSES, XRP_ISSUER};
 use crate::error::ContractError;
 use crate::evidence::{Evidence, OperationResult, TransactionResult};
 use crate::msg::{
-    CosmosTokensResponse, ExecuteMsg, PendingOperationsResponse, PendingRefundsResponse,
-    ProcessedTxsResponse, QueryMsg, XRPLTokensResponse,
+    AvailableTicketsResponse, CosmosTokensResponse, ExecuteMsg, PendingOperationsResponse,
+    PendingRefundsResponse, ProcessedTxsResponse, QueryMsg, XRPLTokensResponse, XRPLTransfer,
 };
 use crate::operation::{Operation, OperationType};
-use crate::state::Config;
+use crate::state::{Config, CosmosToken};
 use crate::tests::helper::{
     generate_hash, generate_xrpl_address, generate_xrpl_pub_key, MockApp, FEE_DENOM,
     TRUST_SET_LIMIT_AMOUNT,
 };
 use crate::token::full_denom;
 use crate::{contract::XRP_CURRENCY, msg::InstantiateMsg, relayer::Relayer};
-use cosmwasm_std::{coin, coins, Addr, Uint128};
+use cosmwasm_std::{coin, coins, Addr, HexBinary, Uint128};
 
 use cw20::Cw20Coin;
 
@@ -43,6 +43,7 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos() {
             cosmos_address: Addr::unchecked(account),
             xrpl_address: xrpl_addresses[i].to_string(),
             xrpl_pub_key: xrpl_pub_keys[i].to_string(),
+            weight: None,
         });
     }
 
@@ -66,6 +67,7 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos() {
                 issue_token: true,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -154,6 +156,8 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos() {
                     amount: amount.clone(),
                     recipient: Addr::unchecked(receiver),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -206,6 +210,8 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos() {
                 amount: amount.clone(),
                 recipient: Addr::unchecked(receiver),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -230,6 +236,8 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos() {
                 amount: amount.clone(),
                 recipient: Addr::unchecked(contract_addr.clone()),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -252,6 +260,7 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos() {
                 issue_token: false,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -394,6 +403,8 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos() {
                 amount: amount.clone(),
                 recipient: Addr::unchecked(receiver),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -412,6 +423,8 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos() {
                 amount: amount.clone(),
                 recipient: Addr::unchecked(receiver),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -430,6 +443,8 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos() {
                 amount: Uint128::new(0),
                 recipient: Addr::unchecked(receiver),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -448,6 +463,8 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos() {
                 amount: amount.clone(),
                 recipient: Addr::unchecked(receiver),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -473,6 +490,8 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos() {
                 amount: amount.clone(),
                 recipient: Addr::unchecked(receiver),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -491,6 +510,8 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos() {
                 amount: amount.clone(),
                 recipient: Addr::unchecked(receiver),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -516,6 +537,8 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos() {
                 amount: amount.clone(),
                 recipient: Addr::unchecked(receiver),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -535,6 +558,8 @@ fn send_xrpl_originated_tokens_from_xrpl_to_cosmos() {
                 amount: new_amount.clone(),
                 recipient: Addr::unchecked(receiver),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -557,6 +582,7 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
         cosmos_address: Addr::unchecked(relayer_account),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let xrpl_receiver_address = generate_xrpl_address();
@@ -579,6 +605,7 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
                 issue_token: true,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -652,6 +679,7 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
             sending_precision: 5,
             max_holding_amount: Uint128::new(100000000000000000000),
             bridging_fee: Uint128::zero(),
+            token_type: None,
         },
         &[],
     )
@@ -668,6 +696,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
             &ExecuteMsg::SendToXRPL {
                 recipient: xrpl_receiver_address.clone(),
                 deliver_amount: Some(Uint128::new(100)),
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(amount_to_send.u128(), denom.clone()),
         )
@@ -687,6 +717,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
             &ExecuteMsg::SendToXRPL {
                 recipient: xrpl_receiver_address.clone(),
                 deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(10000000000000000010, denom.clone()), // Nothing is truncated, and after transforming into XRPL amount it will have more than 17 digits
         )
@@ -704,6 +736,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(amount_to_send.u128(), denom.clone()),
     )
@@ -761,6 +795,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
             max_amount: Some(amount_truncated_and_converted),
             sender: Addr::unchecked(sender),
             recipient: xrpl_receiver_address.clone(),
+            destination_tag: None,
+            refund_to: None,
         }
     );
 
@@ -933,6 +969,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(amount_to_send.u128(), denom.clone()),
     )
@@ -994,6 +1032,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
                     amount: amount_to_send_back.clone(),
                     recipient: Addr::unchecked(sender),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -1018,6 +1058,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
                     amount: amount_to_send_back.clone(),
                     recipient: Addr::unchecked(sender),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -1042,6 +1084,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
                     amount: amount_to_send_back.checked_sub(Uint128::one()).unwrap(),
                     recipient: Addr::unchecked(sender),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -1066,6 +1110,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
                 amount: amount_to_send_back.clone(),
                 recipient: Addr::unchecked(sender),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -1131,6 +1177,7 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
             sending_precision: 10,
             max_holding_amount: Uint128::new(200000000000000000000), //2e20
             bridging_fee: Uint128::zero(),
+            token_type: None,
         },
         &[],
     )
@@ -1146,6 +1193,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(amount_to_send.u128(), denom.clone()),
     )
@@ -1206,6 +1255,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
             max_amount: Some(amount_truncated_and_converted),
             sender: Addr::unchecked(sender),
             recipient: xrpl_receiver_address.clone(),
+            destination_tag: None,
+            refund_to: None,
         }
     );
 
@@ -1300,6 +1351,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(amount_to_send.u128(), denom.clone()),
     )
@@ -1361,6 +1414,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
                     amount: amount_to_send_back.clone(),
                     recipient: Addr::unchecked(sender),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -1385,6 +1440,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
                     amount: amount_to_send_back.clone(),
                     recipient: Addr::unchecked(sender),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -1409,6 +1466,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
                     amount: amount_to_send_back.checked_sub(Uint128::one()).unwrap(),
                     recipient: Addr::unchecked(sender),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -1433,6 +1492,8 @@ fn send_cosmos_originated_tokens_from_xrpl_to_cosmos() {
                 amount: amount_to_send_back.clone(),
                 recipient: Addr::unchecked(sender),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -1484,6 +1545,7 @@ fn send_from_cosmos_to_xrpl() {
         cosmos_address: Addr::unchecked(relayer_account),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let xrpl_base_fee = 10;
@@ -1506,6 +1568,7 @@ fn send_from_cosmos_to_xrpl() {
                 issue_token: true,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -1603,6 +1666,8 @@ fn send_from_cosmos_to_xrpl() {
                 amount: amount_to_send_xrp.clone(),
                 recipient: Addr::unchecked(sender),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -1625,6 +1690,8 @@ fn send_from_cosmos_to_xrpl() {
             &ExecuteMsg::SendToXRPL {
                 recipient: xrpl_receiver_address.clone(),
                 deliver_amount: Some(Uint128::one()),
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(amount_to_send_back.u128(), denom_xrp.clone()),
         )
@@ -1643,6 +1710,8 @@ fn send_from_cosmos_to_xrpl() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(amount_to_send_back.u128(), denom_xrp.clone()),
     )
@@ -1664,6 +1733,7 @@ fn send_from_cosmos_to_xrpl() {
         query_pending_operations.operations[0],
         Operation {
             id: query_pending_operations.operations[0].id.clone(),
+            created_at: query_pending_operations.operations[0].created_at,
             version: 1,
             ticket_sequence: Some(1),
             account_sequence: None,
@@ -1675,6 +1745,8 @@ fn send_from_cosmos_to_xrpl() {
                 max_amount: None,
                 sender: Addr::unchecked(sender),
                 recipient: xrpl_receiver_address.clone(),
+                destination_tag: None,
+                refund_to: None,
             },
             xrpl_base_fee,
         }
@@ -1688,6 +1760,8 @@ fn send_from_cosmos_to_xrpl() {
             &ExecuteMsg::SendToXRPL {
                 recipient: multisig_address,
                 deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(1, denom_xrp.clone()),
         )
@@ -1706,6 +1780,8 @@ fn send_from_cosmos_to_xrpl() {
             &ExecuteMsg::SendToXRPL {
                 recipient: INITIAL_PROHIBITED_XRPL_ADDRESSES[0].to_string(),
                 deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(1, denom_xrp.clone()),
         )
@@ -1789,6 +1865,8 @@ fn send_from_cosmos_to_xrpl() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(amount_to_send_back.u128(), denom_xrp.clone()),
     )
@@ -1901,6 +1979,8 @@ fn send_from_cosmos_to_xrpl() {
                 amount: amount_to_send.clone(),
                 recipient: Addr::unchecked(sender),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -1938,6 +2018,8 @@ fn send_from_cosmos_to_xrpl() {
             &ExecuteMsg::SendToXRPL {
                 recipient: xrpl_receiver_address.clone(),
                 deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
             },
             &vec![
                 coin(amount_to_send_back.u128(), denom_xrpl_origin_token.clone()),
@@ -1959,6 +2041,8 @@ fn send_from_cosmos_to_xrpl() {
             &ExecuteMsg::SendToXRPL {
                 recipient: "invalid_address".to_string(),
                 deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(amount_to_send_back.u128(), denom_xrpl_origin_token.clone()),
         )
@@ -1980,6 +2064,8 @@ fn send_from_cosmos_to_xrpl() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(amount_to_send_back.u128(), denom_xrpl_origin_token.clone()),
     )
@@ -2002,6 +2088,7 @@ fn send_from_cosmos_to_xrpl() {
         query_pending_operations.operations[0],
         Operation {
             id: query_pending_operations.operations[0].id.clone(),
+            created_at: query_pending_operations.operations[0].created_at,
             version: 1,
             ticket_sequence: Some(4),
             account_sequence: None,
@@ -2013,6 +2100,8 @@ fn send_from_cosmos_to_xrpl() {
                 max_amount: Some(amount_to_send_back),
                 sender: Addr::unchecked(sender),
                 recipient: xrpl_receiver_address.clone(),
+                destination_tag: None,
+                refund_to: None,
             },
             xrpl_base_fee
         }
@@ -2066,6 +2155,8 @@ fn send_from_cosmos_to_xrpl() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(amount_to_send_back.u128(), denom_xrpl_origin_token.clone()),
     )
@@ -2191,6 +2282,8 @@ fn send_from_cosmos_to_xrpl() {
             &ExecuteMsg::SendToXRPL {
                 recipient: xrpl_receiver_address.clone(),
                 deliver_amount: Some(max_amount.checked_add(Uint128::one()).unwrap()),
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(max_amount.u128(), denom_xrpl_origin_token.clone()),
         )
@@ -2209,6 +2302,8 @@ fn send_from_cosmos_to_xrpl() {
             &ExecuteMsg::SendToXRPL {
                 recipient: xrpl_receiver_address.clone(),
                 deliver_amount: Some(Uint128::new(99999999999999999)),
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(1000000000000000000, denom_xrpl_origin_token.clone()),
         )
@@ -2227,6 +2322,8 @@ fn send_from_cosmos_to_xrpl() {
             &ExecuteMsg::SendToXRPL {
                 recipient: xrpl_receiver_address.clone(),
                 deliver_amount: Some(Uint128::new(10000000000000000)),
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(10000000000000001, denom_xrpl_origin_token.clone()),
         )
@@ -2244,6 +2341,8 @@ fn send_from_cosmos_to_xrpl() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(max_amount.u128(), denom_xrpl_origin_token.clone()),
     )
@@ -2264,6 +2363,7 @@ fn send_from_cosmos_to_xrpl() {
         query_pending_operations.operations[0],
         Operation {
             id: query_pending_operations.operations[0].id.clone(),
+            created_at: query_pending_operations.operations[0].created_at,
             version: 1,
             ticket_sequence: Some(6),
             account_sequence: None,
@@ -2275,6 +2375,8 @@ fn send_from_cosmos_to_xrpl() {
                 max_amount: Some(max_amount),
                 sender: Addr::unchecked(sender),
                 recipient: xrpl_receiver_address.clone(),
+                destination_tag: None,
+                refund_to: None,
             },
             xrpl_base_fee
         }
@@ -2377,6 +2479,7 @@ fn send_from_cosmos_to_xrpl() {
             sending_precision: 5,
             max_holding_amount: Uint128::new(10000000),
             bridging_fee: Uint128::zero(),
+            token_type: None,
         },
         &[],
     )
@@ -2391,6 +2494,8 @@ fn send_from_cosmos_to_xrpl() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(amount_to_send.u128(), denom.clone()),
     )
@@ -2402,6 +2507,8 @@ fn send_from_cosmos_to_xrpl() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(amount_to_send.u128(), denom.clone()),
     )
@@ -2445,6 +2552,7 @@ fn send_from_cosmos_to_xrpl() {
         query_pending_operations.operations[0],
         Operation {
             id: query_pending_operations.operations[0].id.clone(),
+            created_at: query_pending_operations.operations[0].created_at,
             version: 1,
             ticket_sequence: Some(7),
             account_sequence: None,
@@ -2456,6 +2564,8 @@ fn send_from_cosmos_to_xrpl() {
                 max_amount: Some(amount.clone()),
                 sender: Addr::unchecked(sender),
                 recipient: xrpl_receiver_address.clone(),
+                destination_tag: None,
+                refund_to: None,
             },
             xrpl_base_fee
         }
@@ -2465,6 +2575,7 @@ fn send_from_cosmos_to_xrpl() {
         query_pending_operations.operations[1],
         Operation {
             id: query_pending_operations.operations[1].id.clone(),
+            created_at: query_pending_operations.operations[1].created_at,
             version: 1,
             ticket_sequence: Some(8),
             account_sequence: None,
@@ -2476,6 +2587,8 @@ fn send_from_cosmos_to_xrpl() {
                 max_amount: Some(amount.clone()),
                 sender: Addr::unchecked(sender),
                 recipient: xrpl_receiver_address,
+                destination_tag: None,
+                refund_to: None,
             },
             xrpl_base_fee
         }
@@ -2616,3 +2729,609 @@ fn send_from_cosmos_to_xrpl() {
 
     assert_eq!(query_processed_txs.processed_txs.len(), 7);
 }
+
+#[test]
+fn send_to_xrpl_batch() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[0];
+    let sender = &accounts[1];
+    let relayer_account = &accounts[2];
+    let relayer = Relayer {
+        cosmos_address: Addr::unchecked(relayer_account),
+        xrpl_address: generate_xrpl_address(),
+        xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
+    };
+
+    let bridge_xrpl_address = generate_xrpl_address();
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: vec![relayer],
+                evidence_threshold: 1,
+                used_ticket_sequence_threshold: 3,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: bridge_xrpl_address.clone(),
+                xrpl_base_fee: 10,
+                token_factory_addr: token_factory_addr.clone(),
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    // Allocate 4 tickets, which means that 3 transfers can be sent at most
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RecoverTickets {
+            account_sequence: 1,
+            number_of_tickets: Some(4),
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute(
+        Addr::unchecked(relayer_account),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveEvidence {
+            evidence: Evidence::XRPLTransactionResult {
+                tx_hash: Some(generate_hash()),
+                account_sequence: Some(1),
+                ticket_sequence: None,
+                transaction_result: TransactionResult::Accepted,
+                operation_result: Some(OperationResult::TicketsAllocation {
+                    tickets: Some((1..5).collect()),
+                }),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let subunit = "utest".to_string();
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::CreateCosmosToken {
+            subdenom: subunit.to_uppercase(),
+            initial_balances: vec![Cw20Coin {
+                address: sender.to_string(),
+                amount: Uint128::new(1_000_000_000),
+            }],
+        },
+        &coins(10_000_000u128, FEE_DENOM),
+    )
+    .unwrap();
+
+    let denom = full_denom(&token_factory_addr, &subunit.to_uppercase());
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RegisterCosmosToken {
+            denom: denom.clone(),
+            decimals: 6,
+            sending_precision: 6,
+            max_holding_amount: Uint128::new(1_000_000_000),
+            bridging_fee: Uint128::zero(),
+            token_type: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let recipients = vec![generate_xrpl_address(), generate_xrpl_address()];
+    let transfers: Vec<XRPLTransfer> = [1_000_000, 2_000_000, 3_000_000]
+        .iter()
+        .enumerate()
+        .map(|(i, amount)| XRPLTransfer {
+            recipient: recipients[i % 2].clone(),
+            coin: coin(*amount, denom.clone()),
+            deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
+        })
+        .collect();
+
+    // An empty batch is not allowed
+    let batch_error = app
+        .execute(
+            Addr::unchecked(sender),
+            contract_addr.clone(),
+            &ExecuteMsg::SendToXRPLBatch { transfers: vec![] },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(batch_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::EmptyTransferBatch {}.to_string().as_str()));
+
+    // Funds must match exactly the sum of all transfers
+    let batch_error = app
+        .execute(
+            Addr::unchecked(sender),
+            contract_addr.clone(),
+            &ExecuteMsg::SendToXRPLBatch {
+                transfers: transfers.clone(),
+            },
+            &coins(5_000_000, denom.clone()),
+        )
+        .unwrap_err();
+
+    assert!(batch_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::InvalidBatchFunds {}.to_string().as_str()));
+
+    // If there are not enough tickets for all transfers, the whole batch fails
+    let mut too_many_transfers = transfers.clone();
+    too_many_transfers.push(XRPLTransfer {
+        recipient: recipients[0].clone(),
+        coin: coin(1_000_000, denom.clone()),
+        deliver_amount: None,
+        destination_tag: None,
+        refund_to: None,
+    });
+    let batch_error = app
+        .execute(
+            Addr::unchecked(sender),
+            contract_addr.clone(),
+            &ExecuteMsg::SendToXRPLBatch {
+                transfers: too_many_transfers,
+            },
+            &coins(7_000_000, denom.clone()),
+        )
+        .unwrap_err();
+
+    assert!(batch_error.root_cause().to_string().contains(
+        ContractError::NotEnoughTickets {
+            required: 4,
+            available: 3
+        }
+        .to_string()
+        .as_str()
+    ));
+
+    // A transfer that is not valid makes the whole batch fail
+    let mut invalid_transfers = transfers.clone();
+    invalid_transfers[2].recipient = "invalid_address".to_string();
+    app.execute(
+        Addr::unchecked(sender),
+        contract_addr.clone(),
+        &ExecuteMsg::SendToXRPLBatch {
+            transfers: invalid_transfers,
+        },
+        &coins(6_000_000, denom.clone()),
+    )
+    .unwrap_err();
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(query_pending_operations.operations.is_empty());
+
+    app.execute(
+        Addr::unchecked(sender),
+        contract_addr.clone(),
+        &ExecuteMsg::SendToXRPLBatch {
+            transfers: transfers.clone(),
+        },
+        &coins(6_000_000, denom.clone()),
+    )
+    .unwrap();
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    let cosmos_token: CosmosToken = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::CosmosToken { key: denom.clone() },
+        )
+        .unwrap();
+
+    assert_eq!(query_pending_operations.operations.len(), 3);
+    for (i, operation) in query_pending_operations.operations.iter().enumerate() {
+        // 6 decimals to 15 decimals
+        let amount = transfers[i].coin.amount * Uint128::new(1_000_000_000);
+        assert_eq!(operation.ticket_sequence, Some(i as u64 + 1));
+        assert_eq!(
+            operation.operation_type,
+            OperationType::CosmosToXRPLTransfer {
+                issuer: bridge_xrpl_address.clone(),
+                currency: cosmos_token.xrpl_currency.clone(),
+                amount,
+                max_amount: Some(amount),
+                sender: Addr::unchecked(sender),
+                recipient: transfers[i].recipient.clone(),
+                destination_tag: None,
+                refund_to: None,
+            }
+        );
+    }
+
+    // Only the reserved ticket is left
+    let query_available_tickets: AvailableTicketsResponse = app
+        .query(contract_addr.clone(), &QueryMsg::AvailableTickets {})
+        .unwrap();
+    assert_eq!(query_available_tickets.tickets, vec![4]);
+
+    let contract_balance = app.query_balance(contract_addr, denom).unwrap();
+    assert_eq!(contract_balance, Uint128::new(6_000_000));
+}
+
+#[test]
+fn send_to_xrpl_with_destination_tag() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[0];
+    let sender = &accounts[1];
+    let relayer_account = &accounts[2];
+    let relayer = Relayer {
+        cosmos_address: Addr::unchecked(relayer_account),
+        xrpl_address: generate_xrpl_address(),
+        xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
+    };
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: vec![relayer],
+                evidence_threshold: 1,
+                used_ticket_sequence_threshold: 3,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: generate_xrpl_address(),
+                xrpl_base_fee: 10,
+                token_factory_addr: token_factory_addr.clone(),
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RecoverTickets {
+            account_sequence: 1,
+            number_of_tickets: Some(4),
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute(
+        Addr::unchecked(relayer_account),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveEvidence {
+            evidence: Evidence::XRPLTransactionResult {
+                tx_hash: Some(generate_hash()),
+                account_sequence: Some(1),
+                ticket_sequence: None,
+                transaction_result: TransactionResult::Accepted,
+                operation_result: Some(OperationResult::TicketsAllocation {
+                    tickets: Some((1..5).collect()),
+                }),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let subunit = "utest".to_string();
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::CreateCosmosToken {
+            subdenom: subunit.to_uppercase(),
+            initial_balances: vec![Cw20Coin {
+                address: sender.to_string(),
+                amount: Uint128::new(1_000_000_000),
+            }],
+        },
+        &coins(10_000_000u128, FEE_DENOM),
+    )
+    .unwrap();
+
+    let denom = full_denom(&token_factory_addr, &subunit.to_uppercase());
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RegisterCosmosToken {
+            denom: denom.clone(),
+            decimals: 6,
+            sending_precision: 6,
+            max_holding_amount: Uint128::new(1_000_000_000),
+            bridging_fee: Uint128::zero(),
+            token_type: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Classic address with a destination tag
+    let classic_address = "r9cZA1mLK5R5Am25ArfXFmqgNwjZgnfk59".to_string();
+    app.execute(
+        Addr::unchecked(sender),
+        contract_addr.clone(),
+        &ExecuteMsg::SendToXRPL {
+            recipient: classic_address.clone(),
+            deliver_amount: None,
+            destination_tag: Some(12345),
+            refund_to: None,
+        },
+        &coins(1_000_000, denom.clone()),
+    )
+    .unwrap();
+
+    // X-address that encodes the destination tag 14
+    app.execute(
+        Addr::unchecked(sender),
+        contract_addr.clone(),
+        &ExecuteMsg::SendToXRPL {
+            recipient: "X7AcgcsBL6XDcUb289X4mJ8djcdyKaGo2K5VpXpmCqbV2gS".to_string(),
+            deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
+        },
+        &coins(1_000_000, denom.clone()),
+    )
+    .unwrap();
+
+    // X-address with a different destination tag than the one provided
+    let send_error = app
+        .execute(
+            Addr::unchecked(sender),
+            contract_addr.clone(),
+            &ExecuteMsg::SendToXRPL {
+                recipient: "X7AcgcsBL6XDcUb289X4mJ8djcdyKaGo2K5VpXpmCqbV2gS".to_string(),
+                deliver_amount: None,
+                destination_tag: Some(15),
+                refund_to: None,
+            },
+            &coins(1_000_000, denom.clone()),
+        )
+        .unwrap_err();
+
+    assert!(send_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::DestinationTagMismatch {}.to_string().as_str()));
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(query_pending_operations.operations.len(), 2);
+    for (operation, expected_tag) in query_pending_operations
+        .operations
+        .iter()
+        .zip([Some(12345), Some(14)])
+    {
+        match &operation.operation_type {
+            OperationType::CosmosToXRPLTransfer {
+                recipient,
+                destination_tag,
+                ..
+            } => {
+                assert_eq!(*recipient, classic_address);
+                assert_eq!(*destination_tag, expected_tag);
+            }
+            _ => panic!("Unexpected operation type"),
+        }
+    }
+
+    // The bridge is on mainnet so testnet X-addresses are rejected
+    let send_error = app
+        .execute(
+            Addr::unchecked(sender),
+            contract_addr.clone(),
+            &ExecuteMsg::SendToXRPL {
+                recipient: "T719a5UwUCnEs54UsxG9CJYYDhwmFCvbJNZbi37gBGkRkbE".to_string(),
+                deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
+            },
+            &coins(1_000_000, denom.clone()),
+        )
+        .unwrap_err();
+
+    assert!(send_error.root_cause().to_string().contains(
+        ContractError::XAddressWrongNetwork {
+            address: "T719a5UwUCnEs54UsxG9CJYYDhwmFCvbJNZbi37gBGkRkbE".to_string()
+        }
+        .to_string()
+        .as_str()
+    ));
+
+    // Prohibiting the X-address prohibits the classic address too
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::UpdateProhibitedXRPLAddresses {
+            prohibited_xrpl_addresses: vec![
+                "X7AcgcsBL6XDcUb289X4mJ8djcdyKaB5hJDWMArnXr61cqZ".to_string()
+            ],
+        },
+        &[],
+    )
+    .unwrap();
+
+    let send_error = app
+        .execute(
+            Addr::unchecked(sender),
+            contract_addr,
+            &ExecuteMsg::SendToXRPL {
+                recipient: classic_address,
+                deliver_amount: None,
+                destination_tag: Some(1),
+                refund_to: None,
+            },
+            &coins(1_000_000, denom),
+        )
+        .unwrap_err();
+
+    assert!(send_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::ProhibitedAddress {}.to_string().as_str()));
+}
+
+#[test]
+fn xrpl_to_cosmos_transfer_source_tag_and_memos() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+        ("account3", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[0];
+    let receiver = &accounts[1];
+    let relayer_accounts = [&accounts[2], &accounts[3]];
+    let relayers: Vec<Relayer> = relayer_accounts
+        .iter()
+        .map(|account| Relayer {
+            cosmos_address: Addr::unchecked(*account),
+            xrpl_address: generate_xrpl_address(),
+            xrpl_pub_key: generate_xrpl_pub_key(),
+            weight: None,
+        })
+        .collect();
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers,
+                evidence_threshold: 2,
+                used_ticket_sequence_threshold: 50,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: generate_xrpl_address(),
+                xrpl_base_fee: 10,
+                token_factory_addr,
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    let tx_hash = generate_hash();
+    let evidence = |source_tag: u32| Evidence::XRPLToCosmosTransfer {
+        tx_hash: tx_hash.clone(),
+        issuer: XRP_ISSUER.to_string(),
+        currency: XRP_CURRENCY.to_string(),
+        amount: Uint128::new(1_000_000),
+        recipient: Addr::unchecked(receiver),
+        memo: None,
+        source_tag: Some(source_tag),
+        raw_memos: Some(vec![
+            HexBinary::from(b"invoice-1".to_vec()),
+            HexBinary::from(b"invoice-2".to_vec()),
+        ]),
+    };
+
+    // The source tag is part of the evidence so relayers must agree on it
+    for (relayer_account, source_tag) in relayer_accounts.iter().zip([7, 8]) {
+        let result = app
+            .execute(
+                Addr::unchecked(*relayer_account),
+                contract_addr.clone(),
+                &ExecuteMsg::SaveEvidence {
+                    evidence: evidence(source_tag),
+                },
+                &[],
+            )
+            .unwrap();
+
+        let save_evidence_event = result.events.iter().find(|e| e.ty == "wasm").unwrap();
+        assert!(!save_evidence_event
+            .attributes
+            .iter()
+            .any(|a| a.key == "source_tag" || a.key == "raw_memos"));
+    }
+
+    // Once the threshold is reached, the source tag and the memos are added to the response
+    let result = app
+        .execute(
+            Addr::unchecked(relayer_accounts[1]),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveEvidence {
+                evidence: evidence(7),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let save_evidence_event = result.events.iter().find(|e| e.ty == "wasm").unwrap();
+    let attribute = |key: &str| {
+        save_evidence_event
+            .attributes
+            .iter()
+            .find(|a| a.key == key)
+            .unwrap()
+            .value
+            .clone()
+    };
+    assert_eq!(attribute("threshold_reached"), "true");
+    assert_eq!(attribute("source_tag"), "7");
+    assert_eq!(
+        attribute("raw_memos"),
+        format!(
+            "{},{}",
+            HexBinary::from(b"invoice-1".to_vec()).to_hex(),
+            HexBinary::from(b"invoice-2".to_vec()).to_hex()
+        )
+    );
+}
@@ -6,8 +6,8 @@ use crate::operation::{Operation, OperationType};
 use crate::signatures::Signature;
 use crate::state::{ TokenState, XRPLToken};
 use crate::tests::helper::{
-    generate_hash, generate_xrpl_address, generate_xrpl_pub_key, MockApp, FEE_DENOM,
-    TRUST_SET_LIMIT_AMOUNT,
+    generate_hash, generate_xrpl_address, generate_xrpl_pub_key, generate_xrpl_signer, MockApp,
+    FEE_DENOM, TRUST_SET_LIMIT_AMOUNT,
 };
 use crate::token::full_denom;
 use crate::{msg::InstantiateMsg, relayer::Relayer};
@@ -25,9 +25,7 @@ fn ticket_recovery() {
     let accounts_number = accounts.len();
 
     let signer = &accounts[accounts_number - 1];
-    let xrpl_addresses = vec![generate_xrpl_address(), generate_xrpl_address()];
-
-    let xrpl_pub_keys = vec![generate_xrpl_pub_key(), generate_xrpl_pub_key()];
+    let xrpl_signers = vec![generate_xrpl_signer(), generate_xrpl_signer()];
 
     let mut relayer_accounts = vec![];
     let mut relayers = vec![];
@@ -36,12 +34,14 @@ fn ticket_recovery() {
         relayer_accounts.push(accounts[i].to_string());
         relayers.push(Relayer {
             cosmos_address: Addr::unchecked(&accounts[i]),
-            xrpl_address: xrpl_addresses[i as usize].to_string(),
-            xrpl_pub_key: xrpl_pub_keys[i as usize].to_string(),
+            xrpl_address: xrpl_signers[i].xrpl_address.to_string(),
+            xrpl_pub_key: xrpl_signers[i].xrpl_pub_key.to_string(),
+            weight: None,
         });
     }
 
     let xrpl_base_fee = 10;
+    let bridge_xrpl_address = generate_xrpl_address();
     let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
     let contract_addr = app
         .create_bridge(
@@ -52,11 +52,12 @@ fn ticket_recovery() {
                 evidence_threshold: 2,
                 used_ticket_sequence_threshold: 4,
                 trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
-                bridge_xrpl_address: generate_xrpl_address(),
+                bridge_xrpl_address: bridge_xrpl_address.clone(),
                 xrpl_base_fee,
                 token_factory_addr: token_factory_addr.clone(),
                 issue_token: true,
                 rate_limit_addr: None,osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -190,6 +191,7 @@ fn ticket_recovery() {
         query_pending_operations.operations,
         [Operation {
             id: query_pending_operations.operations[0].id.clone(),
+            created_at: query_pending_operations.operations[0].created_at,
             version: 1,
             ticket_sequence: None,
             account_sequence: Some(account_sequence),
@@ -201,7 +203,16 @@ fn ticket_recovery() {
 
     let tx_hash = generate_hash();
     let tickets = vec![1, 2, 3, 4, 5];
-    let correct_signature_example = "3045022100DFA01DA5D6C9877F9DAA59A06032247F3D7ED6444EAD5C90A3AC33CCB7F19B3F02204D8D50E4D085BB1BC9DFB8281B8F35BDAEB7C74AE4B825F8CAE1217CFBDF4EA1".to_string();
+    let signatures: Vec<String> = xrpl_signers
+        .iter()
+        .map(|xrpl_signer| {
+            xrpl_signer.sign_operation(
+                &query_pending_operations.operations[0],
+                &bridge_xrpl_address,
+                2,
+            )
+        })
+        .collect();
 
     // Trying to relay the operation with a different sequence number than the one in pending operation should fail.
     let relayer_error = app
@@ -247,6 +258,35 @@ fn ticket_recovery() {
             .as_str()
     ));
 
+    // Once verification is enforced, providing a signature that was not produced by the relayer's key should error
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::UpdateSignatureVerification {
+            enforce_signature_verification: true,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let signature_error = app
+        .execute(
+            Addr::unchecked(&relayer_accounts[0]),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveSignature {
+                operation_id: account_sequence,
+                operation_version: 1,
+                signature: signatures[1].clone(),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(signature_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::InvalidSignature {}.to_string().as_str()));
+
     // Provide signatures for the operation for each relayer
     app.execute(
         Addr::unchecked(&relayer_accounts[0]),
@@ -254,7 +294,7 @@ fn ticket_recovery() {
         &ExecuteMsg::SaveSignature {
             operation_id: account_sequence,
             operation_version: 1,
-            signature: correct_signature_example.clone(),
+            signature: signatures[0].clone(),
         },
         &[],
         
@@ -269,7 +309,7 @@ fn ticket_recovery() {
             &ExecuteMsg::SaveSignature {
                 operation_id: account_sequence,
                 operation_version: 1,
-                signature: correct_signature_example.clone(),
+                signature: signatures[0].clone(),
             },
             &[],
             
@@ -290,7 +330,7 @@ fn ticket_recovery() {
             &ExecuteMsg::SaveSignature {
                 operation_id: account_sequence + 1,
                 operation_version: 1,
-                signature: correct_signature_example.clone(),
+                signature: signatures[0].clone(),
             },
             &[],
             
@@ -311,7 +351,7 @@ fn ticket_recovery() {
             &ExecuteMsg::SaveSignature {
                 operation_id: account_sequence,
                 operation_version: 2,
-                signature: correct_signature_example.clone(),
+                signature: signatures[0].clone(),
             },
             &[],
             
@@ -330,7 +370,7 @@ fn ticket_recovery() {
         &ExecuteMsg::SaveSignature {
             operation_id: account_sequence,
             operation_version: 1,
-            signature: correct_signature_example.clone(),
+            signature: signatures[1].clone(),
         },
         &[],
         
@@ -353,11 +393,11 @@ fn ticket_recovery() {
         query_pending_operation.operations[0].signatures,
         vec![
             Signature {
-                signature: correct_signature_example.clone(),
+                signature: signatures[0].clone(),
                 relayer_cosmos_address: Addr::unchecked(relayers[0].cosmos_address.clone()),
             },
             Signature {
-                signature: correct_signature_example.clone(),
+                signature: signatures[1].clone(),
                 relayer_cosmos_address: Addr::unchecked(relayers[1].cosmos_address.clone()),
             }
         ]
@@ -441,13 +481,33 @@ fn ticket_recovery() {
     .unwrap();
 
     // We provide the signatures again
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let signatures: Vec<String> = xrpl_signers
+        .iter()
+        .map(|xrpl_signer| {
+            xrpl_signer.sign_operation(
+                &query_pending_operations.operations[0],
+                &bridge_xrpl_address,
+                2,
+            )
+        })
+        .collect();
+
     app.execute(
         Addr::unchecked(&relayer_accounts[0]),
         contract_addr.clone(),
         &ExecuteMsg::SaveSignature {
             operation_id: account_sequence,
             operation_version: 1,
-            signature: correct_signature_example.clone(),
+            signature: signatures[0].clone(),
         },
         &[],
         
@@ -460,7 +520,7 @@ fn ticket_recovery() {
         &ExecuteMsg::SaveSignature {
             operation_id: account_sequence,
             operation_version: 1,
-            signature: correct_signature_example.clone(),
+            signature: signatures[1].clone(),
         },
         &[],
         
@@ -633,6 +693,7 @@ fn rejected_ticket_allocation_with_no_tickets_left() {
         cosmos_address: Addr::unchecked(signer),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let test_tokens = vec![
@@ -673,6 +734,7 @@ fn rejected_ticket_allocation_with_no_tickets_left() {
                 token_factory_addr: token_factory_addr.clone(),
                 issue_token: true,
                 rate_limit_addr: None,osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -764,6 +826,7 @@ fn rejected_ticket_allocation_with_no_tickets_left() {
         query_pending_operations.operations,
         [Operation {
             id: query_pending_operations.operations[0].id.clone(),
+            created_at: query_pending_operations.operations[0].created_at,
             version: 1,
             ticket_sequence: Some(3),
             account_sequence: None,
@@ -833,6 +896,7 @@ fn ticket_return_invalid_transactions() {
         cosmos_address: Addr::unchecked(relayer_account),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let xrpl_receiver_address = generate_xrpl_address();
@@ -854,6 +918,7 @@ fn ticket_return_invalid_transactions() {
                 token_factory_addr: token_factory_addr.clone(),
                 issue_token: true,
                 rate_limit_addr: None,osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -923,6 +988,7 @@ fn ticket_return_invalid_transactions() {
             sending_precision: 6,
             max_holding_amount: Uint128::new(10000000),
             bridging_fee: Uint128::zero(),
+            token_type: None,
         },
         &[],
         
@@ -936,6 +1002,8 @@ fn ticket_return_invalid_transactions() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(1, denom.clone()),
         
@@ -982,6 +1050,8 @@ fn ticket_return_invalid_transactions() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(1, denom.clone()),
         
@@ -1006,3 +1076,154 @@ fn ticket_return_invalid_transactions() {
             .unwrap()
     );
 }
+
+#[test]
+fn signature_verification_is_log_only_until_enforced() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[2];
+    let xrpl_signers = vec![generate_xrpl_signer(), generate_xrpl_signer()];
+    let relayers: Vec<Relayer> = accounts[0..2]
+        .iter()
+        .zip(xrpl_signers.iter())
+        .map(|(account, xrpl_signer)| Relayer {
+            cosmos_address: Addr::unchecked(account),
+            xrpl_address: xrpl_signer.xrpl_address.clone(),
+            xrpl_pub_key: xrpl_signer.xrpl_pub_key.clone(),
+            weight: None,
+        })
+        .collect();
+
+    let bridge_xrpl_address = generate_xrpl_address();
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: relayers.clone(),
+                evidence_threshold: 2,
+                used_ticket_sequence_threshold: 4,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: bridge_xrpl_address.clone(),
+                xrpl_base_fee: 10,
+                token_factory_addr,
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RecoverTickets {
+            account_sequence: 1,
+            number_of_tickets: Some(5),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let operation = &query_pending_operations.operations[0];
+
+    // Signatures made with another relayer's key don't match, but they are only reported until verification is enforced
+    let result = app
+        .execute(
+            relayers[0].cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveSignature {
+                operation_id: 1,
+                operation_version: 1,
+                signature: xrpl_signers[1].sign_operation(operation, &bridge_xrpl_address, 2),
+            },
+            &[],
+        )
+        .unwrap();
+
+    assert!(result
+        .events
+        .iter()
+        .any(|e| e.ty == "wasm-unverified_signature"
+            && e.attributes
+                .iter()
+                .any(|a| a.key == "relayer" && a.value == relayers[0].cosmos_address.as_str())));
+
+    // Only the owner can enforce the verification
+    let update_error = app
+        .execute(
+            relayers[0].cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::UpdateSignatureVerification {
+                enforce_signature_verification: true,
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(update_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::UnauthorizedSender {}.to_string().as_str()));
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::UpdateSignatureVerification {
+            enforce_signature_verification: true,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let signature_error = app
+        .execute(
+            relayers[1].cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveSignature {
+                operation_id: 1,
+                operation_version: 1,
+                signature: xrpl_signers[0].sign_operation(operation, &bridge_xrpl_address, 2),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(signature_error
+        .root_cause()
+        .to_string()
+        .contains(ContractError::InvalidSignature {}.to_string().as_str()));
+
+    let result = app
+        .execute(
+            relayers[1].cosmos_address.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveSignature {
+                operation_id: 1,
+                operation_version: 1,
+                signature: xrpl_signers[1].sign_operation(operation, &bridge_xrpl_address, 2),
+            },
+            &[],
+        )
+        .unwrap();
+
+    assert!(!result
+        .events
+        .iter()
+        .any(|e| e.ty == "wasm-unverified_signature"));
+}
@@ -39,6 +39,7 @@ fn token_update() {
             cosmos_address: Addr::unchecked(&accounts[i]),
             xrpl_address: xrpl_addresses[i as usize].to_string(),
             xrpl_pub_key: xrpl_pub_keys[i as usize].to_string(),
+            weight: None,
         });
     }
 
@@ -60,6 +61,7 @@ fn token_update() {
                 issue_token: true,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -250,6 +252,8 @@ fn token_update() {
                 amount: Uint128::one(),
                 recipient: Addr::unchecked(signer),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -285,6 +289,8 @@ fn token_update() {
                     amount: Uint128::one(),
                     recipient: Addr::unchecked(signer),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -346,6 +352,8 @@ fn token_update() {
                 amount: Uint128::one(),
                 recipient: Addr::unchecked(signer),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -381,6 +389,8 @@ fn token_update() {
             &ExecuteMsg::SendToXRPL {
                 recipient: generate_xrpl_address(),
                 deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(1, xrpl_token_denom.clone()),
         )
@@ -402,6 +412,7 @@ fn token_update() {
             sending_precision,
             max_holding_amount,
             bridging_fee,
+            token_type: None,
         },
         &[],
     )
@@ -452,6 +463,8 @@ fn token_update() {
             &ExecuteMsg::SendToXRPL {
                 recipient: generate_xrpl_address(),
                 deliver_amount: None,
+                destination_tag: None,
+                refund_to: None,
             },
             &coins(1, denom.clone()),
         )
@@ -543,6 +556,8 @@ fn token_update() {
                 amount: Uint128::one(),
                 recipient: Addr::unchecked(signer),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -577,6 +592,8 @@ fn token_update() {
                     amount: Uint128::one(),
                     recipient: Addr::unchecked(signer),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -616,6 +633,8 @@ fn token_update() {
                 amount: Uint128::one(),
                 recipient: Addr::unchecked(signer),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -641,6 +660,8 @@ fn token_update() {
                 amount: Uint128::new(amount_to_send),
                 recipient: Addr::unchecked(signer),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -674,6 +695,8 @@ fn token_update() {
                 amount: Uint128::new(amount_to_send),
                 recipient: Addr::unchecked(signer),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -741,6 +764,8 @@ fn token_update() {
                 amount: Uint128::new(amount_to_send),
                 recipient: Addr::unchecked(signer),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -776,6 +801,8 @@ fn token_update() {
                     amount: Uint128::new(amount_to_send),
                     recipient: Addr::unchecked(signer),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -817,6 +844,8 @@ fn token_update() {
                     amount: Uint128::new(amount_to_send),
                     recipient: Addr::unchecked(signer),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -857,6 +886,8 @@ fn token_update() {
                 amount: Uint128::new(amount_to_send),
                 recipient: Addr::unchecked(signer),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -888,6 +919,8 @@ fn token_update() {
         &ExecuteMsg::SendToXRPL {
             recipient: generate_xrpl_address(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(current_max_amount, denom.clone()),
     )
@@ -1032,6 +1065,8 @@ fn token_update() {
                 amount: Uint128::new(amount_to_send),
                 recipient: Addr::unchecked(signer),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -1069,6 +1104,8 @@ fn token_update() {
                     amount: Uint128::new(amount_to_send),
                     recipient: Addr::unchecked(signer),
                     memo: None,
+                    source_tag: None,
+                    raw_memos: None,
                 },
             },
             &[],
@@ -1113,6 +1150,8 @@ fn token_update() {
                 amount: Uint128::new(amount_to_send),
                 recipient: Addr::unchecked(signer),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
@@ -1150,6 +1189,7 @@ fn test_burning_rate_and_commission_fee_cosmos_tokens() {
         cosmos_address: Addr::unchecked(relayer_account),
         xrpl_address: generate_xrpl_address(),
         xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
     };
 
     let xrpl_receiver_address = generate_xrpl_address();
@@ -1173,6 +1213,7 @@ fn test_burning_rate_and_commission_fee_cosmos_tokens() {
                 issue_token: true,
                 rate_limit_addr: None,
                 osor_entry_point: None,
+                xrpl_network: None,
             },
         )
         .unwrap();
@@ -1255,6 +1296,7 @@ fn test_burning_rate_and_commission_fee_cosmos_tokens() {
             sending_precision: 6,
             max_holding_amount: Uint128::new(1000000000),
             bridging_fee: Uint128::zero(),
+            token_type: None,
         },
         &[],
     )
@@ -1272,6 +1314,8 @@ fn test_burning_rate_and_commission_fee_cosmos_tokens() {
         &ExecuteMsg::SendToXRPL {
             recipient: xrpl_receiver_address.clone(),
             deliver_amount: None,
+            destination_tag: None,
+            refund_to: None,
         },
         &coins(100, denom.clone()),
     )
@@ -1348,6 +1392,8 @@ fn test_burning_rate_and_commission_fee_cosmos_tokens() {
                 amount: amount_to_send_back.clone(),
                 recipient: Addr::unchecked(Addr::unchecked(sender)),
                 memo: None,
+                source_tag: None,
+                raw_memos: None,
             },
         },
         &[],
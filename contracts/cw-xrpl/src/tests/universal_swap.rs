@@ -0,0 +1,626 @@
+use cosmwasm_std::{coin, coins, to_json_binary, to_json_string, Addr, Uint128};
+use cw20::Cw20Coin;
+use skip::entry_point::ExecuteMsg as EntryPointExecuteMsg;
+
+use crate::contract::{XRP_CURRENCY, XRP_ISSUER, XRP_SYMBOL};
+use crate::error::ContractError;
+use crate::evidence::Evidence;
+use crate::memo::{BridgeMemo, MemoAction, MEMO_VERSION};
+use crate::msg::{
+    ExecuteMsg, InstantiateMsg, PendingRefund, PendingRefundsResponse,
+    PendingUniversalSwapsResponse, QueryMsg,
+};
+use crate::relayer::Relayer;
+use crate::state::CosmosToken;
+use crate::tests::helper::{
+    generate_hash, generate_xrpl_address, generate_xrpl_pub_key, MockApp, FEE_DENOM,
+    TRUST_SET_LIMIT_AMOUNT,
+};
+use crate::token::full_denom;
+use crate::universal_swap::UniversalSwapFallback;
+
+fn swap_memo(fallback: Option<UniversalSwapFallback>) -> String {
+    to_json_string(&BridgeMemo {
+        version: MEMO_VERSION,
+        action: MemoAction::Swap {
+            memo: "universal_swap_memo".to_string(),
+            fallback,
+        },
+    })
+    .unwrap()
+}
+
+#[test]
+#[cfg(not(feature = "test-tube"))]
+fn failed_universal_swap_refunds_recipient() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[0];
+    let relayer_account = &accounts[1];
+    let receiver = &accounts[2];
+
+    let relayer = Relayer {
+        cosmos_address: Addr::unchecked(relayer_account),
+        xrpl_address: generate_xrpl_address(),
+        xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
+    };
+
+    let bridge_xrpl_address = generate_xrpl_address();
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+    let entry_point_addr = app
+        .create_mock_entry_point(Addr::unchecked(signer), true)
+        .unwrap();
+
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: vec![relayer],
+                evidence_threshold: 1,
+                used_ticket_sequence_threshold: 50,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: bridge_xrpl_address.clone(),
+                xrpl_base_fee: 10,
+                token_factory_addr: token_factory_addr.clone(),
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: Some(entry_point_addr),
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    // Bridge XRP with a memo so that the bridge tries a universal swap that is going to fail
+    let amount = Uint128::new(1_000_000);
+    let result = app
+        .execute(
+            Addr::unchecked(relayer_account),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveEvidence {
+                evidence: Evidence::XRPLToCosmosTransfer {
+                    tx_hash: generate_hash(),
+                    issuer: XRP_ISSUER.to_string(),
+                    currency: XRP_CURRENCY.to_string(),
+                    amount,
+                    recipient: Addr::unchecked(receiver),
+                    memo: Some(swap_memo(None)),
+                    source_tag: None,
+                    raw_memos: None,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+    // The minted tokens must be sent to the recipient instead of being stuck in the bridge
+    let xrp_denom = full_denom(&token_factory_addr, XRP_SYMBOL);
+    let receiver_balance = app
+        .query_balance(Addr::unchecked(receiver), xrp_denom.clone())
+        .unwrap();
+    assert_eq!(receiver_balance.to_string(), amount.to_string());
+
+    let contract_balance = app
+        .query_balance(contract_addr.clone(), xrp_denom.clone())
+        .unwrap();
+    assert_eq!(contract_balance.to_string(), "0".to_string());
+
+    assert!(result
+        .events
+        .iter()
+        .any(|e| e.ty == "wasm-refund_universal_swap"
+            && e.attributes
+                .iter()
+                .any(|a| a.key == "recipient" && a.value == receiver.to_string())
+            && e.attributes
+                .iter()
+                .any(|a| a.key == "amount" && a.value == format!("{}{}", amount, xrp_denom))));
+
+    // Now the same flow for a Cosmos originated token that the bridge is holding in escrow
+    let subunit = "utest".to_string();
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::CreateCosmosToken {
+            subdenom: subunit.to_uppercase(),
+            initial_balances: vec![Cw20Coin {
+                address: signer.to_string(),
+                amount: Uint128::new(100_000_000),
+            }],
+        },
+        &coins(10_000_000u128, FEE_DENOM),
+    )
+    .unwrap();
+
+    let denom = full_denom(&token_factory_addr, &subunit.to_uppercase());
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RegisterCosmosToken {
+            denom: denom.clone(),
+            decimals: 6,
+            sending_precision: 6,
+            max_holding_amount: Uint128::new(100_000_000),
+            bridging_fee: Uint128::zero(),
+            token_type: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Simulate the escrowed amount of a previous Cosmos -> XRPL transfer
+    app.send_coins(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &coins(1_000_000, denom.clone()),
+    )
+    .unwrap();
+
+    let cosmos_token: CosmosToken = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::CosmosToken { key: denom.clone() },
+        )
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(relayer_account),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveEvidence {
+            evidence: Evidence::XRPLToCosmosTransfer {
+                tx_hash: generate_hash(),
+                issuer: bridge_xrpl_address,
+                currency: cosmos_token.xrpl_currency,
+                // 1e15 in XRPL decimals is 1e6 in Cosmos decimals
+                amount: Uint128::new(1_000_000_000_000_000),
+                recipient: Addr::unchecked(receiver),
+                memo: Some(swap_memo(None)),
+                source_tag: None,
+                raw_memos: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let receiver_balance = app
+        .query_balance(Addr::unchecked(receiver), denom.clone())
+        .unwrap();
+    assert_eq!(receiver_balance.to_string(), "1000000".to_string());
+
+    let contract_balance = app.query_balance(contract_addr, denom).unwrap();
+    assert_eq!(contract_balance.to_string(), "0".to_string());
+}
+
+#[test]
+#[cfg(not(feature = "test-tube"))]
+fn pending_universal_swaps_query_and_sweep() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[0];
+    let relayer_account = &accounts[1];
+    let receiver = &accounts[2];
+
+    let relayer = Relayer {
+        cosmos_address: Addr::unchecked(relayer_account),
+        xrpl_address: generate_xrpl_address(),
+        xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
+    };
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+    let entry_point_addr = app
+        .create_mock_entry_point(Addr::unchecked(signer), false)
+        .unwrap();
+
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: vec![relayer],
+                evidence_threshold: 1,
+                used_ticket_sequence_threshold: 50,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: generate_xrpl_address(),
+                xrpl_base_fee: 10,
+                token_factory_addr: token_factory_addr.clone(),
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: Some(entry_point_addr.clone()),
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    // Every universal swap gets its own recovery entry while it's in flight
+    let tx_hashes = vec![generate_hash(), generate_hash()];
+    for tx_hash in tx_hashes.iter() {
+        app.execute(
+            Addr::unchecked(relayer_account),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveEvidence {
+                evidence: Evidence::XRPLToCosmosTransfer {
+                    tx_hash: tx_hash.clone(),
+                    issuer: XRP_ISSUER.to_string(),
+                    currency: XRP_CURRENCY.to_string(),
+                    amount: Uint128::new(1_000_000),
+                    recipient: Addr::unchecked(receiver),
+                    memo: Some(swap_memo(None)),
+                    source_tag: None,
+                    raw_memos: None,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    let xrp_denom = full_denom(&token_factory_addr, XRP_SYMBOL);
+    let entry_point_balance = app
+        .query_balance(entry_point_addr, xrp_denom.clone())
+        .unwrap();
+    assert_eq!(entry_point_balance.to_string(), "2000000".to_string());
+
+    let query_pending_universal_swaps: PendingUniversalSwapsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingUniversalSwaps {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    // Entries of successful swaps are removed when we get their reply
+    assert!(query_pending_universal_swaps
+        .pending_universal_swaps
+        .is_empty());
+    assert_eq!(query_pending_universal_swaps.last_key, None);
+
+    // Only the owner can sweep the entries of swaps that are not in flight anymore
+    let sweep_error = app
+        .execute(
+            Addr::unchecked(relayer_account),
+            contract_addr.clone(),
+            &ExecuteMsg::SweepPendingUniversalSwaps { nonces: vec![1, 2] },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(sweep_error
+        .root_cause()
+        .to_string()
+        .contains(&ContractError::UnauthorizedSender {}.to_string()));
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::SweepPendingUniversalSwaps { nonces: vec![1, 2] },
+        &[],
+    )
+    .unwrap();
+
+    let query_pending_universal_swaps: PendingUniversalSwapsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingUniversalSwaps {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(query_pending_universal_swaps
+        .pending_universal_swaps
+        .is_empty());
+}
+
+#[test]
+#[cfg(not(feature = "test-tube"))]
+fn universal_swap_fallback_policies() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[0];
+    let relayer_account = &accounts[1];
+    let receiver = &accounts[2];
+
+    let relayer = Relayer {
+        cosmos_address: Addr::unchecked(relayer_account),
+        xrpl_address: generate_xrpl_address(),
+        xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
+    };
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+    let entry_point_addr = app
+        .create_mock_entry_point(Addr::unchecked(signer), true)
+        .unwrap();
+
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: vec![relayer],
+                evidence_threshold: 1,
+                used_ticket_sequence_threshold: 50,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: generate_xrpl_address(),
+                xrpl_base_fee: 10,
+                token_factory_addr: token_factory_addr.clone(),
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: Some(entry_point_addr),
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    let xrp_denom = full_denom(&token_factory_addr, XRP_SYMBOL);
+    let amount = Uint128::new(1_000_000);
+
+    // If the memo asks to park the funds, the failed swap leaves a pending refund for the recipient
+    let tx_hash = generate_hash();
+    app.execute(
+        Addr::unchecked(relayer_account),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveEvidence {
+            evidence: Evidence::XRPLToCosmosTransfer {
+                tx_hash: tx_hash.clone(),
+                issuer: XRP_ISSUER.to_string(),
+                currency: XRP_CURRENCY.to_string(),
+                amount,
+                recipient: Addr::unchecked(receiver),
+                memo: Some(swap_memo(Some(UniversalSwapFallback::ParkAsPendingRefund))),
+                source_tag: None,
+                raw_memos: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let receiver_balance = app
+        .query_balance(Addr::unchecked(receiver), xrp_denom.clone())
+        .unwrap();
+    assert_eq!(receiver_balance.to_string(), "0".to_string());
+
+    let query_pending_refunds: PendingRefundsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingRefunds {
+                address: Addr::unchecked(receiver),
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(query_pending_refunds.pending_refunds.len(), 1);
+    let pending_refund: &PendingRefund = &query_pending_refunds.pending_refunds[0];
+    assert_eq!(pending_refund.xrpl_tx_hash, Some(tx_hash));
+    assert_eq!(pending_refund.coin, coin(amount.u128(), xrp_denom.clone()));
+
+    app.execute(
+        Addr::unchecked(receiver),
+        contract_addr.clone(),
+        &ExecuteMsg::ClaimRefund {
+            pending_refund_id: pending_refund.id.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let receiver_balance = app
+        .query_balance(Addr::unchecked(receiver), xrp_denom.clone())
+        .unwrap();
+    assert_eq!(receiver_balance.to_string(), amount.to_string());
+
+    // If the memo asks to revert, the failed swap makes the whole evidence fail
+    let revert_error = app
+        .execute(
+            Addr::unchecked(relayer_account),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveEvidence {
+                evidence: Evidence::XRPLToCosmosTransfer {
+                    tx_hash: generate_hash(),
+                    issuer: XRP_ISSUER.to_string(),
+                    currency: XRP_CURRENCY.to_string(),
+                    amount,
+                    recipient: Addr::unchecked(receiver),
+                    memo: Some(swap_memo(Some(UniversalSwapFallback::Revert))),
+                    source_tag: None,
+                    raw_memos: None,
+                },
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(!revert_error.root_cause().to_string().is_empty());
+
+    let receiver_balance = app
+        .query_balance(Addr::unchecked(receiver), xrp_denom.clone())
+        .unwrap();
+    assert_eq!(receiver_balance.to_string(), amount.to_string());
+
+    // Nothing was left to recover for any of the swaps
+    let query_pending_universal_swaps: PendingUniversalSwapsResponse = app
+        .query(
+            contract_addr,
+            &QueryMsg::PendingUniversalSwaps {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(query_pending_universal_swaps
+        .pending_universal_swaps
+        .is_empty());
+}
+
+#[test]
+#[cfg(not(feature = "test-tube"))]
+fn memo_actions() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[0];
+    let relayer_account = &accounts[1];
+    let receiver = &accounts[2];
+
+    let relayer = Relayer {
+        cosmos_address: Addr::unchecked(relayer_account),
+        xrpl_address: generate_xrpl_address(),
+        xrpl_pub_key: generate_xrpl_pub_key(),
+        weight: None,
+    };
+
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+    let failing_entry_point_addr = app
+        .create_mock_entry_point(Addr::unchecked(signer), true)
+        .unwrap();
+    let contract_to_call_addr = app
+        .create_mock_entry_point(Addr::unchecked(signer), false)
+        .unwrap();
+
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: vec![relayer],
+                evidence_threshold: 1,
+                used_ticket_sequence_threshold: 50,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: generate_xrpl_address(),
+                xrpl_base_fee: 10,
+                token_factory_addr: token_factory_addr.clone(),
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: Some(failing_entry_point_addr),
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    let xrp_denom = full_denom(&token_factory_addr, XRP_SYMBOL);
+
+    // A memo that doesn't follow the schema is a plain transfer, so the entry point is never called
+    let result = app
+        .execute(
+            Addr::unchecked(relayer_account),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveEvidence {
+                evidence: Evidence::XRPLToCosmosTransfer {
+                    tx_hash: generate_hash(),
+                    issuer: XRP_ISSUER.to_string(),
+                    currency: XRP_CURRENCY.to_string(),
+                    amount: Uint128::new(1_000_000),
+                    recipient: Addr::unchecked(receiver),
+                    memo: Some("universal_swap_memo".to_string()),
+                    source_tag: None,
+                    raw_memos: None,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+    assert!(!result
+        .events
+        .iter()
+        .any(|e| e.ty == "wasm-refund_universal_swap"));
+
+    let receiver_balance = app
+        .query_balance(Addr::unchecked(receiver), xrp_denom.clone())
+        .unwrap();
+    assert_eq!(receiver_balance.to_string(), "1000000".to_string());
+
+    // A contract call sends the funds to the contract with the message of the memo
+    let contract_call_memo = to_json_string(&BridgeMemo {
+        version: MEMO_VERSION,
+        action: MemoAction::ContractCall {
+            contract: contract_to_call_addr.to_string(),
+            msg: to_json_binary(&EntryPointExecuteMsg::UniversalSwap {
+                memo: "contract_call".to_string(),
+            })
+            .unwrap(),
+            fallback: None,
+        },
+    })
+    .unwrap();
+
+    app.execute(
+        Addr::unchecked(relayer_account),
+        contract_addr.clone(),
+        &ExecuteMsg::SaveEvidence {
+            evidence: Evidence::XRPLToCosmosTransfer {
+                tx_hash: generate_hash(),
+                issuer: XRP_ISSUER.to_string(),
+                currency: XRP_CURRENCY.to_string(),
+                amount: Uint128::new(1_000_000),
+                recipient: Addr::unchecked(receiver),
+                memo: Some(contract_call_memo),
+                source_tag: None,
+                raw_memos: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let contract_to_call_balance = app
+        .query_balance(contract_to_call_addr, xrp_denom.clone())
+        .unwrap();
+    assert_eq!(contract_to_call_balance.to_string(), "1000000".to_string());
+
+    // A contract call to an invalid address is rejected before reaching the threshold
+    let invalid_contract_call_memo = to_json_string(&BridgeMemo {
+        version: MEMO_VERSION,
+        action: MemoAction::ContractCall {
+            contract: "INVALID_ADDRESS".to_string(),
+            msg: to_json_binary(&EntryPointExecuteMsg::UniversalSwap {
+                memo: "contract_call".to_string(),
+            })
+            .unwrap(),
+            fallback: None,
+        },
+    })
+    .unwrap();
+
+    app.execute(
+        Addr::unchecked(relayer_account),
+        contract_addr,
+        &ExecuteMsg::SaveEvidence {
+            evidence: Evidence::XRPLToCosmosTransfer {
+                tx_hash: generate_hash(),
+                issuer: XRP_ISSUER.to_string(),
+                currency: XRP_CURRENCY.to_string(),
+                amount: Uint128::new(1_000_000),
+                recipient: Addr::unchecked(receiver),
+                memo: Some(invalid_contract_call_memo),
+                source_tag: None,
+                raw_memos: None,
+            },
+        },
+        &[],
+    )
+    .unwrap_err();
+}
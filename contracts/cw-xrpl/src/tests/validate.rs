@@ -1,12 +1,19 @@
 use std::collections::HashMap;
 
-use cosmwasm_std::{to_json_string, to_json_vec, Addr, Uint128};
+use cosmwasm_std::{to_json_binary, to_json_string, to_json_vec, Addr, HexBinary, Uint128};
 
 use super::helper::{generate_invalid_xrpl_address, generate_xrpl_address};
 use crate::{
-    address::validate_xrpl_address_format,
+    address::{
+        decode_x_address, encode_x_address, normalize_xrpl_address, resolve_xrpl_recipient,
+        validate_xrpl_address_format,
+    },
     contract::INITIAL_PROHIBITED_XRPL_ADDRESSES,
+    error::ContractError,
     evidence::{hash_bytes, Evidence, OperationResult, TransactionResult},
+    ibc_forward::MAX_IBC_FORWARD_TIMEOUT_SECONDS,
+    memo::{parse_memo, BridgeMemo, MemoAction, MAX_MEMO_LENGTH, MEMO_VERSION},
+    state::XRPLNetwork,
     tests::helper::generate_hash,
 };
 
@@ -54,6 +61,134 @@ fn validate_xrpl_addresses() {
     }
 }
 
+#[test]
+fn x_addresses() {
+    let classic_address = "r9cZA1mLK5R5Am25ArfXFmqgNwjZgnfk59".to_string();
+
+    let x_addresses = vec![
+        (
+            "X7AcgcsBL6XDcUb289X4mJ8djcdyKaB5hJDWMArnXr61cqZ",
+            None,
+            XRPLNetwork::Mainnet,
+        ),
+        (
+            "T719a5UwUCnEs54UsxG9CJYYDhwmFCqkr7wxCcNcfZ6p5GZ",
+            None,
+            XRPLNetwork::Testnet,
+        ),
+        (
+            "X7AcgcsBL6XDcUb289X4mJ8djcdyKaGZMhc9YTE92ehJ2Fu",
+            Some(1),
+            XRPLNetwork::Mainnet,
+        ),
+        (
+            "T719a5UwUCnEs54UsxG9CJYYDhwmFCvbJNZbi37gBGkRkbE",
+            Some(1),
+            XRPLNetwork::Testnet,
+        ),
+        (
+            "X7AcgcsBL6XDcUb289X4mJ8djcdyKaGo2K5VpXpmCqbV2gS",
+            Some(14),
+            XRPLNetwork::Mainnet,
+        ),
+        (
+            "X7AcgcsBL6XDcUb289X4mJ8djcdyKaM4S135zJJmc3HMChp",
+            Some(u32::MAX),
+            XRPLNetwork::Mainnet,
+        ),
+    ];
+
+    for (x_address, tag, network) in x_addresses {
+        assert_eq!(
+            decode_x_address(x_address, &network).unwrap(),
+            (classic_address.clone(), tag)
+        );
+        assert_eq!(
+            encode_x_address(&classic_address, tag, &network).unwrap(),
+            x_address
+        );
+        assert_eq!(
+            normalize_xrpl_address(x_address, &network).unwrap(),
+            classic_address
+        );
+
+        // X-addresses of the other network are rejected
+        let other_network = match network {
+            XRPLNetwork::Mainnet => XRPLNetwork::Testnet,
+            XRPLNetwork::Testnet => XRPLNetwork::Mainnet,
+        };
+        let error = decode_x_address(x_address, &other_network).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            ContractError::XAddressWrongNetwork {
+                address: x_address.to_string()
+            }
+            .to_string()
+        );
+    }
+
+    let invalid_x_addresses = vec![
+        "X7AcgcsBL6XDcUb289X4mJ8djcdyKaB5hJDWMArnXr61cqz", // Invalid checksum
+        "X7AcgcsBL6XDcUb289X4mJ8djcdyKaGYE2g3s6f6M6XhwLm", // Tag doesn't fit in 32 bits
+        "X7AcgcsBL6XDcUb289X4mJ8djcdyKa",                  // Too short
+        "r9cZA1mLK5R5Am25ArfXFmqgNwjZgnfk59",              // Classic address
+    ];
+
+    for x_address in invalid_x_addresses {
+        decode_x_address(x_address, &XRPLNetwork::Mainnet).unwrap_err();
+    }
+
+    // Classic addresses are kept as they are
+    assert_eq!(
+        normalize_xrpl_address(&classic_address, &XRPLNetwork::Mainnet).unwrap(),
+        classic_address
+    );
+    assert_eq!(
+        resolve_xrpl_recipient(classic_address.clone(), Some(5), &XRPLNetwork::Mainnet).unwrap(),
+        (classic_address.clone(), Some(5))
+    );
+
+    // The tag of the X-address is used and it can be repeated in the destination tag but it can't be different
+    assert_eq!(
+        resolve_xrpl_recipient(
+            "X7AcgcsBL6XDcUb289X4mJ8djcdyKaGo2K5VpXpmCqbV2gS".to_string(),
+            None,
+            &XRPLNetwork::Mainnet
+        )
+        .unwrap(),
+        (classic_address.clone(), Some(14))
+    );
+    assert_eq!(
+        resolve_xrpl_recipient(
+            "X7AcgcsBL6XDcUb289X4mJ8djcdyKaGo2K5VpXpmCqbV2gS".to_string(),
+            Some(14),
+            &XRPLNetwork::Mainnet
+        )
+        .unwrap(),
+        (classic_address.clone(), Some(14))
+    );
+    assert_eq!(
+        resolve_xrpl_recipient(
+            "X7AcgcsBL6XDcUb289X4mJ8djcdyKaB5hJDWMArnXr61cqZ".to_string(),
+            Some(3),
+            &XRPLNetwork::Mainnet
+        )
+        .unwrap(),
+        (classic_address, Some(3))
+    );
+
+    let error = resolve_xrpl_recipient(
+        "X7AcgcsBL6XDcUb289X4mJ8djcdyKaGo2K5VpXpmCqbV2gS".to_string(),
+        Some(15),
+        &XRPLNetwork::Mainnet,
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        ContractError::DestinationTagMismatch {}.to_string()
+    );
+}
+
 #[test]
 fn enum_hashes() {
     let hash = generate_hash();
@@ -71,6 +206,8 @@ fn enum_hashes() {
             amount: amount.clone(),
             recipient: recipient.clone(),
             memo: None,
+            source_tag: None,
+            raw_memos: None,
         },
         Evidence::XRPLToCosmosTransfer {
             tx_hash: generate_hash(),
@@ -79,6 +216,8 @@ fn enum_hashes() {
             amount: amount.clone(),
             recipient: recipient.clone(),
             memo: None,
+            source_tag: None,
+            raw_memos: None,
         },
         Evidence::XRPLToCosmosTransfer {
             tx_hash: hash.clone(),
@@ -87,6 +226,8 @@ fn enum_hashes() {
             amount: amount.clone(),
             recipient: recipient.clone(),
             memo: None,
+            source_tag: None,
+            raw_memos: None,
         },
         Evidence::XRPLToCosmosTransfer {
             tx_hash: hash.clone(),
@@ -95,6 +236,8 @@ fn enum_hashes() {
             amount: amount.clone(),
             recipient: recipient.clone(),
             memo: None,
+            source_tag: None,
+            raw_memos: None,
         },
         Evidence::XRPLToCosmosTransfer {
             tx_hash: hash.clone(),
@@ -103,6 +246,8 @@ fn enum_hashes() {
             amount: Uint128::one(),
             recipient: recipient.clone(),
             memo: None,
+            source_tag: None,
+            raw_memos: None,
         },
         Evidence::XRPLToCosmosTransfer {
             tx_hash: hash.clone(),
@@ -111,9 +256,36 @@ fn enum_hashes() {
             amount: amount.clone(),
             recipient: Addr::unchecked("new_recipient"),
             memo: None,
+            source_tag: None,
+            raw_memos: None,
+        },
+        Evidence::XRPLToCosmosTransfer {
+            tx_hash: hash.clone(),
+            issuer: issuer.clone(),
+            currency: currency.clone(),
+            amount: amount.clone(),
+            recipient: recipient.clone(),
+            memo: None,
+            source_tag: Some(1),
+            raw_memos: None,
+        },
+        Evidence::XRPLToCosmosTransfer {
+            tx_hash: hash.clone(),
+            issuer: issuer.clone(),
+            currency: currency.clone(),
+            amount: amount.clone(),
+            recipient: recipient.clone(),
+            memo: None,
+            source_tag: None,
+            raw_memos: Some(vec![HexBinary::from(b"memo")]),
         },
     ];
 
+    // Evidences without source tag and raw memos serialize as before these fields existed
+    let evidence_json = to_json_string(&xrpl_to_cosmos_transfer_evidences[0]).unwrap();
+    assert!(!evidence_json.contains("source_tag"));
+    assert!(!evidence_json.contains("raw_memos"));
+
     // Add them all to a map to see that they create different entries
     let mut evidence_map = HashMap::new();
     for evidence in xrpl_to_cosmos_transfer_evidences.iter() {
@@ -199,3 +371,161 @@ fn enum_hashes() {
 
     assert_eq!(evidence_map.len(), xrpl_transaction_result_evidences.len());
 }
+
+#[test]
+fn validate_memos() {
+    // Memos that don't follow the schema are plain transfers
+    for memo in [
+        "".to_string(),
+        "universal_swap_memo".to_string(),
+        r#"{"version":1,"action":{"unknown":{}}}"#.to_string(),
+        r#"{"action":{"swap":{"memo":"universal_swap_memo"}}}"#.to_string(),
+    ] {
+        assert_eq!(parse_memo(&memo).unwrap(), None);
+    }
+
+    let valid_memos = vec![
+        BridgeMemo {
+            version: MEMO_VERSION,
+            action: MemoAction::Swap {
+                memo: "universal_swap_memo".to_string(),
+                fallback: None,
+            },
+        },
+        BridgeMemo {
+            version: MEMO_VERSION,
+            action: MemoAction::Forward {
+                channel: "channel-0".to_string(),
+                receiver: "osmo1receiver".to_string(),
+                timeout_seconds: Some(600),
+            },
+        },
+        BridgeMemo {
+            version: MEMO_VERSION,
+            action: MemoAction::ContractCall {
+                contract: "contract".to_string(),
+                msg: to_json_binary(&"msg").unwrap(),
+                fallback: None,
+            },
+        },
+    ];
+
+    for memo in valid_memos {
+        assert_eq!(
+            parse_memo(&to_json_string(&memo).unwrap()).unwrap(),
+            Some(memo)
+        );
+    }
+
+    let invalid_memos = vec![
+        (
+            BridgeMemo {
+                version: MEMO_VERSION + 1,
+                action: MemoAction::Swap {
+                    memo: "universal_swap_memo".to_string(),
+                    fallback: None,
+                },
+            },
+            ContractError::UnsupportedMemoVersion {
+                version: MEMO_VERSION + 1,
+            },
+        ),
+        (
+            BridgeMemo {
+                version: MEMO_VERSION,
+                action: MemoAction::Swap {
+                    memo: "".to_string(),
+                    fallback: None,
+                },
+            },
+            ContractError::InvalidMemo {},
+        ),
+        (
+            BridgeMemo {
+                version: MEMO_VERSION,
+                action: MemoAction::Forward {
+                    channel: "".to_string(),
+                    receiver: "osmo1receiver".to_string(),
+                    timeout_seconds: None,
+                },
+            },
+            ContractError::InvalidMemo {},
+        ),
+        (
+            BridgeMemo {
+                version: MEMO_VERSION,
+                action: MemoAction::Forward {
+                    channel: "channel-0".to_string(),
+                    receiver: "osmo1receiver".to_string(),
+                    timeout_seconds: Some(0),
+                },
+            },
+            ContractError::InvalidMemo {},
+        ),
+        (
+            BridgeMemo {
+                version: MEMO_VERSION,
+                action: MemoAction::Forward {
+                    channel: "channel-0".to_string(),
+                    receiver: "osmo1receiver".to_string(),
+                    timeout_seconds: Some(MAX_IBC_FORWARD_TIMEOUT_SECONDS + 1),
+                },
+            },
+            ContractError::InvalidMemo {},
+        ),
+        (
+            BridgeMemo {
+                version: MEMO_VERSION,
+                action: MemoAction::ContractCall {
+                    contract: "a".repeat(200),
+                    msg: to_json_binary(&"msg").unwrap(),
+                    fallback: None,
+                },
+            },
+            ContractError::InvalidMemo {},
+        ),
+    ];
+
+    for (memo, error) in invalid_memos {
+        // Invalid memos are rejected when the evidence is validated
+        let evidence = Evidence::XRPLToCosmosTransfer {
+            tx_hash: generate_hash(),
+            issuer: generate_xrpl_address(),
+            currency: "USD".to_string(),
+            amount: Uint128::one(),
+            recipient: Addr::unchecked("recipient"),
+            memo: Some(to_json_string(&memo).unwrap()),
+            source_tag: None,
+            raw_memos: None,
+        };
+        assert_eq!(
+            evidence.validate_basic().unwrap_err().to_string(),
+            error.to_string()
+        );
+    }
+
+    let too_long_memo = "a".repeat(MAX_MEMO_LENGTH + 1);
+    assert_eq!(
+        parse_memo(&too_long_memo).unwrap_err().to_string(),
+        ContractError::MemoTooLong {}.to_string()
+    );
+
+    // The raw memos of the XRPL transaction are limited too
+    let evidence = Evidence::XRPLToCosmosTransfer {
+        tx_hash: generate_hash(),
+        issuer: generate_xrpl_address(),
+        currency: "USD".to_string(),
+        amount: Uint128::one(),
+        recipient: Addr::unchecked("recipient"),
+        memo: None,
+        source_tag: Some(1),
+        raw_memos: Some(vec![
+            HexBinary::from(vec![0u8; MAX_MEMO_LENGTH / 2]),
+            HexBinary::from(vec![0u8; MAX_MEMO_LENGTH / 2 + 1]),
+        ]),
+    };
+    assert_eq!(
+        evidence.validate_basic().unwrap_err().to_string(),
+        ContractError::MemoTooLong {}.to_string()
+    );
+}
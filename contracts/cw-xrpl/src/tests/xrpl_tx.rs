@@ -0,0 +1,384 @@
+use cosmwasm_std::{coins, Addr, HexBinary, Uint128};
+
+use crate::{
+    contract::{XRP_CURRENCY, XRP_ISSUER},
+    msg::{
+        ExecuteMsg, InstantiateMsg, PendingOperationsResponse, QueryMsg, SignedTransactionResponse,
+        SigningPayloadResponse,
+    },
+    operation::{Operation, OperationType},
+    relayer::Relayer,
+    tests::helper::{
+        generate_xrpl_address, generate_xrpl_signer, MockApp, FEE_DENOM, TRUST_SET_LIMIT_AMOUNT,
+    },
+    xrpl_tx::{
+        multisigning_data, serialize_operation, serialize_signed_operation, to_hex,
+        TransactionSigner,
+    },
+};
+
+// Account and issuer taken from the TrustSet serialization example of the XRPL documentation.
+// The expected transactions below are the output of this serializer, not of an independent XRPL codec, so they detect
+// regressions but don't prove that relayers serialize the same bytes
+const ACCOUNT: &str = "rMBzp8CgpE441cp5PVyA9rpVV7oT8hP3ys";
+const ACCOUNT_ID: &str = "DD76483FACDEE26E60D8A586BB58D09F27045C46";
+const ISSUER: &str = "rvYAfWj5gh67oV6fW32ZzP3Aw4Eubs59B";
+const ISSUER_ID: &str = "0A20B3C85F482532A9578DBB3950B85CA06594D1";
+
+fn operation(
+    ticket_sequence: Option<u64>,
+    account_sequence: Option<u64>,
+    xrpl_base_fee: u64,
+    operation_type: OperationType,
+) -> Operation {
+    Operation {
+        id: ticket_sequence.or(account_sequence).unwrap().to_string(),
+        version: 1,
+        ticket_sequence,
+        account_sequence,
+        signatures: vec![],
+        operation_type,
+        xrpl_base_fee,
+        created_at: None,
+    }
+}
+
+#[test]
+fn serialize_operations() {
+    // TrustSet with account sequence, fee of 10 drops (5 * (1 signer + 1)) and a limit of 7072.8 USD
+    let trust_set = operation(
+        None,
+        Some(1752792),
+        5,
+        OperationType::TrustSet {
+            issuer: ISSUER.to_string(),
+            currency: "USD".to_string(),
+            trust_set_limit_amount: Uint128::new(7_072_800_000_000_000_000),
+        },
+    );
+    assert_eq!(
+        to_hex(&serialize_operation(&trust_set, ACCOUNT, 1).unwrap()),
+        format!(
+            "120014220002000024001ABED863D55920AC93914000{}{}68400000000000000A73008114{}",
+            "0000000000000000000000005553440000000000", ISSUER_ID, ACCOUNT_ID
+        )
+    );
+
+    // TicketCreate with account sequence
+    let allocate_tickets = operation(
+        None,
+        Some(3),
+        10,
+        OperationType::AllocateTickets { number: 5 },
+    );
+    let allocate_tickets_tx = format!(
+        "12000A2200000000240000000320280000000568400000000000001E73008114{}",
+        ACCOUNT_ID
+    );
+    assert_eq!(
+        to_hex(&serialize_operation(&allocate_tickets, ACCOUNT, 2).unwrap()),
+        allocate_tickets_tx
+    );
+
+    // The multisigning data is the prefix, the transaction and the account ID of the signer
+    assert_eq!(
+        to_hex(&multisigning_data(&allocate_tickets, ACCOUNT, 2, ISSUER).unwrap()),
+        format!("534D5400{}{}", allocate_tickets_tx, ISSUER_ID)
+    );
+
+    // The multisigned transaction has the Signers field sorted by account ID of the signers
+    let pub_key = "03EE83BB432547885C219634A1BC407A9DB0474145D69737D09CCDC63E1DEE7FE3";
+    let signers = vec![
+        TransactionSigner {
+            account: ACCOUNT.to_string(),
+            signing_pub_key: pub_key.to_string(),
+            txn_signature: "3044AA".to_string(),
+        },
+        TransactionSigner {
+            account: ISSUER.to_string(),
+            signing_pub_key: pub_key.to_string(),
+            txn_signature: "3044BB".to_string(),
+        },
+    ];
+    assert_eq!(
+        to_hex(&serialize_signed_operation(&allocate_tickets, ACCOUNT, 2, &signers).unwrap()),
+        format!(
+            "{}F3E0107321{}74033044BB8114{}E1E0107321{}74033044AA8114{}E1F1",
+            allocate_tickets_tx, pub_key, ISSUER_ID, pub_key, ACCOUNT_ID
+        )
+    );
+
+    // SignerListSet with ticket
+    let rotate_keys = operation(
+        Some(4),
+        None,
+        10,
+        OperationType::RotateKeys {
+            new_relayers: vec![
+                Relayer {
+                    cosmos_address: Addr::unchecked("relayer0"),
+                    xrpl_address: ISSUER.to_string(),
+                    xrpl_pub_key: "pub_key0".to_string(),
+                    weight: None,
+                },
+                Relayer {
+                    cosmos_address: Addr::unchecked("relayer1"),
+                    xrpl_address: ACCOUNT.to_string(),
+                    xrpl_pub_key: "pub_key1".to_string(),
+                    weight: None,
+                },
+            ],
+            new_evidence_threshold: 2,
+        },
+    );
+    assert_eq!(
+        to_hex(&serialize_operation(&rotate_keys, ACCOUNT, 2).unwrap()),
+        format!(
+            "12000C2200000000240000000020230000000220290000000468400000000000001E73008114{}F4EB1300018114{}E1EB1300018114{}E1F1",
+            ACCOUNT_ID, ISSUER_ID, ACCOUNT_ID
+        )
+    );
+
+    // XRP Payment with ticket and destination tag
+    let xrp_payment = operation(
+        Some(7),
+        None,
+        10,
+        OperationType::CosmosToXRPLTransfer {
+            issuer: XRP_ISSUER.to_string(),
+            currency: XRP_CURRENCY.to_string(),
+            amount: Uint128::new(15_000_000_000),
+            max_amount: None,
+            sender: Addr::unchecked("sender"),
+            recipient: ISSUER.to_string(),
+            destination_tag: Some(12345),
+            refund_to: None,
+        },
+    );
+    assert_eq!(
+        to_hex(&serialize_operation(&xrp_payment, ACCOUNT, 2).unwrap()),
+        format!(
+            "120000220000000024000000002E0000303920290000000761400000037E11D60068400000000000001E73008114{}8314{}",
+            ACCOUNT_ID, ISSUER_ID
+        )
+    );
+
+    // Token Payment with ticket, hexadecimal currency and max amount (1 token sent, 1.5 tokens max)
+    let currency = "524C555344000000000000000000000000000000";
+    let token_payment = operation(
+        Some(9),
+        None,
+        10,
+        OperationType::CosmosToXRPLTransfer {
+            issuer: ACCOUNT.to_string(),
+            currency: currency.to_string(),
+            amount: Uint128::new(1_000_000_000_000_000),
+            max_amount: Some(Uint128::new(1_500_000_000_000_000)),
+            sender: Addr::unchecked("sender"),
+            recipient: ISSUER.to_string(),
+            destination_tag: None,
+            refund_to: None,
+        },
+    );
+    assert_eq!(
+        to_hex(&serialize_operation(&token_payment, ACCOUNT, 3).unwrap()),
+        format!(
+            "1200002200000000240000000020290000000961D4838D7EA4C68000{}{}684000000000000028{}{}{}73008114{}8314{}",
+            currency, ACCOUNT_ID, "69D485543DF729C000", currency, ACCOUNT_ID, ACCOUNT_ID, ISSUER_ID
+        )
+    );
+
+    // Zero amounts of tokens have their own representation
+    let zero_payment = operation(
+        Some(9),
+        None,
+        10,
+        OperationType::CosmosToXRPLTransfer {
+            issuer: ISSUER.to_string(),
+            currency: "USD".to_string(),
+            amount: Uint128::zero(),
+            max_amount: None,
+            sender: Addr::unchecked("sender"),
+            recipient: ACCOUNT.to_string(),
+            destination_tag: None,
+            refund_to: None,
+        },
+    );
+    assert!(
+        to_hex(&serialize_operation(&zero_payment, ACCOUNT, 1).unwrap())
+            .contains("618000000000000000")
+    );
+
+    // Operations must have either a ticket or an account sequence
+    let invalid_operation = Operation {
+        ticket_sequence: Some(1),
+        account_sequence: Some(1),
+        ..allocate_tickets
+    };
+    assert!(serialize_operation(&invalid_operation, ACCOUNT, 1).is_err());
+}
+
+#[test]
+fn signing_payload_query() {
+    let (mut app, accounts) = MockApp::new(&[
+        ("account0", &coins(100_000_000_000, FEE_DENOM)),
+        ("account1", &coins(100_000_000_000, FEE_DENOM)),
+        ("account2", &coins(100_000_000_000, FEE_DENOM)),
+    ]);
+
+    let signer = &accounts[2];
+    let xrpl_signers = vec![generate_xrpl_signer(), generate_xrpl_signer()];
+    let relayers: Vec<Relayer> = xrpl_signers
+        .iter()
+        .enumerate()
+        .map(|(i, xrpl_signer)| Relayer {
+            cosmos_address: Addr::unchecked(&accounts[i]),
+            xrpl_address: xrpl_signer.xrpl_address.clone(),
+            xrpl_pub_key: xrpl_signer.xrpl_pub_key.clone(),
+            weight: None,
+        })
+        .collect();
+
+    let bridge_xrpl_address = generate_xrpl_address();
+    let token_factory_addr = app.create_tokenfactory(Addr::unchecked(signer)).unwrap();
+    let contract_addr = app
+        .create_bridge(
+            Addr::unchecked(signer),
+            &InstantiateMsg {
+                owner: Addr::unchecked(signer),
+                relayers: relayers.clone(),
+                evidence_threshold: 2,
+                used_ticket_sequence_threshold: 4,
+                trust_set_limit_amount: Uint128::new(TRUST_SET_LIMIT_AMOUNT),
+                bridge_xrpl_address: bridge_xrpl_address.clone(),
+                xrpl_base_fee: 10,
+                token_factory_addr,
+                issue_token: true,
+                rate_limit_addr: None,
+                osor_entry_point: None,
+                xrpl_network: None,
+            },
+        )
+        .unwrap();
+
+    // Querying the payload of an operation that doesn't exist fails
+    app.query::<SigningPayloadResponse, _>(
+        contract_addr.clone(),
+        &QueryMsg::SigningPayload { operation_id: 1 },
+    )
+    .unwrap_err();
+
+    app.execute(
+        Addr::unchecked(signer),
+        contract_addr.clone(),
+        &ExecuteMsg::RecoverTickets {
+            account_sequence: 1,
+            number_of_tickets: Some(5),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let operation = &query_pending_operations.operations[0];
+
+    let query_signing_payload: SigningPayloadResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::SigningPayload { operation_id: 1 },
+        )
+        .unwrap();
+
+    assert_eq!(query_signing_payload.operation_id, 1);
+    assert_eq!(query_signing_payload.operation_version, 1);
+    assert_eq!(
+        query_signing_payload.transaction,
+        to_hex(&serialize_operation(operation, &bridge_xrpl_address, 2).unwrap())
+    );
+    assert_eq!(query_signing_payload.signing_payloads.len(), 2);
+
+    // Each relayer signs its own payload and the contract accepts the signatures
+    // The signed transaction is only available once the evidence threshold is reached
+    for (i, signing_payload) in query_signing_payload.signing_payloads.iter().enumerate() {
+        assert_eq!(
+            signing_payload.relayer_cosmos_address,
+            relayers[i].cosmos_address
+        );
+        assert_eq!(signing_payload.xrpl_address, relayers[i].xrpl_address);
+        assert_eq!(
+            HexBinary::from_hex(&signing_payload.payload)
+                .unwrap()
+                .to_vec(),
+            multisigning_data(
+                operation,
+                &bridge_xrpl_address,
+                2,
+                &relayers[i].xrpl_address
+            )
+            .unwrap()
+        );
+
+        app.execute(
+            Addr::unchecked(&relayers[i].cosmos_address),
+            contract_addr.clone(),
+            &ExecuteMsg::SaveSignature {
+                operation_id: 1,
+                operation_version: 1,
+                signature: xrpl_signers[i].sign_operation(operation, &bridge_xrpl_address, 2),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let query_signed_transaction: SignedTransactionResponse = app
+            .query(
+                contract_addr.clone(),
+                &QueryMsg::SignedTransaction { operation_id: 1 },
+            )
+            .unwrap();
+        assert_eq!(query_signed_transaction.tx_blob.is_some(), i == 1);
+    }
+
+    let query_pending_operations: PendingOperationsResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::PendingOperations {
+                start_after_key: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let operation = &query_pending_operations.operations[0];
+
+    let query_signed_transaction: SignedTransactionResponse = app
+        .query(
+            contract_addr.clone(),
+            &QueryMsg::SignedTransaction { operation_id: 1 },
+        )
+        .unwrap();
+
+    let signers: Vec<TransactionSigner> = operation
+        .signatures
+        .iter()
+        .zip(&relayers)
+        .map(|(signature, relayer)| TransactionSigner {
+            account: relayer.xrpl_address.clone(),
+            signing_pub_key: relayer.xrpl_pub_key.clone(),
+            txn_signature: signature.signature.clone(),
+        })
+        .collect();
+    assert_eq!(
+        query_signed_transaction.tx_blob,
+        Some(to_hex(
+            &serialize_signed_operation(operation, &bridge_xrpl_address, 2, &signers).unwrap()
+        ))
+    );
+}
@@ -1,9 +1,12 @@
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{
+    wasm_execute, Addr, BankMsg, Coin, CosmosMsg, QuerierWrapper, StdResult, Storage, Uint128,
+};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
 
 use crate::{
     contract::{validate_sending_precision, XRP_CURRENCY, XRP_ISSUER, XRP_SUBUNIT},
     error::ContractError,
-    state::TokenState,
+    state::{CosmosToken, CosmosTokenType, TokenState, COSMOS_TOKENS},
 };
 
 pub fn full_denom(addr: &Addr, subdenom: &str) -> String {
@@ -84,3 +87,72 @@ pub fn set_token_max_holding_amount(
 
     Ok(())
 }
+
+// Helper to know if a denom is the address of a registered CW20 token
+pub fn is_cw20_token(storage: &dyn Storage, denom: &str) -> StdResult<bool> {
+    Ok(COSMOS_TOKENS
+        .may_load(storage, denom.to_string())?
+        .is_some_and(|token| token.token_type.eq(&CosmosTokenType::CW20)))
+}
+
+// Builds the messages to send coins from the bridge. Native coins are sent together with a bank send and CW20 tokens (the denom is the CW20 contract address) with a transfer each
+pub fn build_send_msgs(
+    storage: &dyn Storage,
+    recipient: &Addr,
+    coins: Vec<Coin>,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut native_coins = vec![];
+    for coin in coins {
+        if is_cw20_token(storage, &coin.denom)? {
+            msgs.push(
+                wasm_execute(
+                    coin.denom,
+                    &Cw20ExecuteMsg::Transfer {
+                        recipient: recipient.to_string(),
+                        amount: coin.amount,
+                    },
+                    vec![],
+                )?
+                .into(),
+            );
+        } else {
+            native_coins.push(coin);
+        }
+    }
+
+    if !native_coins.is_empty() {
+        msgs.insert(
+            0,
+            BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: native_coins,
+            }
+            .into(),
+        );
+    }
+
+    Ok(msgs)
+}
+
+// Amount of a Cosmos originated token that the bridge is holding in escrow
+pub fn query_cosmos_token_balance(
+    querier: &QuerierWrapper,
+    bridge_addr: &Addr,
+    token: &CosmosToken,
+) -> StdResult<Uint128> {
+    match token.token_type {
+        CosmosTokenType::Native => Ok(querier
+            .query_balance(bridge_addr, token.denom.clone())?
+            .amount),
+        CosmosTokenType::CW20 => {
+            let response: BalanceResponse = querier.query_wasm_smart(
+                token.denom.clone(),
+                &Cw20QueryMsg::Balance {
+                    address: bridge_addr.to_string(),
+                },
+            )?;
+            Ok(response.balance)
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    wasm_execute, Addr, BankMsg, Binary, Coin, Event, Response, Storage, SubMsg, SubMsgResult,
+    WasmMsg,
+};
+use skip::entry_point::ExecuteMsg as EntryPointExecuteMsg;
+
+use crate::{
+    error::{ContractError, ContractResult},
+    operation::store_pending_refund,
+    state::{PendingUniversalSwap, PENDING_UNIVERSAL_SWAPS, UNIVERSAL_SWAP_NONCE},
+};
+
+// What to do with the funds if the universal swap fails
+#[cw_serde]
+#[derive(Default)]
+pub enum UniversalSwapFallback {
+    // Send the funds to the recipient of the transfer
+    #[default]
+    RefundToRecipient,
+    // Store the funds as a pending refund that the recipient can claim later
+    ParkAsPendingRefund,
+    // Fail the whole transfer, so the evidence that reached the threshold is reverted
+    Revert,
+}
+
+impl UniversalSwapFallback {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::RefundToRecipient => "refund_to_recipient",
+            Self::ParkAsPendingRefund => "park_as_pending_refund",
+            Self::Revert => "revert",
+        }
+    }
+}
+
+// Builds the submessage that sends the funds to the osor entry point
+pub fn create_universal_swap_submsg(
+    storage: &mut dyn Storage,
+    entry_point: Addr,
+    memo: String,
+    fallback: UniversalSwapFallback,
+    tx_hash: String,
+    recipient: Addr,
+    coin: Coin,
+) -> ContractResult<SubMsg> {
+    let swap_msg = wasm_execute(
+        entry_point,
+        &EntryPointExecuteMsg::UniversalSwap { memo },
+        vec![coin.clone()],
+    )?;
+
+    create_recoverable_submsg(storage, swap_msg, fallback, tx_hash, recipient, coin)
+}
+
+// Builds the submessage that executes a contract with the funds. Failed contract calls are recovered the same way as failed universal swaps
+pub fn create_contract_call_submsg(
+    storage: &mut dyn Storage,
+    contract: Addr,
+    msg: Binary,
+    fallback: UniversalSwapFallback,
+    tx_hash: String,
+    recipient: Addr,
+    coin: Coin,
+) -> ContractResult<SubMsg> {
+    let call_msg = WasmMsg::Execute {
+        contract_addr: contract.to_string(),
+        msg,
+        funds: vec![coin.clone()],
+    };
+
+    create_recoverable_submsg(storage, call_msg, fallback, tx_hash, recipient, coin)
+}
+
+// Stores what we need to recover the funds if the submessage fails.
+// The swap nonce is used as the reply id so that several swaps in the same transaction don't overwrite each other.
+// We reply always: on success to remove the entry and on error to recover the funds
+fn create_recoverable_submsg(
+    storage: &mut dyn Storage,
+    msg: WasmMsg,
+    fallback: UniversalSwapFallback,
+    tx_hash: String,
+    recipient: Addr,
+    coin: Coin,
+) -> ContractResult<SubMsg> {
+    // If the swap must revert on failure, there is nothing to recover
+    if fallback == UniversalSwapFallback::Revert {
+        return Ok(SubMsg::new(msg));
+    }
+
+    let nonce = next_reply_nonce(storage)?;
+    PENDING_UNIVERSAL_SWAPS.save(
+        storage,
+        nonce,
+        &PendingUniversalSwap {
+            recipient,
+            coin,
+            tx_hash,
+            fallback,
+        },
+    )?;
+
+    Ok(SubMsg::reply_always(msg, nonce))
+}
+
+// Universal swaps and IBC forwards share the nonce because both use it as reply id
+pub fn next_reply_nonce(storage: &mut dyn Storage) -> ContractResult<u64> {
+    let nonce = UNIVERSAL_SWAP_NONCE
+        .may_load(storage)?
+        .unwrap_or_default()
+        .checked_add(1)
+        .ok_or_else(|| ContractError::UniversalSwapNonceOverflow {})?;
+    UNIVERSAL_SWAP_NONCE.save(storage, &nonce)?;
+
+    Ok(nonce)
+}
+
+pub fn is_universal_swap_reply(storage: &dyn Storage, nonce: u64) -> bool {
+    PENDING_UNIVERSAL_SWAPS.has(storage, nonce)
+}
+
+pub fn handle_universal_swap_reply(
+    storage: &mut dyn Storage,
+    timestamp: u64,
+    nonce: u64,
+    result: SubMsgResult,
+) -> ContractResult<Response> {
+    match result {
+        SubMsgResult::Ok(_) => {
+            PENDING_UNIVERSAL_SWAPS.remove(storage, nonce);
+            Ok(Response::new()
+                .add_attribute("action", "universal_swap")
+                .add_attribute("nonce", nonce.to_string()))
+        }
+        SubMsgResult::Err(err) => handle_universal_swap_error(storage, timestamp, nonce, err),
+    }
+}
+
+// Recovers the funds of a universal swap that failed according to the fallback chosen for it
+fn handle_universal_swap_error(
+    storage: &mut dyn Storage,
+    timestamp: u64,
+    nonce: u64,
+    error: String,
+) -> ContractResult<Response> {
+    let pending_universal_swap = PENDING_UNIVERSAL_SWAPS
+        .may_load(storage, nonce)?
+        .ok_or(ContractError::UnknownReplyId { id: nonce })?;
+    PENDING_UNIVERSAL_SWAPS.remove(storage, nonce);
+
+    let mut response = Response::new().add_attribute("action", "refund_universal_swap");
+
+    match pending_universal_swap.fallback {
+        UniversalSwapFallback::ParkAsPendingRefund => {
+            // The recipient will claim the funds with a ClaimRefund message
+            store_pending_refund(
+                storage,
+                format!("{timestamp}-swap-{nonce}"),
+                Some(pending_universal_swap.tx_hash.clone()),
+                pending_universal_swap.recipient.clone(),
+                pending_universal_swap.coin.clone(),
+                None,
+            )?;
+        }
+        // Swaps that revert don't have a recovery entry so we will never get here with them
+        UniversalSwapFallback::RefundToRecipient | UniversalSwapFallback::Revert => {
+            // The swap failed so we send the funds to the original recipient of the transfer
+            response = response.add_message(BankMsg::Send {
+                to_address: pending_universal_swap.recipient.to_string(),
+                amount: vec![pending_universal_swap.coin.clone()],
+            });
+        }
+    }
+
+    Ok(response.add_event(
+        Event::new("refund_universal_swap")
+            .add_attribute("nonce", nonce.to_string())
+            .add_attribute("tx_hash", pending_universal_swap.tx_hash)
+            .add_attribute("recipient", pending_universal_swap.recipient)
+            .add_attribute("amount", pending_universal_swap.coin.to_string())
+            .add_attribute("fallback", pending_universal_swap.fallback.as_str())
+            .add_attribute("error", error),
+    ))
+}
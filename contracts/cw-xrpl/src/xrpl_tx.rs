@@ -0,0 +1,400 @@
+use cosmwasm_std::{HexBinary, Uint128};
+use sha2::{Digest, Sha512};
+
+use crate::{
+    address::validate_xrpl_address_format,
+    contract::XRPL_TOKENS_DECIMALS,
+    error::ContractError,
+    operation::{Operation, OperationType},
+    relayer::Relayer,
+    token::is_token_xrp,
+};
+use bs58::Alphabet;
+
+// Prefix that XRPL adds to the transaction before signing it for multisigning ("SMT\0")
+// See https://xrpl.org/multi-signing.html
+const MULTISIGNING_PREFIX: [u8; 4] = [0x53, 0x4D, 0x54, 0x00];
+
+// Transaction types (https://xrpl.org/transaction-types.html)
+const PAYMENT: u16 = 0;
+const TICKET_CREATE: u16 = 10;
+const SIGNER_LIST_SET: u16 = 12;
+const TRUST_SET: u16 = 20;
+
+// Flags
+const TF_SET_NO_RIPPLE: u32 = 0x00020000;
+
+// Type codes of the binary codec (https://xrpl.org/serialization.html#type-list)
+const TYPE_UINT16: u8 = 1;
+const TYPE_UINT32: u8 = 2;
+const TYPE_AMOUNT: u8 = 6;
+const TYPE_BLOB: u8 = 7;
+const TYPE_ACCOUNT: u8 = 8;
+const TYPE_OBJECT: u8 = 14;
+const TYPE_ARRAY: u8 = 15;
+
+// Fields used in our transactions as (type code, field code)
+const TRANSACTION_TYPE: (u8, u8) = (TYPE_UINT16, 2);
+const SIGNER_WEIGHT: (u8, u8) = (TYPE_UINT16, 3);
+const FLAGS: (u8, u8) = (TYPE_UINT32, 2);
+const SEQUENCE: (u8, u8) = (TYPE_UINT32, 4);
+const DESTINATION_TAG: (u8, u8) = (TYPE_UINT32, 14);
+const SIGNER_QUORUM: (u8, u8) = (TYPE_UINT32, 35);
+const TICKET_COUNT: (u8, u8) = (TYPE_UINT32, 40);
+const TICKET_SEQUENCE: (u8, u8) = (TYPE_UINT32, 41);
+const AMOUNT: (u8, u8) = (TYPE_AMOUNT, 1);
+const LIMIT_AMOUNT: (u8, u8) = (TYPE_AMOUNT, 3);
+const FEE: (u8, u8) = (TYPE_AMOUNT, 8);
+const SEND_MAX: (u8, u8) = (TYPE_AMOUNT, 9);
+const SIGNING_PUB_KEY: (u8, u8) = (TYPE_BLOB, 3);
+const TXN_SIGNATURE: (u8, u8) = (TYPE_BLOB, 4);
+const ACCOUNT: (u8, u8) = (TYPE_ACCOUNT, 1);
+const DESTINATION: (u8, u8) = (TYPE_ACCOUNT, 3);
+const OBJECT_END_MARKER: (u8, u8) = (TYPE_OBJECT, 1);
+const SIGNER_ENTRY: (u8, u8) = (TYPE_OBJECT, 11);
+const SIGNER: (u8, u8) = (TYPE_OBJECT, 16);
+const ARRAY_END_MARKER: (u8, u8) = (TYPE_ARRAY, 1);
+const SIGNERS: (u8, u8) = (TYPE_ARRAY, 3);
+const SIGNER_ENTRIES: (u8, u8) = (TYPE_ARRAY, 4);
+
+// Limits of the mantissa and exponent of issued currency amounts
+const MIN_MANTISSA: u128 = 1_000_000_000_000_000;
+const MAX_MANTISSA: u128 = 9_999_999_999_999_999;
+const MIN_EXPONENT: i32 = -96;
+const MAX_EXPONENT: i32 = 80;
+
+// Signature of a relayer that goes into the Signers field of a multisigned transaction
+pub struct TransactionSigner {
+    pub account: String,
+    pub signing_pub_key: String,
+    pub txn_signature: String,
+}
+
+// Value of a field of the transaction, already serialized
+struct Field {
+    id: (u8, u8),
+    value: Vec<u8>,
+}
+
+// Builds the bytes that each relayer signs to multisign the XRPL transaction of an operation:
+// multisigning prefix + transaction (signing fields) + account ID of the signer
+pub fn multisigning_data(
+    operation: &Operation,
+    bridge_xrpl_address: &str,
+    number_of_signers: u32,
+    signer_xrpl_address: &str,
+) -> Result<Vec<u8>, ContractError> {
+    let mut data = MULTISIGNING_PREFIX.to_vec();
+    data.extend(serialize_operation(
+        operation,
+        bridge_xrpl_address,
+        number_of_signers,
+    )?);
+    data.extend(decode_account_id(signer_xrpl_address)?);
+    Ok(data)
+}
+
+// Serializes the XRPL transaction of an operation with the XRPL binary codec. Only signing fields are included and
+// SigningPubKey is empty because the transaction is multisigned.
+pub fn serialize_operation(
+    operation: &Operation,
+    bridge_xrpl_address: &str,
+    number_of_signers: u32,
+) -> Result<Vec<u8>, ContractError> {
+    Ok(serialize_fields(operation_fields(
+        operation,
+        bridge_xrpl_address,
+        number_of_signers,
+    )?))
+}
+
+// Serializes the multisigned XRPL transaction of an operation, ready to be submitted.
+// XRPL requires the signers to be sorted by account ID
+// number_of_signers must be the same one used for the signing data because it determines the fee
+pub fn serialize_signed_operation(
+    operation: &Operation,
+    bridge_xrpl_address: &str,
+    number_of_signers: u32,
+    signers: &[TransactionSigner],
+) -> Result<Vec<u8>, ContractError> {
+    let mut fields = operation_fields(operation, bridge_xrpl_address, number_of_signers)?;
+
+    let mut signers = signers
+        .iter()
+        .map(|signer| Ok((decode_account_id(&signer.account)?, signer)))
+        .collect::<Result<Vec<_>, ContractError>>()?;
+    signers.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut data = vec![];
+    for (account_id, signer) in signers {
+        data.extend(encode_field_id(SIGNER));
+        data.extend(serialize_fields(vec![
+            field(ACCOUNT, encode_blob(&account_id)?),
+            field(SIGNING_PUB_KEY, encode_hex_blob(&signer.signing_pub_key)?),
+            field(TXN_SIGNATURE, encode_hex_blob(&signer.txn_signature)?),
+        ]));
+        data.extend(encode_field_id(OBJECT_END_MARKER));
+    }
+    data.extend(encode_field_id(ARRAY_END_MARKER));
+    fields.push(field(SIGNERS, data));
+
+    Ok(serialize_fields(fields))
+}
+
+fn operation_fields(
+    operation: &Operation,
+    bridge_xrpl_address: &str,
+    number_of_signers: u32,
+) -> Result<Vec<Field>, ContractError> {
+    // Multisigned transactions cost the base fee once for the transaction and once for each signature
+    let fee = operation
+        .xrpl_base_fee
+        .checked_mul(u64::from(number_of_signers) + 1)
+        .ok_or(ContractError::InvalidXRPLTransaction {})?;
+
+    // Operations use either a ticket or the account sequence
+    let (sequence, ticket_sequence) = match (operation.ticket_sequence, operation.account_sequence)
+    {
+        (Some(ticket_sequence), None) => (0, Some(to_u32(ticket_sequence)?)),
+        (None, Some(account_sequence)) => (to_u32(account_sequence)?, None),
+        _ => return Err(ContractError::InvalidXRPLTransaction {}),
+    };
+
+    let mut fields = vec![
+        field(ACCOUNT, encode_account(bridge_xrpl_address)?),
+        field(FEE, encode_xrp_amount(Uint128::from(fee))?),
+        field(SEQUENCE, sequence.to_be_bytes().to_vec()),
+        field(SIGNING_PUB_KEY, encode_blob(&[])?),
+    ];
+    if let Some(ticket_sequence) = ticket_sequence {
+        fields.push(field(
+            TICKET_SEQUENCE,
+            ticket_sequence.to_be_bytes().to_vec(),
+        ));
+    }
+
+    match &operation.operation_type {
+        OperationType::AllocateTickets { number } => {
+            fields.push(transaction_type(TICKET_CREATE));
+            fields.push(flags(0));
+            fields.push(field(TICKET_COUNT, number.to_be_bytes().to_vec()));
+        }
+        OperationType::TrustSet {
+            issuer,
+            currency,
+            trust_set_limit_amount,
+        } => {
+            fields.push(transaction_type(TRUST_SET));
+            fields.push(flags(TF_SET_NO_RIPPLE));
+            fields.push(field(
+                LIMIT_AMOUNT,
+                encode_issued_amount(*trust_set_limit_amount, currency, issuer)?,
+            ));
+        }
+        OperationType::RotateKeys {
+            new_relayers,
+            new_evidence_threshold,
+        } => {
+            fields.push(transaction_type(SIGNER_LIST_SET));
+            fields.push(flags(0));
+            fields.push(field(
+                SIGNER_QUORUM,
+                new_evidence_threshold.to_be_bytes().to_vec(),
+            ));
+            fields.push(field(SIGNER_ENTRIES, encode_signer_entries(new_relayers)?));
+        }
+        OperationType::CosmosToXRPLTransfer {
+            issuer,
+            currency,
+            amount,
+            max_amount,
+            recipient,
+            destination_tag,
+            ..
+        } => {
+            fields.push(transaction_type(PAYMENT));
+            fields.push(flags(0));
+            fields.push(field(DESTINATION, encode_account(recipient)?));
+            if let Some(destination_tag) = destination_tag {
+                fields.push(field(
+                    DESTINATION_TAG,
+                    destination_tag.to_be_bytes().to_vec(),
+                ));
+            }
+            if is_token_xrp(issuer, currency) {
+                fields.push(field(AMOUNT, encode_xrp_amount(*amount)?));
+            } else {
+                fields.push(field(
+                    AMOUNT,
+                    encode_issued_amount(*amount, currency, issuer)?,
+                ));
+                // The max amount is what the bridge is willing to spend when the token has a transfer rate
+                if let Some(max_amount) = max_amount {
+                    fields.push(field(
+                        SEND_MAX,
+                        encode_issued_amount(*max_amount, currency, issuer)?,
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(fields)
+}
+
+// SHA-512 truncated to 32 bytes, the hash that XRPL uses for signing with secp256k1
+pub fn sha512_half(data: &[u8]) -> Vec<u8> {
+    Sha512::digest(data)[..32].to_vec()
+}
+
+// XRPL represents binary data as uppercase hexadecimal
+pub fn to_hex(data: &[u8]) -> String {
+    HexBinary::from(data).to_hex().to_uppercase()
+}
+
+fn field(id: (u8, u8), value: Vec<u8>) -> Field {
+    Field { id, value }
+}
+
+fn transaction_type(transaction_type: u16) -> Field {
+    field(TRANSACTION_TYPE, transaction_type.to_be_bytes().to_vec())
+}
+
+fn flags(flags: u32) -> Field {
+    field(FLAGS, flags.to_be_bytes().to_vec())
+}
+
+// Fields are serialized in canonical order: sorted by type code and then by field code
+fn serialize_fields(mut fields: Vec<Field>) -> Vec<u8> {
+    fields.sort_by_key(|f| f.id);
+
+    let mut data = vec![];
+    for Field { id, value } in fields {
+        data.extend(encode_field_id(id));
+        data.extend(value);
+    }
+    data
+}
+
+fn encode_field_id((type_code, field_code): (u8, u8)) -> Vec<u8> {
+    match (type_code < 16, field_code < 16) {
+        (true, true) => vec![type_code << 4 | field_code],
+        (true, false) => vec![type_code << 4, field_code],
+        (false, true) => vec![field_code, type_code],
+        (false, false) => vec![0, type_code, field_code],
+    }
+}
+
+// Variable length prefix of blobs and accounts. We never serialize anything longer than 192 bytes
+fn encode_blob(data: &[u8]) -> Result<Vec<u8>, ContractError> {
+    if data.len() > 192 {
+        return Err(ContractError::InvalidXRPLTransaction {});
+    }
+    let mut encoded = vec![data.len() as u8];
+    encoded.extend_from_slice(data);
+    Ok(encoded)
+}
+
+fn encode_hex_blob(data: &str) -> Result<Vec<u8>, ContractError> {
+    let data = HexBinary::from_hex(data).map_err(|_| ContractError::InvalidXRPLTransaction {})?;
+    encode_blob(&data)
+}
+
+fn encode_account(address: &str) -> Result<Vec<u8>, ContractError> {
+    encode_blob(&decode_account_id(address)?)
+}
+
+// Returns the 20 bytes account ID of a classic address
+pub fn decode_account_id(address: &str) -> Result<Vec<u8>, ContractError> {
+    validate_xrpl_address_format(address)?;
+    let data = bs58::decode(address)
+        .with_alphabet(Alphabet::RIPPLE)
+        .into_vec()
+        .map_err(|_| ContractError::InvalidXRPLAddress {
+            address: address.to_string(),
+        })?;
+    Ok(data[1..21].to_vec())
+}
+
+// XRP amounts are 64 bits: bit 63 is 0 (native), bit 62 is 1 (positive) and the rest are the drops
+fn encode_xrp_amount(drops: Uint128) -> Result<Vec<u8>, ContractError> {
+    let drops = u64::try_from(drops.u128())
+        .ok()
+        .filter(|drops| *drops < 1 << 62)
+        .ok_or(ContractError::InvalidXRPLTransaction {})?;
+    Ok((drops | 1 << 62).to_be_bytes().to_vec())
+}
+
+// Issued currency amounts are 64 bits for the value followed by the currency (20 bytes) and the issuer (20 bytes).
+// Value: bit 63 is 1 (not native), bit 62 is the sign, 8 bits for the exponent (+97) and 54 bits for the normalized mantissa
+// Amounts of issued currencies in the bridge always have XRPL_TOKENS_DECIMALS decimals
+fn encode_issued_amount(
+    amount: Uint128,
+    currency: &str,
+    issuer: &str,
+) -> Result<Vec<u8>, ContractError> {
+    let value = if amount.is_zero() {
+        1u64 << 63
+    } else {
+        let mut mantissa = amount.u128();
+        let mut exponent = -(XRPL_TOKENS_DECIMALS as i32);
+        while mantissa > MAX_MANTISSA {
+            // Amounts were validated to not have more precision than XRPL allows
+            if mantissa % 10 != 0 {
+                return Err(ContractError::InvalidXRPLTransaction {});
+            }
+            mantissa /= 10;
+            exponent += 1;
+        }
+        while mantissa < MIN_MANTISSA {
+            mantissa *= 10;
+            exponent -= 1;
+        }
+        if !(MIN_EXPONENT..=MAX_EXPONENT).contains(&exponent) {
+            return Err(ContractError::InvalidXRPLTransaction {});
+        }
+        1u64 << 63 | 1u64 << 62 | ((exponent + 97) as u64) << 54 | mantissa as u64
+    };
+
+    let mut encoded = value.to_be_bytes().to_vec();
+    encoded.extend(encode_currency(currency)?);
+    encoded.extend(decode_account_id(issuer)?);
+    Ok(encoded)
+}
+
+// Currencies are either 3 character codes (placed in bytes 12 to 14) or 40 hexadecimal characters
+fn encode_currency(currency: &str) -> Result<Vec<u8>, ContractError> {
+    if currency.len() == 3 {
+        let mut encoded = vec![0u8; 20];
+        encoded[12..15].copy_from_slice(currency.as_bytes());
+        return Ok(encoded);
+    }
+
+    HexBinary::from_hex(currency)
+        .ok()
+        .filter(|currency| currency.len() == 20)
+        .map(|currency| currency.to_vec())
+        .ok_or(ContractError::InvalidXRPLCurrency {})
+}
+
+// The signer list mirrors the weights of the relayers, so the quorum is the evidence threshold
+fn encode_signer_entries(relayers: &[Relayer]) -> Result<Vec<u8>, ContractError> {
+    let mut data = vec![];
+    for relayer in relayers {
+        data.extend(encode_field_id(SIGNER_ENTRY));
+        data.extend(serialize_fields(vec![
+            field(ACCOUNT, encode_account(&relayer.xrpl_address)?),
+            field(
+                SIGNER_WEIGHT,
+                relayer.signer_weight().to_be_bytes().to_vec(),
+            ),
+        ]));
+        data.extend(encode_field_id(OBJECT_END_MARKER));
+    }
+    data.extend(encode_field_id(ARRAY_END_MARKER));
+    Ok(data)
+}
+
+fn to_u32(sequence: u64) -> Result<u32, ContractError> {
+    u32::try_from(sequence).map_err(|_| ContractError::InvalidXRPLTransaction {})
+}